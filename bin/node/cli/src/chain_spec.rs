@@ -33,7 +33,7 @@ use sp_runtime::{
 // --- hyperspace ---
 use hyperspace_claims::ClaimsList;
 use hyperspace_ethereum_relay::DagsMerkleRootsLoader as DagsMerkleRootsLoaderR;
-use hyperspace_evm::GenesisAccount;
+use hyperspace_evm::{geth::GethAlloc, GenesisAccount};
 use hyperspace_primitives::*;
 use hyperspace_runtime::{constants::COIN, BalancesConfig as EtpConfig, *};
 
@@ -104,12 +104,12 @@ pub fn get_authority_keys_from_seed(
 	)
 }
 
-pub fn hyperspace_build_spec_config() -> HyperspaceChainSpec {
+pub fn hyperspace_build_spec_config(evm_alloc: GethAlloc) -> HyperspaceChainSpec {
 	HyperspaceChainSpec::from_genesis(
 		"Hyperspace",
 		"hyperspace",
 		ChainType::Live,
-		hyperspace_build_spec_genesis,
+		move || hyperspace_build_spec_genesis(evm_alloc.clone()),
 		vec![],
 		Some(
 			TelemetryEndpoints::new(vec![(PANGOLIN_TELEMETRY_URL.to_string(), 0)])
@@ -121,7 +121,7 @@ pub fn hyperspace_build_spec_config() -> HyperspaceChainSpec {
 	)
 }
 
-fn hyperspace_build_spec_genesis() -> GenesisConfig {
+fn hyperspace_build_spec_genesis(evm_alloc: GethAlloc) -> GenesisConfig {
 	const ROOT: &'static str = "0x72819fbc1b93196fa230243947c1726cbea7e33044c7eb6f736ff345561f9e4c";
 	const GENESIS_VALIDATOR: &'static str = "Alice";
 	const GENESIS_VALIDATOR_STASH: &'static str = "Alice//stash";
@@ -157,6 +157,7 @@ fn hyperspace_build_spec_genesis() -> GenesisConfig {
 			code: vec![],
 		},
 	);
+	evm_accounts.extend(evm_alloc.into_genesis_accounts());
 
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
@@ -236,12 +237,12 @@ fn hyperspace_build_spec_genesis() -> GenesisConfig {
 	}
 }
 
-pub fn hyperspace_development_config() -> HyperspaceChainSpec {
+pub fn hyperspace_development_config(evm_alloc: GethAlloc) -> HyperspaceChainSpec {
 	HyperspaceChainSpec::from_genesis(
 		"Development",
 		"hyperspace_dev",
 		ChainType::Development,
-		|| {
+		move || {
 			let initial_evm_account = vec![
 				array_bytes::hex2array_unchecked!("0x6be02d1d3665660d22ff9624b7be0551ee1ac91b", 20)
 					.into(),
@@ -261,6 +262,7 @@ pub fn hyperspace_development_config() -> HyperspaceChainSpec {
 					},
 				);
 			}
+			evm_accounts.extend(evm_alloc.clone().into_genesis_accounts());
 
 			hyperspace_development_genesis(
 				vec![get_authority_keys_from_seed("Alice")],
@@ -16,10 +16,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
 
+// --- std ---
+use std::{path::PathBuf, str::FromStr};
 // --- crates ---
 use structopt::StructOpt;
 // --- substrate ---
-use sc_cli::{KeySubcommand, SignCmd, VanityCmd, VerifyCmd};
+use sc_cli::{
+	BlockNumberOrHash, CliConfiguration, KeySubcommand, PruningParams, SharedParams, SignCmd,
+	VanityCmd, VerifyCmd,
+};
 
 /// An overarching CLI command definition.
 #[derive(Debug, StructOpt)]
@@ -34,7 +39,12 @@ pub struct Cli {
 
 	/// Load the boot configuration json file from <PATH>. Command line input will be overwritten by this.
 	#[structopt(long = "conf", value_name = "PATH")]
-	pub conf: Option<std::path::PathBuf>,
+	pub conf: Option<PathBuf>,
+
+	/// Add the accounts of a geth genesis or `alloc` JSON file at <PATH> to the EVM genesis of
+	/// the built-in chain specifications.
+	#[structopt(long = "evm-alloc", value_name = "PATH")]
+	pub evm_alloc: Option<PathBuf>,
 }
 
 #[allow(missing_docs)]
@@ -61,6 +71,57 @@ pub struct RunCmd {
 	///     to a subset.
 	#[structopt(long = "disable-authority-discovery")]
 	pub authority_discovery_disabled: bool,
+
+	/// Replace BABE and GRANDPA with manual sealing, for local EVM development.
+	///
+	/// `instant` seals a block as soon as a transaction enters the pool, `manual` only seals
+	/// on `engine_createBlock` and a number seals a block every that many milliseconds.
+	/// Blocks can always be sealed and finalized through the `engine_*` RPC.
+	#[structopt(long = "sealing", value_name = "instant|manual|MS")]
+	pub sealing: Option<Sealing>,
+
+	/// Number of blocks whose Ethereum block, receipts and transaction statuses are kept in
+	/// memory for the `eth_*` RPC, zero disables the cache.
+	#[structopt(long = "eth-block-cache", value_name = "BLOCKS", default_value = "64")]
+	pub eth_block_cache: usize,
+
+	/// Maximum number of calls in a JSON-RPC batch sent to the HTTP and WS servers of a full
+	/// node, larger batches are rejected as a whole.
+	#[structopt(
+		long = "rpc-max-batch-size",
+		value_name = "CALLS",
+		default_value = "256"
+	)]
+	pub rpc_max_batch_size: usize,
+}
+
+/// Block production used in place of BABE and GRANDPA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block whenever a transaction is imported into the pool.
+	Instant,
+	/// Seal a block only when requested through the RPC.
+	Manual,
+	/// Seal a block every given milliseconds.
+	Interval(u64),
+}
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			s => match s.parse() {
+				Ok(0) => Err("sealing interval must be greater than zero".into()),
+				Ok(millis) => Ok(Sealing::Interval(millis)),
+				Err(_) => Err(format!(
+					"invalid sealing `{}`, expected `instant`, `manual` or milliseconds",
+					s
+				)),
+			},
+		}
+	}
 }
 
 /// Possible subcommands of the main binary.
@@ -98,4 +159,43 @@ pub enum Subcommand {
 
 	/// Sign a message, with a given (secret) key.
 	Sign(SignCmd),
+
+	/// EVM state utilities.
+	Evm(EvmSubcommand),
+}
+
+/// EVM state utilities.
+#[derive(Debug, StructOpt)]
+pub enum EvmSubcommand {
+	/// Export the EVM accounts of a given block in the geth genesis `alloc` format.
+	ExportState(EvmExportStateCmd),
+}
+
+/// The `evm export-state` command.
+#[derive(Debug, StructOpt)]
+pub struct EvmExportStateCmd {
+	/// Block hash or number to export the state of, the best block if omitted.
+	#[structopt(long = "at", value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// Write the `alloc` to <PATH> instead of stdout.
+	#[structopt(long = "output", value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+impl CliConfiguration for EvmExportStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
 }
@@ -23,10 +23,11 @@ use sc_cli::{Role, RunCmd, RuntimeVersion, SubstrateCli};
 // --- hyperspace ---
 use crate::{
 	chain_spec,
-	cli::{Cli, Subcommand},
+	cli::{Cli, EvmSubcommand, Subcommand},
 	service,
 };
 use hyperspace_cli::{Configuration, HyperspaceCli};
+use hyperspace_evm::geth::GethAlloc;
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> String {
@@ -73,10 +74,17 @@ impl SubstrateCli for Cli {
 			id
 		};
 
+		let evm_alloc = match &self.evm_alloc {
+			Some(path) => GethAlloc::from_file(path)?,
+			None => GethAlloc::default(),
+		};
+
 		Ok(match id {
 			"hyperspace" => Box::new(chain_spec::hyperspace_config()?),
-			"hyperspace-dev" | "dev" => Box::new(chain_spec::hyperspace_development_config()),
-			"hyperspace-genesis" => Box::new(chain_spec::hyperspace_build_spec_config()),
+			"hyperspace-dev" | "dev" => {
+				Box::new(chain_spec::hyperspace_development_config(evm_alloc))
+			}
+			"hyperspace-genesis" => Box::new(chain_spec::hyperspace_build_spec_config(evm_alloc)),
 			path => Box::new(chain_spec::HyperspaceChainSpec::from_json_file(
 				PathBuf::from(path),
 			)?),
@@ -111,15 +119,23 @@ pub fn run() -> sc_cli::Result<()> {
 	match &cli.subcommand {
 		None => {
 			let authority_discovery_disabled = cli.run.authority_discovery_disabled;
+			let sealing = cli.run.sealing;
+			let eth_block_cache = cli.run.eth_block_cache;
+			let rpc_max_batch_size = cli.run.rpc_max_batch_size;
 			let runner = Configuration::create_runner(cli)?;
 
 			runner.run_node_until_exit(|config| async move {
 				match config.role {
-					Role::Light => {
-						service::hyperspace_new_light(config).map(|(task_manager, _, _)| task_manager)
-					}
-					_ => service::hyperspace_new_full(config, authority_discovery_disabled)
+					Role::Light => service::hyperspace_new_light(config)
 						.map(|(task_manager, _, _)| task_manager),
+					_ => service::hyperspace_new_full(
+						config,
+						authority_discovery_disabled,
+						sealing,
+						eth_block_cache,
+						rpc_max_batch_size,
+					)
+					.map(|(task_manager, _, _)| task_manager),
 				}
 				.map_err(sc_cli::Error::Service)
 			})
@@ -191,5 +207,16 @@ pub fn run() -> sc_cli::Result<()> {
 		Some(Subcommand::Sign(cmd)) => cmd.run(),
 		Some(Subcommand::Verify(cmd)) => cmd.run(),
 		Some(Subcommand::Vanity(cmd)) => cmd.run(),
+		Some(Subcommand::Evm(EvmSubcommand::ExportState(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+
+			runner.async_run(|mut config| {
+				let (client, _, _, task_manager) = service::new_chain_ops::<
+					service::hyperspace_runtime::RuntimeApi,
+					service::HyperspaceExecutor,
+				>(&mut config)?;
+				Ok((cmd.run(client), task_manager))
+			})
+		}
 	}
 }
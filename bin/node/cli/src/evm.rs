@@ -0,0 +1,132 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+// --- std ---
+use std::{collections::BTreeMap, convert::TryInto, fmt::Debug, str::FromStr, sync::Arc};
+// --- crates ---
+use codec::Decode;
+// --- substrate ---
+use sc_client_api::{Backend, StorageProvider};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey, H160, H256};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, NumberFor},
+	AccountId32,
+};
+// --- hyperspace ---
+use crate::cli::EvmExportStateCmd;
+use dvm_rpc_runtime_api::EthereumRuntimeRPCApi;
+use hyperspace_evm::{
+	geth::{GethAccount, GethAlloc},
+	ConcatAddressMapping,
+};
+
+// The keys are the storage prefix, then the `blake2_128_concat` hashed keys.
+const PREFIX_LEN: usize = 32;
+const HASHED_ADDRESS_END: usize = PREFIX_LEN + 16 + 20;
+const HASHED_INDEX_END: usize = HASHED_ADDRESS_END + 16 + 32;
+const HASHED_ACCOUNT_ID_END: usize = PREFIX_LEN + 16 + 32;
+
+impl EvmExportStateCmd {
+	/// Export the EVM accounts in the geth genesis `alloc` format.
+	///
+	/// The accounts are the contracts with code or storage, and the Substrate accounts mapped
+	/// from an EVM address.
+	pub async fn run<B, BE, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		B::Hash: FromStr,
+		<B::Hash as FromStr>::Err: Debug,
+		NumberFor<B>: FromStr,
+		<NumberFor<B> as FromStr>::Err: Debug,
+		BE: Backend<B>,
+		C: StorageProvider<B, BE> + HeaderBackend<B> + ProvideRuntimeApi<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+	{
+		let id = match &self.at {
+			Some(at) => at.parse::<B>().map_err(sc_cli::Error::Input)?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		let prefix = |module: &[u8], storage: &[u8]| {
+			StorageKey([twox_128(module), twox_128(storage)].concat())
+		};
+		let mut alloc = BTreeMap::<H160, GethAccount>::new();
+
+		for (key, value) in client.storage_pairs(&id, &prefix(b"HyperspaceEVM", b"AccountCodes"))? {
+			if key.0.len() == HASHED_ADDRESS_END {
+				let address = H160::from_slice(&key.0[HASHED_ADDRESS_END - 20..]);
+				let code = Vec::<u8>::decode(&mut &value.0[..])
+					.map_err(|e| format!("invalid code of {:?}: {:?}", address, e))?;
+
+				alloc.entry(address).or_default().code = code;
+			}
+		}
+		for (key, value) in
+			client.storage_pairs(&id, &prefix(b"HyperspaceEVM", b"AccountStorages"))?
+		{
+			if key.0.len() == HASHED_INDEX_END && value.0.len() == 32 {
+				let address = H160::from_slice(&key.0[HASHED_ADDRESS_END - 20..HASHED_ADDRESS_END]);
+				let index = H256::from_slice(&key.0[HASHED_INDEX_END - 32..]);
+
+				alloc
+					.entry(address)
+					.or_default()
+					.storage
+					.insert(index, H256::from_slice(&value.0));
+			}
+		}
+		for key in client.storage_keys(&id, &prefix(b"System", b"Account"))? {
+			let account_id: Option<[u8; 32]> = key
+				.0
+				.get(HASHED_ACCOUNT_ID_END - 32..)
+				.and_then(|raw| raw.try_into().ok());
+
+			if let Some(address) = account_id
+				.and_then(|raw| ConcatAddressMapping::into_address(&AccountId32::from(raw)))
+			{
+				alloc.entry(address).or_default();
+			}
+		}
+
+		for (address, account) in alloc.iter_mut() {
+			let basic = client
+				.runtime_api()
+				.account_basic(&id, *address)
+				.map_err(|e| format!("failed to fetch the account of {:?}: {:?}", address, e))?;
+
+			account.balance = basic.balance;
+			account.nonce = basic.nonce;
+		}
+		let alloc = GethAlloc(
+			alloc
+				.into_iter()
+				.filter(|(_, account)| *account != GethAccount::default())
+				.collect(),
+		);
+
+		match &self.output {
+			Some(path) => alloc.to_file(path)?,
+			None => serde_json::to_writer_pretty(std::io::stdout(), &alloc)
+				.map_err(|e| e.to_string())?,
+		}
+
+		Ok(())
+	}
+}
@@ -24,6 +24,7 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod evm;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
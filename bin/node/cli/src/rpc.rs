@@ -36,12 +36,74 @@ pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
 
 // --- std ---
 use std::sync::Arc;
+// --- crates ---
+use jsonrpc_core::{
+	futures::future::{self, Either, Future},
+	middleware::{Middleware, NoopCallFuture, NoopFuture},
+	Error as RpcError, ErrorCode, Metadata, Request, Response, Version,
+};
 // --- hyperspace ---
 use hyperspace_primitives::{AccountId, Balance, BlockNumber, Hash, Nonce, OpaqueBlock as Block, Power};
 
 /// A type representing all RPC extensions.
 pub type RpcExtension = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
+/// The RPC handler served by the HTTP and WS servers of a full node.
+pub type RpcHandler = jsonrpc_pubsub::PubSubHandler<sc_rpc::Metadata, BatchSizeLimit>;
+
+/// Default maximum number of calls in a JSON-RPC batch.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 256;
+
+/// Middleware rejecting the JSON-RPC batches holding more than `max` calls.
+///
+/// The calls of a batch are answered in a single response, so one request asking for the receipts
+/// of a long range of blocks could keep the node busy for a long time.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchSizeLimit {
+	max: usize,
+}
+impl BatchSizeLimit {
+	/// Reject the batches holding more than `max` calls.
+	pub fn new(max: usize) -> Self {
+		Self { max }
+	}
+}
+impl Default for BatchSizeLimit {
+	fn default() -> Self {
+		Self::new(DEFAULT_MAX_BATCH_SIZE)
+	}
+}
+impl<M: Metadata> Middleware<M> for BatchSizeLimit {
+	type Future = NoopFuture;
+	type CallFuture = NoopCallFuture;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: Fn(Request, M) -> X + Send + Sync,
+		X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+	{
+		match request {
+			Request::Batch(calls) if calls.len() > self.max => {
+				let error = RpcError {
+					code: ErrorCode::InvalidRequest,
+					message: format!(
+						"batch of {} calls exceeds the limit of {}",
+						calls.len(),
+						self.max
+					),
+					data: None,
+				};
+
+				Either::A(Box::new(future::ok(Some(Response::from(
+					error,
+					Some(Version::V2),
+				)))))
+			}
+			request => Either::B(next(request, meta)),
+		}
+	}
+}
+
 /// Extra dependencies for BABE.
 pub struct BabeDeps {
 	/// BABE protocol config.
@@ -71,6 +133,8 @@ pub struct GrandpaDeps<B> {
 pub struct FullDeps<C, P, SC, B> {
 	/// The client instance to use.
 	pub client: Arc<C>,
+	/// The backend, its off-chain indexing storage keeps the header-MMR nodes.
+	pub backend: Arc<B>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
 	/// The SelectChain Strategy
@@ -87,6 +151,13 @@ pub struct FullDeps<C, P, SC, B> {
 	pub babe: BabeDeps,
 	/// GRANDPA specific dependencies.
 	pub grandpa: GrandpaDeps<B>,
+	/// Manual seal command sink, only present in the development sealing mode.
+	pub command_sink:
+		Option<futures::channel::mpsc::Sender<sc_consensus_manual_seal::EngineCommand<Hash>>>,
+	/// Ethereum block data cache shared by the Ethereum RPC handlers.
+	pub eth_block_data_cache: Arc<dvm_rpc::EthereumBlockDataCache<Block>>,
+	/// Ethereum RPC metrics, only present if Prometheus is enabled.
+	pub rpc_metrics: Option<dvm_rpc::RpcMetrics>,
 }
 
 /// Light client extra dependencies.
@@ -121,6 +192,7 @@ where
 	C::Api: sc_consensus_babe::BabeApi<Block>,
 	C::Api: sp_block_builder::BlockBuilder<Block>,
 	C::Api: hyperspace_balances_rpc::BalancesRuntimeApi<Block, AccountId, Balance>,
+	C::Api: hyperspace_evm_rpc::EVMRuntimeApi<Block, Balance>,
 	C::Api: hyperspace_header_mmr_rpc::HeaderMMRRuntimeApi<Block, Hash>,
 	C::Api: hyperspace_staking_rpc::StakingRuntimeApi<Block, AccountId, Power>,
 	C::Api: dvm_rpc_runtime_api::EthereumRuntimeRPCApi<Block>,
@@ -135,21 +207,25 @@ where
 	// --- substrate ---
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use sc_consensus_babe_rpc::{BabeApi, BabeRpcHandler};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
 	use sc_finality_grandpa_rpc::{GrandpaApi, GrandpaRpcHandler};
 	use sc_sync_state_rpc::{SyncStateRpcApi, SyncStateRpcHandler};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	// --- hyperspace ---
 	use hyperspace_balances_rpc::{Balances, BalancesApi};
+	use hyperspace_evm_rpc::{EVMApi, EVM};
 	use hyperspace_header_mmr_rpc::{HeaderMMR, HeaderMMRApi};
 	use hyperspace_staking_rpc::{Staking, StakingApi};
+	use dvm_consensus::EthereumInstance;
 	use dvm_rpc::{
-		EthApi, EthApiServer, EthPubSubApi, EthPubSubApiServer, HexEncodedIdProvider, NetApi,
-		NetApiServer, Web3Api, Web3ApiServer,
+		with_metrics, EthApi, EthApiServer, EthPubSubApi, EthPubSubApiServer, HexEncodedIdProvider,
+		NetApi, NetApiServer, Web3Api, Web3ApiServer,
 	};
 	use hyperspace_runtime::TransactionConverter;
 
 	let FullDeps {
 		client,
+		backend,
 		pool,
 		select_chain,
 		chain_spec,
@@ -158,6 +234,9 @@ where
 		network,
 		babe,
 		grandpa,
+		command_sink,
+		eth_block_data_cache,
+		rpc_metrics,
 	} = deps;
 	let mut io = jsonrpc_core::IoHandler::default();
 
@@ -204,30 +283,48 @@ where
 		deny_unsafe,
 	)));
 	io.extend_with(BalancesApi::to_delegate(Balances::new(client.clone())));
-	io.extend_with(HeaderMMRApi::to_delegate(HeaderMMR::new(client.clone())));
-	io.extend_with(StakingApi::to_delegate(Staking::new(client.clone())));
-	io.extend_with(EthApiServer::to_delegate(EthApi::new(
-		client.clone(),
-		pool.clone(),
-		TransactionConverter,
-		network.clone(),
-		is_authority,
-	)));
-	io.extend_with(EthPubSubApiServer::to_delegate(EthPubSubApi::new(
-		pool,
+	io.extend_with(EVMApi::to_delegate(EVM::new(client.clone())));
+	io.extend_with(HeaderMMRApi::to_delegate(HeaderMMR::new(
 		client.clone(),
-		network.clone(),
-		SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
-			HexEncodedIdProvider::default(),
-			Arc::new(subscription_task_executor),
-		),
+		backend.offchain_storage(),
 	)));
+	io.extend_with(StakingApi::to_delegate(Staking::new(client.clone())));
+	io.extend_with(with_metrics(
+		EthApiServer::to_delegate(EthApi::new(
+			client.clone(),
+			pool.clone(),
+			TransactionConverter,
+			network.clone(),
+			EthereumInstance::default(),
+			eth_block_data_cache.clone(),
+			is_authority,
+		)),
+		rpc_metrics.as_ref(),
+	));
+	io.extend_with(with_metrics(
+		EthPubSubApiServer::to_delegate(EthPubSubApi::new(
+			pool,
+			client.clone(),
+			network.clone(),
+			SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
+				HexEncodedIdProvider::default(),
+				Arc::new(subscription_task_executor),
+			),
+			eth_block_data_cache,
+			rpc_metrics.clone(),
+		)),
+		rpc_metrics.as_ref(),
+	));
 	io.extend_with(NetApiServer::to_delegate(NetApi::new(
 		client.clone(),
 		network,
 	)));
 	io.extend_with(Web3ApiServer::to_delegate(Web3Api::new(client)));
 
+	if let Some(command_sink) = command_sink {
+		io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+	}
+
 	io
 }
 
@@ -261,3 +358,56 @@ where
 
 	io
 }
+
+#[cfg(test)]
+mod tests {
+	// --- crates ---
+	use jsonrpc_core::{MetaIoHandler, Value};
+	// --- hyperspace ---
+	use super::*;
+
+	fn handler(max: usize) -> MetaIoHandler<(), BatchSizeLimit> {
+		let mut io = MetaIoHandler::with_middleware(BatchSizeLimit::new(max));
+
+		io.add_method("ping", |_| Ok(Value::String("pong".into())));
+
+		io
+	}
+
+	fn batch(len: usize) -> String {
+		let calls = (0..len)
+			.map(|id| format!(r#"{{"jsonrpc":"2.0","method":"ping","id":{}}}"#, id))
+			.collect::<Vec<_>>();
+
+		format!("[{}]", calls.join(","))
+	}
+
+	#[test]
+	fn oversized_batch_should_be_rejected() {
+		let response: Value =
+			serde_json::from_str(&handler(2).handle_request_sync(&batch(3), ()).unwrap()).unwrap();
+
+		assert_eq!(response["error"]["code"], -32600);
+		assert_eq!(
+			response["error"]["message"],
+			"batch of 3 calls exceeds the limit of 2"
+		);
+	}
+
+	#[test]
+	fn batch_within_limit_should_be_served() {
+		let response: Value =
+			serde_json::from_str(&handler(2).handle_request_sync(&batch(2), ()).unwrap()).unwrap();
+
+		assert_eq!(response.as_array().unwrap().len(), 2);
+		assert!(response
+			.as_array()
+			.unwrap()
+			.iter()
+			.all(|response| response["result"] == "pong"));
+		assert!(handler(2)
+			.handle_request_sync(r#"{"jsonrpc":"2.0","method":"ping","id":0}"#, ())
+			.unwrap()
+			.contains("pong"));
+	}
+}
@@ -24,12 +24,23 @@ pub use sc_executor::NativeExecutor;
 pub use hyperspace_runtime;
 
 // --- std ---
-use std::{sync::Arc, time::Duration};
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+// --- crates ---
+use futures::{
+	channel::mpsc,
+	stream::{self, BoxStream},
+	StreamExt,
+};
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_pubsub::{manager::SubscriptionManager, PubSubHandler};
 // --- substrate ---
 use sc_basic_authorship::ProposerFactory;
-use sc_client_api::{ExecutorProvider, RemoteBackend, StateBackendFor};
+use sc_client_api::{Backend, ExecutorProvider, RemoteBackend, StateBackendFor};
 use sc_consensus::LongestChain;
 use sc_consensus_babe::{BabeBlockImport, BabeLink, BabeParams, Config as BabeConfig};
+use sc_consensus_manual_seal::{
+	consensus::babe::BabeConsensusDataProvider, EngineCommand, ManualSealParams,
+};
 use sc_executor::{native_executor_instance, NativeExecutionDispatch};
 use sc_finality_grandpa::{
 	Config as GrandpaConfig, FinalityProofProvider as GrandpaFinalityProofProvider, GrandpaParams,
@@ -39,7 +50,7 @@ use sc_finality_grandpa::{
 use sc_keystore::LocalKeystore;
 use sc_network::NetworkService;
 use sc_service::{
-	config::{KeystoreConfig, PrometheusConfig},
+	config::{KeystoreConfig, PrometheusConfig, RpcMethods},
 	BuildNetworkParams, Configuration, Error as ServiceError, NoopRpcExtensionBuilder,
 	PartialComponents, RpcHandlers, SpawnTasksParams, TaskManager,
 };
@@ -54,12 +65,14 @@ use sp_runtime::traits::BlakeTwo256;
 use sp_trie::PrefixedMemoryDB;
 use substrate_prometheus_endpoint::Registry;
 // --- hyperspace ---
+use crate::cli::Sealing;
 use crate::rpc::{
-	self, BabeDeps, DenyUnsafe, FullDeps, GrandpaDeps, LightDeps, RpcExtension,
-	SubscriptionTaskExecutor,
+	self, BabeDeps, BatchSizeLimit, DenyUnsafe, FullDeps, GrandpaDeps, LightDeps, RpcExtension,
+	RpcHandler, SubscriptionTaskExecutor,
 };
 use hyperspace_primitives::{AccountId, Balance, Hash, Nonce, OpaqueBlock as Block, Power};
-use dvm_consensus::FrontierBlockImport;
+use dvm_consensus::{EthereumInstance, FrontierBlockImport};
+use dvm_rpc::{EthereumBlockDataCache, RpcMetrics};
 
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
@@ -164,6 +177,8 @@ fn set_prometheus_registry(config: &mut Configuration) -> Result<(), ServiceErro
 #[cfg(feature = "full-node")]
 fn new_partial<RuntimeApi, Executor>(
 	config: &mut Configuration,
+	command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	eth_block_cache: usize,
 ) -> Result<
 	PartialComponents<
 		FullClient<RuntimeApi, Executor>,
@@ -193,6 +208,7 @@ fn new_partial<RuntimeApi, Executor>(
 			),
 			GrandpaSharedVoterState,
 			Option<TelemetrySpan>,
+			Arc<EthereumBlockDataCache<Block>>,
 		),
 	>,
 	ServiceError,
@@ -233,8 +249,12 @@ where
 			grandpa_hard_forks,
 		)?;
 	let justification_import = grandpa_block_import.clone();
-	let frontier_block_import =
-		FrontierBlockImport::new(grandpa_block_import.clone(), client.clone(), true);
+	let frontier_block_import = FrontierBlockImport::new(
+		grandpa_block_import.clone(),
+		client.clone(),
+		EthereumInstance::default(),
+		true,
+	);
 	let (babe_import, babe_link) = sc_consensus_babe::block_import(
 		BabeConfig::get_or_compute(&*client)?,
 		frontier_block_import,
@@ -263,8 +283,23 @@ where
 	let babe_config = babe_link.config().clone();
 	let shared_epoch_changes = babe_link.epoch_changes().clone();
 	let subscription_task_executor = SubscriptionTaskExecutor::new(task_manager.spawn_handle());
+	let eth_block_data_cache = Arc::new(EthereumBlockDataCache::new(
+		eth_block_cache,
+		config.prometheus_registry(),
+	));
+	let rpc_metrics = config
+		.prometheus_registry()
+		.and_then(|registry| match RpcMetrics::register(registry) {
+			Ok(metrics) => Some(metrics),
+			Err(e) => {
+				log::warn!("Failed to register the Ethereum RPC metrics: {:?}", e);
+
+				None
+			}
+		});
 	let rpc_extensions_builder = {
 		let client = client.clone();
+		let backend = backend.clone();
 		let keystore = keystore_container.sync_keystore();
 		let transaction_pool = transaction_pool.clone();
 		let select_chain = select_chain.clone();
@@ -273,6 +308,7 @@ where
 		move |deny_unsafe, is_authority, network, subscription_executor| -> RpcExtension {
 			let deps = FullDeps {
 				client: client.clone(),
+				backend: backend.clone(),
 				pool: transaction_pool.clone(),
 				select_chain: select_chain.clone(),
 				chain_spec: chain_spec.cloned_box(),
@@ -291,6 +327,9 @@ where
 					subscription_executor,
 					finality_provider: finality_proof_provider.clone(),
 				},
+				command_sink: command_sink.clone(),
+				eth_block_data_cache: eth_block_data_cache.clone(),
+				rpc_metrics: rpc_metrics.clone(),
 			};
 
 			rpc::create_full(deps, subscription_task_executor.clone())
@@ -311,6 +350,7 @@ where
 			import_setup,
 			rpc_setup,
 			telemetry_span,
+			eth_block_data_cache,
 		),
 	})
 }
@@ -322,10 +362,136 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
+#[cfg(feature = "full-node")]
+/// Maximum size of a JSON-RPC request or WS message, as in sc-rpc-server.
+const RPC_MAX_PAYLOAD: usize = 15 * 1024 * 1024;
+#[cfg(feature = "full-node")]
+/// Default maximum number of WS connections, as in sc-rpc-server.
+const RPC_WS_MAX_CONNECTIONS: usize = 100;
+
+#[cfg(feature = "full-node")]
+/// The HTTP and WS RPC servers of a full node.
+///
+/// sc-service takes no custom RPC middleware, so the node starts these servers itself, to limit the
+/// size of the JSON-RPC batches they serve.
+struct RpcServers {
+	http: Option<SocketAddr>,
+	ws: Option<SocketAddr>,
+	ws_max_connections: Option<usize>,
+	cors: Option<Vec<String>>,
+	methods: RpcMethods,
+}
+#[cfg(feature = "full-node")]
+impl RpcServers {
+	/// Take the HTTP and WS addresses out of `config`, so that sc-service doesn't start them.
+	fn take(config: &mut Configuration) -> Self {
+		Self {
+			http: config.rpc_http.take(),
+			ws: config.rpc_ws.take(),
+			ws_max_connections: config.rpc_ws_max_connections,
+			cors: config.rpc_cors.clone(),
+			methods: config.rpc_methods,
+		}
+	}
+
+	/// Start the servers, the returned handles must be kept alive.
+	fn start(
+		self,
+		mut gen_handler: impl FnMut(DenyUnsafe) -> RpcHandler,
+	) -> Result<
+		(
+			Option<jsonrpc_http_server::Server>,
+			Option<jsonrpc_ws_server::Server>,
+		),
+		io::Error,
+	> {
+		let Self {
+			http,
+			ws,
+			ws_max_connections,
+			cors,
+			methods,
+		} = self;
+		let cors = cors.as_ref();
+		let http = http
+			.map(|addr| {
+				jsonrpc_http_server::ServerBuilder::new(gen_handler(deny_unsafe(&addr, &methods)))
+					.threads(4)
+					.health_api(("/health", "system_health"))
+					.allowed_hosts(hosts_filtering(cors.is_some()))
+					.rest_api(if cors.is_some() {
+						jsonrpc_http_server::RestApi::Secure
+					} else {
+						jsonrpc_http_server::RestApi::Unsecure
+					})
+					.cors(map_cors(cors))
+					.max_request_body_size(RPC_MAX_PAYLOAD)
+					.start_http(&addr)
+			})
+			.transpose()?;
+		let ws = ws
+			.map(|addr| {
+				jsonrpc_ws_server::ServerBuilder::with_meta_extractor(
+					gen_handler(deny_unsafe(&addr, &methods)),
+					|context: &jsonrpc_ws_server::RequestContext| context.sender().into(),
+				)
+				.max_payload(RPC_MAX_PAYLOAD)
+				.max_connections(ws_max_connections.unwrap_or(RPC_WS_MAX_CONNECTIONS))
+				.allowed_origins(map_cors(cors))
+				.allowed_hosts(hosts_filtering(cors.is_some()))
+				.start(&addr)
+				.map_err(|e| match e {
+					jsonrpc_ws_server::Error::Io(e) => e,
+					e => io::Error::new(io::ErrorKind::Other, e.to_string()),
+				})
+			})
+			.transpose()?;
+
+		Ok((http, ws))
+	}
+}
+
+#[cfg(feature = "full-node")]
+/// Unsafe RPC calls are only served on the loopback interface, unless explicitly allowed.
+fn deny_unsafe(addr: &SocketAddr, methods: &RpcMethods) -> DenyUnsafe {
+	match (addr.ip().is_loopback(), methods) {
+		(_, RpcMethods::Unsafe) | (true, RpcMethods::Auto) => DenyUnsafe::No,
+		_ => DenyUnsafe::Yes,
+	}
+}
+
+#[cfg(feature = "full-node")]
+fn hosts_filtering(
+	enable: bool,
+) -> jsonrpc_http_server::DomainsValidation<jsonrpc_http_server::Host> {
+	if enable {
+		// An empty list only allows the listening address.
+		jsonrpc_http_server::DomainsValidation::AllowOnly(vec![])
+	} else {
+		jsonrpc_http_server::DomainsValidation::Disabled
+	}
+}
+
+#[cfg(feature = "full-node")]
+fn map_cors<T: for<'a> From<&'a str>>(
+	cors: Option<&Vec<String>>,
+) -> jsonrpc_http_server::DomainsValidation<T> {
+	cors.map(|cors| {
+		cors.iter()
+			.map(AsRef::as_ref)
+			.map(Into::into)
+			.collect::<Vec<_>>()
+	})
+	.into()
+}
+
 #[cfg(feature = "full-node")]
 fn new_full<RuntimeApi, Executor>(
 	mut config: Configuration,
 	authority_discovery_disabled: bool,
+	sealing: Option<Sealing>,
+	eth_block_cache: usize,
+	rpc_max_batch_size: usize,
 ) -> Result<
 	(
 		TaskManager,
@@ -348,6 +514,8 @@ where
 		Some(sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging::default());
 	let disable_grandpa = config.disable_grandpa;
 	let name = config.network.node_name.clone();
+	let manual_seal = sealing.map(|sealing| (sealing, mpsc::channel(1000)));
+	let command_sink = manual_seal.as_ref().map(|(_, (sink, _))| sink.clone());
 	let PartialComponents {
 		client,
 		backend,
@@ -357,8 +525,9 @@ where
 		import_queue,
 		transaction_pool,
 		inherent_data_providers,
-		other: (rpc_extensions_builder, import_setup, rpc_setup, telemetry_span),
-	} = new_partial::<RuntimeApi, Executor>(&mut config)?;
+		other:
+			(rpc_extensions_builder, import_setup, rpc_setup, telemetry_span, eth_block_data_cache),
+	} = new_partial::<RuntimeApi, Executor>(&mut config, command_sink, eth_block_cache)?;
 
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
@@ -410,6 +579,90 @@ where
 		);
 	}
 
+	let rpc_extensions_builder = Arc::new(rpc_extensions_builder);
+	let rpc_servers = RpcServers::take(&mut config);
+	// The handler of sc-service, behind the batch size limit.
+	let gen_rpc_handler = {
+		let system_info = sc_rpc::system::SystemInfo {
+			chain_name: config.chain_spec.name().into(),
+			impl_name: config.impl_name.clone(),
+			impl_version: config.impl_version.clone(),
+			properties: config.chain_spec.properties(),
+			chain_type: config.chain_spec.chain_type(),
+		};
+		let client = client.clone();
+		let transaction_pool = transaction_pool.clone();
+		let keystore = keystore_container.sync_keystore();
+		let offchain_storage = backend.offchain_storage();
+		let system_rpc_tx = system_rpc_tx.clone();
+		let network = network.clone();
+		let rpc_extensions_builder = rpc_extensions_builder.clone();
+		let task_executor = SubscriptionTaskExecutor::new(task_manager.spawn_handle());
+
+		move |deny_unsafe| -> RpcHandler {
+			// --- substrate ---
+			use sc_rpc::{
+				author::AuthorApi,
+				chain::ChainApi,
+				offchain::OffchainApi,
+				state::{ChildStateApi, StateApi},
+				system::SystemApi,
+			};
+
+			let subscriptions = SubscriptionManager::new(Arc::new(task_executor.clone()));
+			let chain = sc_rpc::chain::new_full(client.clone(), subscriptions.clone());
+			let (state, child_state) =
+				sc_rpc::state::new_full(client.clone(), subscriptions.clone(), deny_unsafe);
+			let author = sc_rpc::author::Author::new(
+				client.clone(),
+				transaction_pool.clone(),
+				subscriptions,
+				keystore.clone(),
+				deny_unsafe,
+			);
+			let system = sc_rpc::system::System::new(
+				system_info.clone(),
+				system_rpc_tx.clone(),
+				deny_unsafe,
+			);
+			let offchain = offchain_storage.clone().map(|storage| {
+				OffchainApi::to_delegate(sc_rpc::offchain::Offchain::new(storage, deny_unsafe))
+			});
+			let mut io = PubSubHandler::new(MetaIoHandler::with_middleware(BatchSizeLimit::new(
+				rpc_max_batch_size,
+			)));
+
+			io.extend_with(StateApi::to_delegate(state));
+			io.extend_with(ChildStateApi::to_delegate(child_state));
+			io.extend_with(ChainApi::to_delegate(chain));
+			if let Some(offchain) = offchain {
+				io.extend_with(offchain);
+			}
+			io.extend_with(AuthorApi::to_delegate(author));
+			io.extend_with(SystemApi::to_delegate(system));
+			io.extend_with(rpc_extensions_builder(
+				deny_unsafe,
+				is_authority,
+				network.clone(),
+				task_executor.clone(),
+			));
+
+			let mut methods = io
+				.iter()
+				.map(|(method, _)| method.clone())
+				.collect::<Vec<_>>();
+
+			methods.sort();
+			io.add_method("rpc_methods", move |_| {
+				Ok(serde_json::json!({
+					"version": 1,
+					"methods": methods.clone(),
+				}))
+			});
+
+			io
+		}
+	};
 	let (rpc_handlers, telemetry_connection_notifier) =
 		sc_service::spawn_tasks(SpawnTasksParams {
 			config,
@@ -441,10 +694,94 @@ where
 			network_status_sinks,
 			system_rpc_tx,
 		})?;
+	let rpc_servers = rpc_servers.start(gen_rpc_handler)?;
+
+	task_manager.keep_alive(rpc_servers);
 
 	let (block_import, link_half, babe_link) = import_setup;
 
-	if role.is_authority() {
+	task_manager.spawn_handle().spawn_blocking(
+		"dvm-mapping-sync-worker",
+		dvm_consensus::run_mapping_sync_worker::<Block, _>(
+			client.clone(),
+			EthereumInstance::default(),
+			prometheus_registry.clone(),
+		),
+	);
+
+	if let Some(registry) = prometheus_registry.clone() {
+		task_manager.spawn_handle().spawn(
+			"dvm-metrics-worker",
+			dvm_rpc::run_dvm_metrics_worker(
+				client.clone(),
+				transaction_pool.clone(),
+				eth_block_data_cache,
+				registry,
+			),
+		);
+	}
+
+	if let Some((sealing, (_, rpc_commands))) = manual_seal {
+		// Automatically sealed blocks are finalized right away, there is no GRANDPA to do so.
+		let seal_new_block = |create_empty| EngineCommand::SealNewBlock {
+			create_empty,
+			finalize: true,
+			parent_hash: None,
+			sender: None,
+		};
+		let commands_stream: BoxStream<_> = match sealing {
+			Sealing::Instant => stream::select(
+				rpc_commands,
+				transaction_pool
+					.pool()
+					.validated_pool()
+					.import_notification_stream()
+					.map(move |_| seal_new_block(false)),
+			)
+			.boxed(),
+			Sealing::Manual => rpc_commands.boxed(),
+			Sealing::Interval(millis) => stream::select(
+				rpc_commands,
+				stream::unfold((), move |_| async move {
+					futures_timer::Delay::new(Duration::from_millis(millis)).await;
+
+					Some((seal_new_block(true), ()))
+				}),
+			)
+			.boxed(),
+		};
+		let proposer = ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+		);
+		// The manual seal registers its own slot and timestamp providers,
+		// so it must not share the ones of the BABE import queue.
+		let inherent_data_providers = InherentDataProviders::new();
+		let consensus_data_provider = BabeConsensusDataProvider::new(
+			client.clone(),
+			keystore_container.sync_keystore(),
+			&inherent_data_providers,
+			babe_link.epoch_changes().clone(),
+			babe_link.config().genesis_authorities.clone(),
+		)
+		.map_err(|e| ServiceError::Other(format!("{:?}", e)))?;
+		let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+			block_import,
+			env: proposer,
+			client: client.clone(),
+			pool: transaction_pool.pool().clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(consensus_data_provider)),
+			inherent_data_providers,
+		});
+
+		task_manager
+			.spawn_essential_handle()
+			.spawn_blocking("manual-seal", manual_seal);
+	} else if role.is_authority() {
 		let can_author_with = CanAuthorWithNativeVersion::new(client.executor().clone());
 		let proposer = ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -486,7 +823,7 @@ where
 		keystore,
 		is_authority: role.is_network_authority(),
 	};
-	let enable_grandpa = !disable_grandpa;
+	let enable_grandpa = !disable_grandpa && sealing.is_none();
 
 	if enable_grandpa {
 		let grandpa_config = GrandpaParams {
@@ -506,7 +843,6 @@ where
 	}
 
 	if role.is_authority() && !authority_discovery_disabled {
-		use futures::StreamExt;
 		use sc_network::Event;
 
 		let authority_discovery_role =
@@ -675,7 +1011,7 @@ where
 		import_queue,
 		task_manager,
 		..
-	} = new_partial::<Runtime, Dispatch>(config)?;
+	} = new_partial::<Runtime, Dispatch>(config, None, 0)?;
 
 	Ok((client, backend, import_queue, task_manager))
 }
@@ -685,6 +1021,9 @@ where
 pub fn hyperspace_new_full(
 	config: Configuration,
 	authority_discovery_disabled: bool,
+	sealing: Option<Sealing>,
+	eth_block_cache: usize,
+	rpc_max_batch_size: usize,
 ) -> Result<
 	(
 		TaskManager,
@@ -693,10 +1032,14 @@ pub fn hyperspace_new_full(
 	),
 	ServiceError,
 > {
-	let (components, client, rpc_handlers) = new_full::<
-		hyperspace_runtime::RuntimeApi,
-		HyperspaceExecutor,
-	>(config, authority_discovery_disabled)?;
+	let (components, client, rpc_handlers) =
+		new_full::<hyperspace_runtime::RuntimeApi, HyperspaceExecutor>(
+			config,
+			authority_discovery_disabled,
+			sealing,
+			eth_block_cache,
+			rpc_max_batch_size,
+		)?;
 
 	Ok((components, client, rpc_handlers))
 }
@@ -261,7 +261,11 @@ use sp_version::RuntimeVersion;
 use constants::*;
 use hyperspace_balances_rpc_runtime_api::RuntimeDispatchInfo as BalancesRuntimeDispatchInfo;
 use hyperspace_evm::{ConcatAddressMapping, Runner};
-use hyperspace_header_mmr_rpc_runtime_api::RuntimeDispatchInfo as HeaderMMRRuntimeDispatchInfo;
+use hyperspace_evm_rpc_runtime_api::RuntimeDispatchInfo as EVMRuntimeDispatchInfo;
+use hyperspace_header_mmr_rpc_runtime_api::{
+	ConsistencyProof as HeaderMMRConsistencyProof, ProofError as HeaderMMRProofError,
+	RuntimeDispatchInfo as HeaderMMRRuntimeDispatchInfo,
+};
 use hyperspace_relay_primitives::relay_authorities::OpCode;
 use hyperspace_staking::EraIndex;
 use hyperspace_staking_rpc_runtime_api::RuntimeDispatchInfo as StakingRuntimeDispatchInfo;
@@ -295,7 +299,11 @@ type Executive = frame_executive::Executive<
 	Runtime,
 	AllModules,
 	// CustomOnRuntimeUpgrade,
-	PhragmenElectionDepositRuntimeUpgrade,
+	(
+		PhragmenElectionDepositRuntimeUpgrade,
+		HeaderMMRRuntimeUpgrade,
+		EvmHardforkRuntimeUpgrade,
+	),
 >;
 /// The payload being signed in transactions.
 type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -311,10 +319,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("Hyperspace"),
 	impl_name: create_runtime_str!("Hyperspace"),
 	authoring_version: 1,
-	spec_version: 19,
+	spec_version: 20,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 2,
+	transaction_version: 3,
 };
 
 /// The version information used to identify this runtime when compiled natively.
@@ -1077,7 +1085,13 @@ impl hyperspace_oldetp_backing::Config for Runtime {
 	type WeightInfo = ();
 }
 
-impl hyperspace_header_mmr::Config for Runtime {}
+parameter_types! {
+	pub const MMRRootHistoryDepth: u64 = DAYS as _;
+}
+impl hyperspace_header_mmr::Config for Runtime {
+	type MMRRootHistoryDepth = MMRRootHistoryDepth;
+	type LeafPayload = ();
+}
 
 /// Fixed gas price of `1`.
 pub struct FixedGasPrice;
@@ -1089,8 +1103,31 @@ impl FeeCalculator for FixedGasPrice {
 	}
 }
 
+/// BABE epoch randomness, falling back to `RandomnessCollectiveFlip` until BABE has produced
+/// its first VRF output.
+pub struct BabeRandomnessWithFallback;
+impl Randomness<Hash> for BabeRandomnessWithFallback {
+	fn random(subject: &[u8]) -> Hash {
+		if Babe::randomness().iter().all(|byte| *byte == 0) {
+			RandomnessCollectiveFlip::random(subject)
+		} else {
+			Babe::random(subject)
+		}
+	}
+}
+
 parameter_types! {
 	pub const ChainId: u64 = 23;
+	pub const RandomnessAsDifficulty: bool = false;
+	// Active since genesis on a new chain, scheduled by `EvmHardforkRuntimeUpgrade` otherwise.
+	pub storage EvmEip3529Block: BlockNumber = 0;
+	// One storage slot is 32 bytes key plus 32 bytes value.
+	pub const EvmStorageDepositPerSlot: Balance = deposit(0, 64);
+	pub const EvmStorageDepositPerByte: Balance = deposit(0, 1);
+	pub EvmDeletionWeightLimit: Weight = Perbill::from_percent(10) *
+		RuntimeBlockWeights::get().max_block;
+	// Same as `DVMAccountBasicMapping`.
+	pub EvmUnitsPerBalance: U256 = U256::from(10_000_000_000u64);
 }
 impl hyperspace_evm::Config for Runtime {
 	type FeeCalculator = FixedGasPrice;
@@ -1100,11 +1137,20 @@ impl hyperspace_evm::Config for Runtime {
 	type AddressMapping = ConcatAddressMapping;
 	type EtpCurrency = Etp;
 	type DnaCurrency = Dna;
+	type UnitsPerBalance = EvmUnitsPerBalance;
 	type Event = Event;
 	type Precompiles = hyperspace_evm_precompile::HyperspacePrecompiles<Self>;
 	type ChainId = ChainId;
 	type AccountBasicMapping = DVMAccountBasicMapping<Self>;
 	type Runner = hyperspace_evm::runner::stack::Runner<Self>;
+	type Randomness = BabeRandomnessWithFallback;
+	type RandomnessAsDifficulty = RandomnessAsDifficulty;
+	type HardforkSchedule = hyperspace_evm::ScheduledHardforks<EvmEip3529Block>;
+	type StorageDepositPerSlot = EvmStorageDepositPerSlot;
+	type StorageDepositPerByte = EvmStorageDepositPerByte;
+	type DeletionWeightLimit = EvmDeletionWeightLimit;
+	type OnChargeTransaction = hyperspace_evm::EVMFeeSplit<EthereumFindAuthor<Babe>, Treasury>;
+	type DeploymentOrigin = EnsureRootOrMoreThanHalfCouncil;
 }
 
 type EthereumRelayAuthoritiesInstance = hyperspace_relay_authorities::Instance0;
@@ -1296,10 +1342,12 @@ impl_runtime_apis! {
 		}
 
 		fn execute_block(block: Block) {
+			HeaderMMR::note_parent_state_root();
 			Executive::execute_block(block)
 		}
 
 		fn initialize_block(header: &<Block as BlockT>::Header) {
+			HeaderMMR::note_parent_state_root();
 			Executive::initialize_block(header)
 		}
 	}
@@ -1479,13 +1527,71 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl hyperspace_evm_rpc_runtime_api::EVMApi<Block, Balance> for Runtime {
+		fn storage_deposit_of(address: H160) -> EVMRuntimeDispatchInfo<Balance> {
+			EVM::storage_deposit_rpc(address)
+		}
+
+		fn fee_balance_of(address: H160) -> U256 {
+			EVM::fee_balance(&address)
+		}
+	}
+
 	impl hyperspace_header_mmr_rpc_runtime_api::HeaderMMRApi<Block, Hash> for Runtime {
 		fn gen_proof(
 			block_number_of_member_leaf: u64,
 			block_number_of_last_leaf: u64
-		) -> HeaderMMRRuntimeDispatchInfo<Hash> {
+		) -> Result<HeaderMMRRuntimeDispatchInfo<Hash>, HeaderMMRProofError> {
 			HeaderMMR::gen_proof_rpc(block_number_of_member_leaf, block_number_of_last_leaf )
 		}
+
+		fn gen_batch_proof(
+			block_numbers_of_member_leaves: Vec<u64>,
+			block_number_of_last_leaf: u64
+		) -> Result<HeaderMMRRuntimeDispatchInfo<Hash>, HeaderMMRProofError> {
+			HeaderMMR::gen_batch_proof_rpc(block_numbers_of_member_leaves, block_number_of_last_leaf)
+		}
+
+		fn get_root(block_number_of_last_leaf: u64) -> Result<Hash, HeaderMMRProofError> {
+			HeaderMMR::get_root_rpc(block_number_of_last_leaf)
+		}
+
+		fn get_leaf(leaf_index: u64) -> Result<Hash, HeaderMMRProofError> {
+			HeaderMMR::get_leaf_rpc(leaf_index)
+		}
+
+		fn verify_proof(
+			root: Hash,
+			leaves: Vec<(u64, Hash)>,
+			proof: Vec<Hash>,
+			mmr_size: u64
+		) -> bool {
+			HeaderMMR::verify_proof_rpc(root, leaves, proof, mmr_size)
+		}
+
+		fn peaks() -> (u64, Vec<(u64, Hash)>) {
+			HeaderMMR::peaks_rpc()
+		}
+
+		fn first_v1_leaf() -> Option<u64> {
+			HeaderMMR::first_v1_leaf()
+		}
+
+		fn verify_consistency_proof(
+			old_root: Hash,
+			old_mmr_size: u64,
+			new_root: Hash,
+			new_mmr_size: u64,
+			proof: HeaderMMRConsistencyProof<Hash>
+		) -> bool {
+			HeaderMMR::verify_consistency_proof_rpc(
+				old_root,
+				old_mmr_size,
+				new_root,
+				new_mmr_size,
+				proof
+			)
+		}
 	}
 
 	impl hyperspace_staking_rpc_runtime_api::StakingApi<Block, AccountId, Power> for Runtime {
@@ -1648,3 +1754,28 @@ impl frame_support::traits::OnRuntimeUpgrade for PhragmenElectionDepositRuntimeU
 		hyperspace_elections_phragmen::migrations_3_0_0::apply::<Self>(5 * MILLI, COIN)
 	}
 }
+
+/// Keep the MMR roots still to be signed by the relay authorities, before the header MMR prunes
+/// the nodes they are computed from.
+pub struct HeaderMMRRuntimeUpgrade;
+impl frame_support::traits::OnRuntimeUpgrade for HeaderMMRRuntimeUpgrade {
+	fn on_runtime_upgrade() -> frame_support::weights::Weight {
+		hyperspace_header_mmr::migrations::keep_roots::<Runtime>(
+			EthereumRelayAuthorities::mmr_root_to_sign_keys()
+				.into_iter()
+				.map(Into::into),
+		)
+	}
+}
+
+/// Activate EIP-3529 from the first block of the upgrade, the blocks before it keep Istanbul.
+pub struct EvmHardforkRuntimeUpgrade;
+impl frame_support::traits::OnRuntimeUpgrade for EvmHardforkRuntimeUpgrade {
+	fn on_runtime_upgrade() -> frame_support::weights::Weight {
+		if !frame_support::storage::unhashed::exists(&EvmEip3529Block::key()) {
+			EvmEip3529Block::set(&(System::block_number() + 1));
+		}
+
+		RocksDbWeight::get().reads_writes(2, 1)
+	}
+}
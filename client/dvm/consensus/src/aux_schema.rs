@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use sc_client_api::backend::AuxStore;
 use sp_blockchain::{Error as ClientError, Result as ClientResult};
 use sp_core::H256;
@@ -35,9 +35,14 @@ fn load_decode<B: AuxStore, T: Decode>(backend: &B, key: &[u8]) -> ClientResult<
 	}
 }
 
+/// Keys of an Ethereum pallet instance live under its namespace.
+fn namespaced_key(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+	[namespace, key].concat()
+}
+
 /// Map an Ethereum block hash into a Substrate block hash.
-pub fn block_hash_key(ethereum_block_hash: H256) -> Vec<u8> {
-	let mut ret = b"ethereum_block_hash:".to_vec();
+pub fn block_hash_key(namespace: &[u8], ethereum_block_hash: H256) -> Vec<u8> {
+	let mut ret = namespaced_key(namespace, b"ethereum_block_hash:");
 	ret.append(&mut ethereum_block_hash.as_ref().to_vec());
 	ret
 }
@@ -45,15 +50,17 @@ pub fn block_hash_key(ethereum_block_hash: H256) -> Vec<u8> {
 /// Given an Ethereum block hash, get the corresponding Substrate block hash from AuxStore.
 pub fn load_block_hash<Block: BlockT, B: AuxStore>(
 	backend: &B,
+	namespace: &[u8],
 	hash: H256,
 ) -> ClientResult<Option<Vec<Block::Hash>>> {
-	let key = block_hash_key(hash);
+	let key = block_hash_key(namespace, hash);
 	load_decode(backend, &key)
 }
 
 /// Update Aux block hash.
-pub fn write_block_hash<Hash: Encode + Decode, F, R, Backend: AuxStore>(
+pub fn write_block_hash<Hash: Encode + Decode + PartialEq, F, R, Backend: AuxStore>(
 	client: &Backend,
+	namespace: &[u8],
 	ethereum_hash: H256,
 	block_hash: Hash,
 	write_aux: F,
@@ -61,38 +68,121 @@ pub fn write_block_hash<Hash: Encode + Decode, F, R, Backend: AuxStore>(
 where
 	F: FnOnce(&[(&[u8], &[u8])]) -> R,
 {
-	let key = block_hash_key(ethereum_hash);
+	let key = block_hash_key(namespace, ethereum_hash);
 
 	let mut data: Vec<Hash> = match load_decode(client, &key) {
 		Ok(Some(hashes)) => hashes,
 		_ => Vec::new(),
 	};
-	data.push(block_hash);
+	if !data.contains(&block_hash) {
+		data.push(block_hash);
+	}
 
 	write_aux(&[(&key, &data.encode()[..])])
 }
 
-/// Map an Ethereum transaction hash into its corresponding Ethereum block hash and index.
-pub fn transaction_metadata_key(ethereum_transaction_hash: H256) -> Vec<u8> {
-	let mut ret = b"ethereum_transaction_hash:".to_vec();
+/// Where an Ethereum transaction has been included, there is one for each Substrate block
+/// containing it, blocks on forks included.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct TransactionMetadata<Hash> {
+	/// The Substrate block.
+	pub block_hash: Hash,
+	/// The Ethereum block.
+	pub ethereum_block_hash: H256,
+	/// The index in the Ethereum block.
+	pub ethereum_index: u32,
+}
+
+/// Map an Ethereum transaction hash into all its candidate locations.
+pub fn transaction_metadata_key(namespace: &[u8], ethereum_transaction_hash: H256) -> Vec<u8> {
+	let mut ret = namespaced_key(namespace, b"ethereum_transaction_hash:");
 	ret.append(&mut ethereum_transaction_hash.as_ref().to_vec());
 	ret
 }
 
-/// Given an Ethereum transaction hash, get the corresponding Ethereum block hash and index.
-pub fn load_transaction_metadata<B: AuxStore>(
+fn load_transaction_metadata_of<Hash: Encode + Decode, B: AuxStore>(
+	backend: &B,
+	namespace: &[u8],
+	key: &[u8],
+) -> ClientResult<Vec<TransactionMetadata<Hash>>> {
+	let data = match backend.get_aux(key)? {
+		Some(data) => data,
+		None => return Ok(Vec::new()),
+	};
+
+	if let Ok(metadata) = Vec::<TransactionMetadata<Hash>>::decode_all(&data) {
+		return Ok(metadata);
+	}
+
+	// The legacy format only kept the last written `(ethereum block hash, index)`, expand it with
+	// the Substrate blocks of the Ethereum block.
+	let (ethereum_block_hash, ethereum_index) = <(H256, u32)>::decode_all(&data).map_err(|e| {
+		ClientError::Backend(format!(
+			"Frontier DB is corrupted. Decode error: {}",
+			e.what()
+		))
+	})?;
+	let block_hashes: Vec<Hash> =
+		load_decode(backend, &block_hash_key(namespace, ethereum_block_hash))?.unwrap_or_default();
+
+	Ok(block_hashes
+		.into_iter()
+		.map(|block_hash| TransactionMetadata {
+			block_hash,
+			ethereum_block_hash,
+			ethereum_index,
+		})
+		.collect())
+}
+
+/// Given an Ethereum transaction hash, get all the candidate locations of it.
+pub fn load_transaction_metadata<Block: BlockT, B: AuxStore>(
 	backend: &B,
+	namespace: &[u8],
 	hash: H256,
-) -> ClientResult<Option<(H256, u32)>> {
-	let key = transaction_metadata_key(hash);
-	load_decode(backend, &key)
+) -> ClientResult<Vec<TransactionMetadata<Block::Hash>>> {
+	let key = transaction_metadata_key(namespace, hash);
+	load_transaction_metadata_of(backend, namespace, &key)
+}
+
+/// Add a candidate location to the Aux transaction metadata.
+pub fn write_transaction_metadata<Hash, F, R, Backend>(
+	client: &Backend,
+	namespace: &[u8],
+	hash: H256,
+	metadata: TransactionMetadata<Hash>,
+	write_aux: F,
+) -> R
+where
+	Hash: Encode + Decode + PartialEq,
+	Backend: AuxStore,
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
+{
+	let key = transaction_metadata_key(namespace, hash);
+
+	let mut data = load_transaction_metadata_of(client, namespace, &key).unwrap_or_default();
+	if !data.contains(&metadata) {
+		data.push(metadata);
+	}
+
+	write_aux(&[(&key, &data.encode()[..])])
+}
+
+/// The best block the mapping sync worker has synced to.
+pub const SYNCED_BEST_KEY: &[u8] = b"ethereum_mapping_synced_best";
+
+/// Get the best block the mapping sync worker has synced to.
+pub fn load_synced_best<Block: BlockT, B: AuxStore>(
+	backend: &B,
+	namespace: &[u8],
+) -> ClientResult<Option<Block::Hash>> {
+	load_decode(backend, &namespaced_key(namespace, SYNCED_BEST_KEY))
 }
 
-/// Update Aux transaction metadata.
-pub fn write_transaction_metadata<F, R>(hash: H256, metadata: (H256, u32), write_aux: F) -> R
+/// Update the best block the mapping sync worker has synced to.
+pub fn write_synced_best<Hash: Encode, F, R>(namespace: &[u8], hash: Hash, write_aux: F) -> R
 where
 	F: FnOnce(&[(&[u8], &[u8])]) -> R,
 {
-	let key = transaction_metadata_key(hash);
-	write_aux(&[(&key, &metadata.encode())])
+	write_aux(&[(&namespaced_key(namespace, SYNCED_BEST_KEY), &hash.encode())])
 }
@@ -17,8 +17,15 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 mod aux_schema;
-
-pub use crate::aux_schema::{load_block_hash, load_transaction_metadata};
+mod mapping;
+#[cfg(test)]
+mod tests;
+
+pub use crate::aux_schema::{load_block_hash, load_transaction_metadata, TransactionMetadata};
+pub use crate::mapping::{
+	canonical_block_hash, canonical_transaction_metadata, is_canon, run_mapping_sync_worker,
+	sync_mapping_batch,
+};
 
 use dvm_consensus_primitives::{ConsensusLog, FRONTIER_ENGINE_ID};
 use log::*;
@@ -32,6 +39,7 @@ use sp_consensus::{
 };
 use sp_runtime::generic::OpaqueDigestItemId;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_runtime::ConsensusEngineId;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -40,8 +48,6 @@ use std::sync::Arc;
 pub enum Error {
 	#[display(fmt = "Multiple post-runtime Ethereum blocks, rejecting!")]
 	MultiplePostRuntimeLogs,
-	#[display(fmt = "Post-runtime Ethereum block not found, rejecting!")]
-	NoPostRuntimeLog,
 }
 
 impl From<Error> for String {
@@ -56,9 +62,31 @@ impl std::convert::From<Error> for ConsensusError {
 	}
 }
 
+/// Where the client finds the Ethereum blocks of a runtime: the consensus engine ID of their log
+/// and the namespace of their mapping in the aux store. The Ethereum pallet is not instantiable,
+/// runtimes use the default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EthereumInstance {
+	/// The consensus engine ID of the instance's Ethereum block log.
+	pub engine_id: ConsensusEngineId,
+	/// Prefix of the instance's mapping keys in the aux store.
+	pub aux_namespace: &'static [u8],
+}
+
+impl Default for EthereumInstance {
+	/// The default instance keeps the unprefixed keys of the existing databases.
+	fn default() -> Self {
+		Self {
+			engine_id: FRONTIER_ENGINE_ID,
+			aux_namespace: b"",
+		}
+	}
+}
+
 pub struct FrontierBlockImport<B: BlockT, I, C> {
 	inner: I,
 	client: Arc<C>,
+	instance: EthereumInstance,
 	enabled: bool,
 	_marker: PhantomData<B>,
 }
@@ -68,6 +96,7 @@ impl<Block: BlockT, I: Clone + BlockImport<Block>, C> Clone for FrontierBlockImp
 		FrontierBlockImport {
 			inner: self.inner.clone(),
 			client: self.client.clone(),
+			instance: self.instance,
 			enabled: self.enabled,
 			_marker: PhantomData,
 		}
@@ -82,10 +111,11 @@ where
 	C: ProvideRuntimeApi<B> + Send + Sync + HeaderBackend<B> + AuxStore + ProvideCache<B> + BlockOf,
 	C::Api: BlockBuilderApi<B, Error = sp_blockchain::Error>,
 {
-	pub fn new(inner: I, client: Arc<C>, enabled: bool) -> Self {
+	pub fn new(inner: I, client: Arc<C>, instance: EthereumInstance, enabled: bool) -> Self {
 		Self {
 			inner,
 			client,
+			instance,
 			enabled,
 			_marker: PhantomData,
 		}
@@ -109,46 +139,13 @@ where
 
 	fn import_block(
 		&mut self,
-		mut block: BlockImportParams<B, Self::Transaction>,
+		block: BlockImportParams<B, Self::Transaction>,
 		new_cache: HashMap<CacheKeyId, Vec<u8>>,
 	) -> Result<ImportResult, Self::Error> {
-		macro_rules! insert_closure {
-			() => {
-				|insert| {
-					block
-						.auxiliary
-						.extend(insert.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec()))))
-				}
-			};
-		}
-
-		let client = self.client.clone();
-
+		// Blocks before the Ethereum pallet have no log and import normally, the mapping is built
+		// by the mapping sync worker.
 		if self.enabled {
-			let log = find_frontier_log::<B>(&block.header)?;
-			let hash = block.post_hash();
-
-			match log {
-				ConsensusLog::EndBlock {
-					block_hash,
-					transaction_hashes,
-				} => {
-					aux_schema::write_block_hash(
-						client.as_ref(),
-						block_hash,
-						hash,
-						insert_closure!(),
-					);
-
-					for (index, transaction_hash) in transaction_hashes.into_iter().enumerate() {
-						aux_schema::write_transaction_metadata(
-							transaction_hash,
-							(block_hash, index as u32),
-							insert_closure!(),
-						);
-					}
-				}
-			}
+			find_frontier_log::<B>(&block.header, &self.instance.engine_id)?;
 		}
 
 		self.inner
@@ -157,11 +154,52 @@ where
 	}
 }
 
-fn find_frontier_log<B: BlockT>(header: &B::Header) -> Result<ConsensusLog, Error> {
+/// The aux writes mapping the Ethereum block and transactions in `log` to the Substrate block.
+fn mapping_writes<B: BlockT, C: AuxStore>(
+	client: &C,
+	namespace: &[u8],
+	hash: B::Hash,
+	log: ConsensusLog,
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+	let mut writes = Vec::new();
+	let mut write_aux = |insert: &[(&[u8], &[u8])]| {
+		writes.extend(insert.iter().map(|(k, v)| (k.to_vec(), v.to_vec())))
+	};
+
+	match log {
+		ConsensusLog::EndBlock {
+			block_hash,
+			transaction_hashes,
+		} => {
+			aux_schema::write_block_hash(client, namespace, block_hash, hash, &mut write_aux);
+
+			for (index, transaction_hash) in transaction_hashes.into_iter().enumerate() {
+				aux_schema::write_transaction_metadata(
+					client,
+					namespace,
+					transaction_hash,
+					TransactionMetadata {
+						block_hash: hash,
+						ethereum_block_hash: block_hash,
+						ethereum_index: index as u32,
+					},
+					&mut write_aux,
+				);
+			}
+		}
+	}
+
+	writes
+}
+
+fn find_frontier_log<B: BlockT>(
+	header: &B::Header,
+	engine_id: &ConsensusEngineId,
+) -> Result<Option<ConsensusLog>, Error> {
 	let mut frontier_log: Option<_> = None;
 	for log in header.digest().logs() {
 		trace!(target: "dvm-consensus", "Checking log {:?}, looking for ethereum block.", log);
-		let log = log.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&FRONTIER_ENGINE_ID));
+		let log = log.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(engine_id));
 		match (log, frontier_log.is_some()) {
 			(Some(_), true) => return Err(Error::MultiplePostRuntimeLogs),
 			(Some(log), false) => frontier_log = Some(log),
@@ -169,5 +207,5 @@ fn find_frontier_log<B: BlockT>(header: &B::Header) -> Result<ConsensusLog, Erro
 		}
 	}
 
-	Ok(frontier_log.ok_or(Error::NoPostRuntimeLog)?)
+	Ok(frontier_log)
 }
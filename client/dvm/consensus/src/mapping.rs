@@ -0,0 +1,253 @@
+// This file is part of Frontier.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Resolve the mapping against the canonical chain and keep it in sync with it.
+
+use crate::aux_schema::{self, TransactionMetadata};
+use crate::{find_frontier_log, mapping_writes, EthereumInstance};
+use futures::StreamExt;
+use log::*;
+use prometheus_endpoint::{register, Gauge, Registry, U64};
+use sc_client_api::{backend::AuxStore, BlockchainEvents};
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata, Result as ClientResult};
+use sp_core::H256;
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as BlockT, One, Saturating, UniqueSaturatedInto, Zero};
+use std::sync::Arc;
+
+/// Whether the block is on the canonical chain.
+pub fn is_canon<Block: BlockT, C: HeaderBackend<Block>>(client: &C, hash: Block::Hash) -> bool {
+	match client.number(hash) {
+		Ok(Some(number)) => matches!(client.hash(number), Ok(Some(canon)) if canon == hash),
+		_ => false,
+	}
+}
+
+/// Given an Ethereum block hash, get the Substrate block of it on the canonical chain.
+pub fn canonical_block_hash<Block, C>(
+	client: &C,
+	instance: &EthereumInstance,
+	hash: H256,
+) -> ClientResult<Option<Block::Hash>>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + AuxStore,
+{
+	let hashes = aux_schema::load_block_hash::<Block, _>(client, instance.aux_namespace, hash)?
+		.unwrap_or_default();
+
+	Ok(select_canonical(hashes, |hash| {
+		is_canon::<Block, _>(client, *hash)
+	}))
+}
+
+/// Given an Ethereum transaction hash, get the location of it on the canonical chain.
+pub fn canonical_transaction_metadata<Block, C>(
+	client: &C,
+	instance: &EthereumInstance,
+	hash: H256,
+) -> ClientResult<Option<TransactionMetadata<Block::Hash>>>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + AuxStore,
+{
+	let metadata =
+		aux_schema::load_transaction_metadata::<Block, _>(client, instance.aux_namespace, hash)?;
+
+	Ok(select_canonical(metadata, |metadata| {
+		is_canon::<Block, _>(client, metadata.block_hash)
+	}))
+}
+
+/// One chain can't contain the same Ethereum block or transaction twice, the first canonical
+/// candidate is the only one.
+pub(crate) fn select_canonical<T>(candidates: Vec<T>, is_canon: impl Fn(&T) -> bool) -> Option<T> {
+	candidates.into_iter().find(|candidate| is_canon(candidate))
+}
+
+/// The number of blocks mapped before the cursor is persisted.
+const SYNC_BATCH_SIZE: u32 = 256;
+
+/// Map the next canonical blocks after the cursor, at most `limit` of them, and move the cursor.
+/// Returns whether nothing is left to map until a new block is imported, either the best block
+/// has been reached or the next canonical block is not in the database yet.
+///
+/// Blocks without the Ethereum block log, e.g. the ones before the Ethereum pallet, are skipped.
+/// After a reorg the cursor restarts from the common ancestor with the canonical chain, the
+/// mapping of the retracted blocks is kept since it's filtered at query time. If the cursor
+/// block has been pruned since, the cursor restarts from the last finalized block.
+pub fn sync_mapping_batch<Block, C>(
+	client: &C,
+	instance: &EthereumInstance,
+	limit: u32,
+) -> ClientResult<bool>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = ClientError> + AuxStore,
+{
+	let info = client.info();
+	let namespace = instance.aux_namespace;
+	let synced_best = aux_schema::load_synced_best::<Block, _>(client, namespace)?;
+	let (mut number, mut synced) = match synced_best {
+		Some(hash) if is_canon::<Block, _>(client, hash) => (
+			client
+				.number(hash)?
+				.ok_or_else(|| ClientError::UnknownBlock(format!("{:?}", hash)))?,
+			hash,
+		),
+		Some(hash) => match sp_blockchain::tree_route(client, hash, info.best_hash) {
+			Ok(route) => {
+				let ancestor = route.common_block();
+
+				(ancestor.number, ancestor.hash)
+			}
+			Err(e) => {
+				warn!(
+					target: "dvm-consensus",
+					"Restart mapping from the last finalized block, no route from {:?}: {:?}",
+					hash,
+					e,
+				);
+
+				(info.finalized_number, info.finalized_hash)
+			}
+		},
+		None => (Zero::zero(), info.genesis_hash),
+	};
+	let mut count = 0;
+	let mut stalled = false;
+
+	while number < info.best_number && count < limit {
+		let next = match client.hash(number + One::one())? {
+			Some(hash) => hash,
+			// The best block moved and the new canonical chain is still being written.
+			None => {
+				stalled = true;
+
+				break;
+			}
+		};
+		let header = client
+			.header(BlockId::Hash(next))?
+			.ok_or_else(|| ClientError::UnknownBlock(format!("{:?}", next)))?;
+
+		match find_frontier_log::<Block>(&header, &instance.engine_id) {
+			Ok(Some(log)) => {
+				let writes = mapping_writes::<Block, _>(client, namespace, next, log);
+				let insert = writes
+					.iter()
+					.map(|(k, v)| (&k[..], &v[..]))
+					.collect::<Vec<_>>();
+
+				client.insert_aux(&insert, &[])?;
+			}
+			Ok(None) => (),
+			Err(e) => warn!(target: "dvm-consensus", "Skip mapping {:?}: {}", next, e),
+		}
+
+		number += One::one();
+		synced = next;
+		count += 1;
+	}
+
+	aux_schema::write_synced_best(namespace, synced, |insert| client.insert_aux(insert, &[]))?;
+
+	Ok(stalled || number >= info.best_number)
+}
+
+/// The number of best blocks not mapped yet.
+fn mapping_lag<Block, C>(client: &C, instance: &EthereumInstance) -> ClientResult<u64>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + AuxStore,
+{
+	let synced = match aux_schema::load_synced_best::<Block, _>(client, instance.aux_namespace)? {
+		Some(hash) => client.number(hash)?.unwrap_or_else(Zero::zero),
+		None => Zero::zero(),
+	};
+
+	Ok(client
+		.info()
+		.best_number
+		.saturating_sub(synced)
+		.unique_saturated_into())
+}
+
+/// Build the mapping of the canonical chain, catching up on startup and following the new best
+/// blocks. It blocks between batches, so it should be spawned as a blocking task.
+///
+/// The number of best blocks not mapped yet is reported to `registry` if given.
+pub async fn run_mapping_sync_worker<Block, C>(
+	client: Arc<C>,
+	instance: EthereumInstance,
+	registry: Option<Registry>,
+) where
+	Block: BlockT,
+	C: BlockchainEvents<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = ClientError>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+{
+	let lag = registry.and_then(|registry| {
+		let gauge = Gauge::<U64>::new(
+			"dvm_mapping_sync_lag",
+			"Number of best blocks not in the Ethereum mapping yet",
+		)
+		.and_then(|gauge| register(gauge, &registry));
+
+		match gauge {
+			Ok(gauge) => Some(gauge),
+			Err(e) => {
+				warn!(target: "dvm-consensus", "Failed to register the mapping metrics: {:?}", e);
+
+				None
+			}
+		}
+	});
+	let sync = || {
+		loop {
+			match sync_mapping_batch(client.as_ref(), &instance, SYNC_BATCH_SIZE) {
+				Ok(true) => break,
+				Ok(false) => (),
+				Err(e) => {
+					warn!(target: "dvm-consensus", "Mapping sync failed: {:?}", e);
+
+					break;
+				}
+			}
+		}
+
+		if let Some(lag) = &lag {
+			if let Ok(blocks) = mapping_lag(client.as_ref(), &instance) {
+				lag.set(blocks);
+			}
+		}
+	};
+	let mut notifications = client.import_notification_stream();
+
+	sync();
+
+	while let Some(notification) = notifications.next().await {
+		if notification.is_new_best {
+			sync();
+		}
+	}
+}
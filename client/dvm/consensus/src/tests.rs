@@ -0,0 +1,426 @@
+// This file is part of Frontier.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::aux_schema::{self, TransactionMetadata};
+use crate::mapping::select_canonical;
+use crate::{
+	canonical_block_hash, find_frontier_log, is_canon, mapping_writes, run_mapping_sync_worker,
+	sync_mapping_batch, Error, EthereumInstance,
+};
+use codec::Encode;
+use dvm_consensus_primitives::{ConsensusLog, FRONTIER_ENGINE_ID};
+use sc_block_builder::BlockBuilderProvider;
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::H256;
+use sp_runtime::generic::{BlockId, Digest, DigestItem};
+use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+use sp_runtime::traits::Header as HeaderT;
+use std::{cell::RefCell, collections::HashMap, sync::Arc, thread, time::Duration};
+use substrate_test_runtime_client::{prelude::*, runtime::Block as TestBlock};
+
+type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+#[derive(Default)]
+struct MockAuxStore(RefCell<HashMap<Vec<u8>, Vec<u8>>>);
+
+impl AuxStore for MockAuxStore {
+	fn insert_aux<
+		'a,
+		'b: 'a,
+		'c: 'a,
+		I: IntoIterator<Item = &'a (&'c [u8], &'c [u8])>,
+		D: IntoIterator<Item = &'a &'b [u8]>,
+	>(
+		&self,
+		insert: I,
+		delete: D,
+	) -> sp_blockchain::Result<()> {
+		let mut store = self.0.borrow_mut();
+
+		for (k, v) in insert {
+			store.insert(k.to_vec(), v.to_vec());
+		}
+		for k in delete {
+			store.remove(*k);
+		}
+
+		Ok(())
+	}
+
+	fn get_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+		Ok(self.0.borrow().get(key).cloned())
+	}
+}
+
+impl MockAuxStore {
+	fn import(&self, namespace: &[u8], hash: H256, log: ConsensusLog) {
+		let writes = mapping_writes::<Block, _>(self, namespace, hash, log);
+		let insert = writes
+			.iter()
+			.map(|(k, v)| (&k[..], &v[..]))
+			.collect::<Vec<_>>();
+
+		self.insert_aux(&insert, &[]).unwrap();
+	}
+}
+
+/// Build a block on `parent` with the log of the Ethereum block `ethereum_block_hash`, import it
+/// and return its hash. The longest chain is the best one.
+fn import_block(client: &mut Arc<TestClient>, parent: H256, ethereum_block_hash: H256) -> H256 {
+	let log = ConsensusLog::EndBlock {
+		block_hash: ethereum_block_hash,
+		transaction_hashes: vec![],
+	};
+	let block = client
+		.new_block_at(
+			&BlockId::Hash(parent),
+			Digest {
+				logs: vec![DigestItem::Consensus(FRONTIER_ENGINE_ID, log.encode())],
+			},
+			false,
+		)
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	let hash = block.header.hash();
+
+	client.import(BlockOrigin::Own, block).unwrap();
+
+	hash
+}
+
+fn canonical(client: &TestClient, ethereum_block_hash: H256) -> Option<H256> {
+	canonical_block_hash::<TestBlock, _>(client, &EthereumInstance::default(), ethereum_block_hash)
+		.unwrap()
+}
+
+fn synced_best(client: &TestClient) -> Option<H256> {
+	aux_schema::load_synced_best::<TestBlock, _>(client, b"").unwrap()
+}
+
+#[test]
+fn competing_forks_resolve_to_canonical_chain() {
+	let store = MockAuxStore::default();
+	let transaction_hash = H256::repeat_byte(1);
+	// Two BABE authors claimed the same slot, both blocks include the transaction.
+	let (fork_a, ethereum_a) = (H256::repeat_byte(0xa), H256::repeat_byte(0xaa));
+	let (fork_b, ethereum_b) = (H256::repeat_byte(0xb), H256::repeat_byte(0xbb));
+
+	store.import(
+		b"",
+		fork_a,
+		ConsensusLog::EndBlock {
+			block_hash: ethereum_a,
+			transaction_hashes: vec![transaction_hash],
+		},
+	);
+	store.import(
+		b"",
+		fork_b,
+		ConsensusLog::EndBlock {
+			block_hash: ethereum_b,
+			transaction_hashes: vec![H256::repeat_byte(2), transaction_hash],
+		},
+	);
+	// Importing again doesn't duplicate the candidates.
+	store.import(
+		b"",
+		fork_a,
+		ConsensusLog::EndBlock {
+			block_hash: ethereum_a,
+			transaction_hashes: vec![transaction_hash],
+		},
+	);
+
+	let candidates =
+		aux_schema::load_transaction_metadata::<Block, _>(&store, b"", transaction_hash).unwrap();
+
+	assert_eq!(
+		candidates,
+		vec![
+			TransactionMetadata {
+				block_hash: fork_a,
+				ethereum_block_hash: ethereum_a,
+				ethereum_index: 0,
+			},
+			TransactionMetadata {
+				block_hash: fork_b,
+				ethereum_block_hash: ethereum_b,
+				ethereum_index: 1,
+			},
+		]
+	);
+
+	// The result doesn't depend on the import order but on the canonical chain.
+	for canon in [fork_a, fork_b].iter() {
+		let metadata =
+			select_canonical(candidates.clone(), |metadata| &metadata.block_hash == canon).unwrap();
+
+		assert_eq!(&metadata.block_hash, canon);
+		assert_eq!(
+			select_canonical(
+				aux_schema::load_block_hash::<Block, _>(&store, b"", metadata.ethereum_block_hash)
+					.unwrap()
+					.unwrap(),
+				|hash| hash == canon
+			),
+			Some(*canon)
+		);
+	}
+
+	// Both retracted.
+	assert!(select_canonical(candidates, |_| false).is_none());
+}
+
+#[test]
+fn legacy_transaction_metadata_is_expanded() {
+	let store = MockAuxStore::default();
+	let transaction_hash = H256::repeat_byte(1);
+	let ethereum_block_hash = H256::repeat_byte(0xaa);
+	let blocks = vec![H256::repeat_byte(0xa), H256::repeat_byte(0xb)];
+
+	store
+		.insert_aux(
+			&[
+				(
+					&aux_schema::block_hash_key(b"", ethereum_block_hash)[..],
+					&blocks.encode()[..],
+				),
+				(
+					&aux_schema::transaction_metadata_key(b"", transaction_hash)[..],
+					&(ethereum_block_hash, 3u32).encode()[..],
+				),
+			],
+			&[],
+		)
+		.unwrap();
+
+	assert_eq!(
+		aux_schema::load_transaction_metadata::<Block, _>(&store, b"", transaction_hash).unwrap(),
+		blocks
+			.into_iter()
+			.map(|block_hash| TransactionMetadata {
+				block_hash,
+				ethereum_block_hash,
+				ethereum_index: 3,
+			})
+			.collect::<Vec<_>>()
+	);
+}
+
+#[test]
+fn synced_best_works() {
+	let store = MockAuxStore::default();
+
+	assert_eq!(
+		aux_schema::load_synced_best::<Block, _>(&store, b"").unwrap(),
+		None
+	);
+
+	aux_schema::write_synced_best(b"", H256::repeat_byte(1), |insert| {
+		store.insert_aux(insert, &[])
+	})
+	.unwrap();
+
+	assert_eq!(
+		aux_schema::load_synced_best::<Block, _>(&store, b"").unwrap(),
+		Some(H256::repeat_byte(1))
+	);
+}
+
+#[test]
+fn blocks_without_log_are_tolerated() {
+	let header = |logs| {
+		Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Digest { logs },
+		)
+	};
+	let log = ConsensusLog::EndBlock {
+		block_hash: H256::repeat_byte(0xaa),
+		transaction_hashes: vec![H256::repeat_byte(1)],
+	};
+	let digest = DigestItem::Consensus(FRONTIER_ENGINE_ID, log.encode());
+
+	assert!(
+		find_frontier_log::<Block>(&header(vec![]), &FRONTIER_ENGINE_ID)
+			.unwrap()
+			.is_none()
+	);
+	assert!(
+		find_frontier_log::<Block>(&header(vec![digest.clone()]), &FRONTIER_ENGINE_ID).unwrap()
+			== Some(log)
+	);
+	assert!(matches!(
+		find_frontier_log::<Block>(&header(vec![digest.clone(), digest]), &FRONTIER_ENGINE_ID),
+		Err(Error::MultiplePostRuntimeLogs)
+	));
+}
+
+#[test]
+fn instances_are_isolated() {
+	let store = MockAuxStore::default();
+	let default = EthereumInstance::default();
+	let other = EthereumInstance {
+		engine_id: *b"fro1",
+		aux_namespace: b"fro1:",
+	};
+	let log = ConsensusLog::EndBlock {
+		block_hash: H256::repeat_byte(0xaa),
+		transaction_hashes: vec![H256::repeat_byte(1)],
+	};
+	let header = Header::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Digest {
+			logs: vec![DigestItem::Consensus(other.engine_id, log.encode())],
+		},
+	);
+
+	assert!(find_frontier_log::<Block>(&header, &default.engine_id)
+		.unwrap()
+		.is_none());
+	assert!(find_frontier_log::<Block>(&header, &other.engine_id).unwrap() == Some(log.clone()));
+
+	store.import(other.aux_namespace, H256::repeat_byte(0xa), log);
+
+	// The default instance keeps the keys of the existing databases.
+	assert_eq!(
+		aux_schema::block_hash_key(default.aux_namespace, H256::repeat_byte(0xaa)),
+		[&b"ethereum_block_hash:"[..], &[0xaa; 32][..]].concat()
+	);
+	assert!(aux_schema::load_block_hash::<Block, _>(
+		&store,
+		default.aux_namespace,
+		H256::repeat_byte(0xaa)
+	)
+	.unwrap()
+	.is_none());
+	assert_eq!(
+		aux_schema::load_block_hash::<Block, _>(
+			&store,
+			other.aux_namespace,
+			H256::repeat_byte(0xaa)
+		)
+		.unwrap(),
+		Some(vec![H256::repeat_byte(0xa)])
+	);
+}
+
+#[test]
+fn mapping_follows_the_best_fork() {
+	let mut client = Arc::new(TestClientBuilder::new().build());
+	let instance = EthereumInstance::default();
+	let genesis = client.info().genesis_hash;
+	let a1 = import_block(&mut client, genesis, H256::repeat_byte(0xa1));
+
+	assert!(sync_mapping_batch(client.as_ref(), &instance, 16).unwrap());
+	assert_eq!(synced_best(&client), Some(a1));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa1)), Some(a1));
+
+	// The longer fork becomes the best one.
+	let b1 = import_block(&mut client, genesis, H256::repeat_byte(0xb1));
+
+	assert!(!is_canon::<TestBlock, _>(client.as_ref(), b1));
+
+	let b2 = import_block(&mut client, b1, H256::repeat_byte(0xb2));
+
+	assert!(is_canon::<TestBlock, _>(client.as_ref(), b1));
+	assert!(!is_canon::<TestBlock, _>(client.as_ref(), a1));
+	assert!(sync_mapping_batch(client.as_ref(), &instance, 16).unwrap());
+	assert_eq!(synced_best(&client), Some(b2));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa1)), None);
+	assert_eq!(canonical(&client, H256::repeat_byte(0xb1)), Some(b1));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xb2)), Some(b2));
+
+	// And back, the cursor restarts from the genesis, one block per batch.
+	let a2 = import_block(&mut client, a1, H256::repeat_byte(0xa2));
+	let a3 = import_block(&mut client, a2, H256::repeat_byte(0xa3));
+
+	assert!(!sync_mapping_batch(client.as_ref(), &instance, 1).unwrap());
+	assert_eq!(synced_best(&client), Some(a1));
+	assert!(!sync_mapping_batch(client.as_ref(), &instance, 1).unwrap());
+	assert!(sync_mapping_batch(client.as_ref(), &instance, 1).unwrap());
+	assert_eq!(synced_best(&client), Some(a3));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa1)), Some(a1));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa3)), Some(a3));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xb1)), None);
+	assert_eq!(canonical(&client, H256::repeat_byte(0xb2)), None);
+}
+
+#[test]
+fn mapping_restarts_from_finalized_block_without_cursor_block() {
+	let mut client = Arc::new(TestClientBuilder::new().build());
+	let instance = EthereumInstance::default();
+	let genesis = client.info().genesis_hash;
+	let a1 = import_block(&mut client, genesis, H256::repeat_byte(0xa1));
+	let a2 = import_block(&mut client, a1, H256::repeat_byte(0xa2));
+
+	client.finalize_block(BlockId::Hash(a1), None).unwrap();
+	// The header of the cursor block is gone, e.g. pruned.
+	aux_schema::write_synced_best(b"", H256::repeat_byte(9), |insert| {
+		client.insert_aux(insert, &[])
+	})
+	.unwrap();
+
+	assert!(sync_mapping_batch(client.as_ref(), &instance, 16).unwrap());
+	assert_eq!(synced_best(&client), Some(a2));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa1)), None);
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa2)), Some(a2));
+}
+
+#[test]
+fn mapping_sync_worker_follows_the_best_fork() {
+	let mut client = Arc::new(TestClientBuilder::new().build());
+	let genesis = client.info().genesis_hash;
+	let worker_client = client.clone();
+
+	thread::spawn(move || {
+		futures::executor::block_on(run_mapping_sync_worker(
+			worker_client,
+			EthereumInstance::default(),
+			None,
+		))
+	});
+
+	let wait_for = |client: &TestClient, ethereum_block_hash, expected| {
+		assert!((0..100).any(|_| {
+			thread::sleep(Duration::from_millis(50));
+
+			canonical(client, ethereum_block_hash) == expected
+		}));
+	};
+	let a1 = import_block(&mut client, genesis, H256::repeat_byte(0xa1));
+
+	wait_for(client.as_ref(), H256::repeat_byte(0xa1), Some(a1));
+
+	let b1 = import_block(&mut client, genesis, H256::repeat_byte(0xb1));
+	let b2 = import_block(&mut client, b1, H256::repeat_byte(0xb2));
+
+	wait_for(client.as_ref(), H256::repeat_byte(0xb2), Some(b2));
+	assert_eq!(canonical(&client, H256::repeat_byte(0xa1)), None);
+	assert_eq!(canonical(&client, H256::repeat_byte(0xb1)), Some(b1));
+}
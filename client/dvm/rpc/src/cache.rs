@@ -0,0 +1,152 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of the Ethereum data stored by Substrate blocks.
+
+use crate::internal_err;
+use dvm_rpc_runtime_api::{EthereumRuntimeRPCApi, TransactionStatus};
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use log::warn;
+use lru::LruCache;
+use parking_lot::Mutex;
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use sha3::{Digest, Keccak256};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+/// The Ethereum block of a Substrate block, with its receipts and transaction statuses.
+pub struct EthereumBlockData {
+	/// The Ethereum block.
+	pub block: ethereum::Block,
+	/// The hash of the Ethereum block.
+	pub hash: H256,
+	/// The receipts of the transactions, in block order.
+	pub receipts: Vec<ethereum::Receipt>,
+	/// The statuses of the transactions, in block order.
+	pub statuses: Vec<TransactionStatus>,
+}
+
+struct Metrics {
+	hits: Counter<U64>,
+	misses: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> std::result::Result<Self, PrometheusError> {
+		Ok(Self {
+			hits: register(
+				Counter::new(
+					"dvm_block_data_cache_hits",
+					"Number of Ethereum block data lookups served from the cache",
+				)?,
+				registry,
+			)?,
+			misses: register(
+				Counter::new(
+					"dvm_block_data_cache_misses",
+					"Number of Ethereum block data lookups served by the runtime",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// LRU cache of the Ethereum data of Substrate blocks, keyed by the Substrate block hash and
+/// shared by the RPC handlers.
+///
+/// The data of a Substrate block never changes, so the entries are never invalidated.
+pub struct EthereumBlockDataCache<B: BlockT> {
+	/// `None` if the cache is disabled.
+	cache: Option<Mutex<LruCache<B::Hash, Option<Arc<EthereumBlockData>>>>>,
+	metrics: Option<Metrics>,
+}
+
+impl<B: BlockT> EthereumBlockDataCache<B> {
+	/// Keep the data of at most `size` blocks, zero disables the cache. The hit and miss
+	/// counters are registered to `registry` if given.
+	pub fn new(size: usize, registry: Option<&Registry>) -> Self {
+		let metrics = registry.and_then(|registry| match Metrics::register(registry) {
+			Ok(metrics) => Some(metrics),
+			Err(e) => {
+				warn!("Failed to register the block data cache metrics: {:?}", e);
+
+				None
+			}
+		});
+
+		Self {
+			cache: if size == 0 {
+				None
+			} else {
+				Some(Mutex::new(LruCache::new(size)))
+			},
+			metrics,
+		}
+	}
+
+	/// Get the Ethereum data of a Substrate block, `None` if the block is unknown or has no
+	/// Ethereum block.
+	pub fn get<C>(&self, client: &C, id: &BlockId<B>) -> Result<Option<Arc<EthereumBlockData>>>
+	where
+		C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+	{
+		let hash = match client
+			.block_hash_from_id(id)
+			.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?
+		{
+			Some(hash) => hash,
+			None => return Ok(None),
+		};
+
+		if let Some(cache) = &self.cache {
+			if let Some(data) = cache.lock().get(&hash) {
+				if let Some(metrics) = &self.metrics {
+					metrics.hits.inc();
+				}
+
+				return Ok(data.clone());
+			}
+		}
+		if let Some(metrics) = &self.metrics {
+			metrics.misses.inc();
+		}
+
+		let data = match client
+			.runtime_api()
+			.current_all(&BlockId::Hash(hash))
+			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?
+		{
+			(Some(block), Some(receipts), Some(statuses)) => Some(Arc::new(EthereumBlockData {
+				hash: H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice()),
+				block,
+				receipts,
+				statuses,
+			})),
+			_ => None,
+		};
+
+		if let Some(cache) = &self.cache {
+			cache.lock().put(hash, data.clone());
+		}
+
+		Ok(data)
+	}
+}
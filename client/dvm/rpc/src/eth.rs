@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::cache::{EthereumBlockData, EthereumBlockDataCache};
 use crate::{error_on_execution_failure, internal_err, EthSigner};
 use codec::{self, Encode};
+use dvm_consensus::EthereumInstance;
 use dvm_rpc_core::{EthApi as EthApiT, NetApi as NetApiT, Web3Api as Web3ApiT};
 use dvm_rpc_core_primitives::{
 	Block, BlockNumber, BlockTransactions, Bytes, CallRequest, Filter, FilteredParams, Index, Log,
@@ -23,8 +25,7 @@ use dvm_rpc_core_primitives::{
 };
 use dvm_rpc_runtime_api::{ConvertTransaction, EthereumRuntimeRPCApi, TransactionStatus};
 use ethereum::{
-	Block as EthereumBlock, Transaction as EthereumTransaction,
-	TransactionMessage as EthereumTransactionMessage,
+	Transaction as EthereumTransaction, TransactionMessage as EthereumTransactionMessage,
 };
 use ethereum_types::{H160, H256, H512, H64, U256, U64};
 use futures::future::TryFutureExt;
@@ -42,7 +43,7 @@ use sp_runtime::traits::{Block as BlockT, One, Saturating, UniqueSaturatedInto,
 use sp_runtime::transaction_validity::TransactionSource;
 use sp_transaction_pool::{InPoolTransaction, TransactionPool};
 use std::collections::BTreeMap;
-use std::{marker::PhantomData, sync::Arc};
+use std::{iter, marker::PhantomData, sync::Arc};
 
 pub use dvm_rpc_core::{EthApiServer, NetApiServer, Web3ApiServer};
 
@@ -51,6 +52,8 @@ pub struct EthApi<B: BlockT, C, P, CT, BE, H: ExHashT> {
 	client: Arc<C>,
 	convert_transaction: CT,
 	network: Arc<NetworkService<B, H>>,
+	instance: EthereumInstance,
+	block_data_cache: Arc<EthereumBlockDataCache<B>>,
 	is_authority: bool,
 	signers: Vec<Box<dyn EthSigner>>,
 	_marker: PhantomData<(B, BE)>,
@@ -62,6 +65,8 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H> {
 		pool: Arc<P>,
 		convert_transaction: CT,
 		network: Arc<NetworkService<B, H>>,
+		instance: EthereumInstance,
+		block_data_cache: Arc<EthereumBlockDataCache<B>>,
 		is_authority: bool,
 	) -> Self {
 		Self {
@@ -69,6 +74,8 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H> {
 			pool,
 			convert_transaction,
 			network,
+			instance,
+			block_data_cache,
 			is_authority,
 			signers: Vec::new(),
 			_marker: PhantomData,
@@ -76,17 +83,12 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H> {
 	}
 }
 
-fn rich_block_build(
-	block: ethereum::Block,
-	statuses: Vec<Option<TransactionStatus>>,
-	hash: Option<H256>,
-	full_transactions: bool,
-) -> RichBlock {
+fn rich_block_build(data: &EthereumBlockData, full_transactions: bool) -> RichBlock {
+	let block = &data.block;
+
 	Rich {
 		inner: Block {
-			hash: Some(hash.unwrap_or_else(|| {
-				H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice())
-			})),
+			hash: Some(data.hash),
 			parent_hash: block.header.parent_hash,
 			uncles_hash: block.header.ommers_hash,
 			author: block.header.beneficiary,
@@ -117,8 +119,8 @@ fn rich_block_build(
 							.map(|(index, transaction)| {
 								transaction_build(
 									transaction.clone(),
-									block.clone(),
-									statuses[index].clone().unwrap_or_default(),
+									data,
+									data.statuses.get(index).cloned().unwrap_or_default(),
 								)
 							})
 							.collect(),
@@ -138,7 +140,7 @@ fn rich_block_build(
 					)
 				}
 			},
-			size: Some(U256::from(rlp::encode(&block).len() as u32)),
+			size: Some(U256::from(rlp::encode(block).len() as u32)),
 		},
 		extra_info: BTreeMap::new(),
 	}
@@ -146,7 +148,7 @@ fn rich_block_build(
 
 fn transaction_build(
 	transaction: EthereumTransaction,
-	block: EthereumBlock,
+	data: &EthereumBlockData,
 	status: TransactionStatus,
 ) -> Transaction {
 	let mut sig = [0u8; 65];
@@ -164,10 +166,8 @@ fn transaction_build(
 	Transaction {
 		hash: H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice()),
 		nonce: transaction.nonce,
-		block_hash: Some(H256::from_slice(
-			Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
-		)),
-		block_number: Some(block.header.number),
+		block_hash: Some(data.hash),
+		block_number: Some(data.block.header.number),
 		transaction_index: Some(U256::from(
 			UniqueSaturatedInto::<u32>::unique_saturated_into(status.transaction_index),
 		)),
@@ -188,6 +188,51 @@ fn transaction_build(
 	}
 }
 
+fn receipt_build(data: &EthereumBlockData, index: usize) -> Option<Receipt> {
+	let (block, block_hash) = (&data.block, data.hash);
+	let receipt = data.receipts.get(index)?;
+	let status = data.statuses.get(index)?;
+	let previous_receipts = &data.receipts[..index];
+	let cumulative_gas: u32 = previous_receipts
+		.iter()
+		.chain(iter::once(receipt))
+		.map(|r| r.used_gas.as_u32())
+		.sum();
+	let pre_receipts_log_index: u32 = previous_receipts.iter().map(|r| r.logs.len() as u32).sum();
+
+	Some(Receipt {
+		transaction_hash: Some(status.transaction_hash),
+		transaction_index: Some(status.transaction_index.into()),
+		block_hash: Some(block_hash),
+		from: Some(status.from),
+		to: status.to,
+		block_number: Some(block.header.number),
+		cumulative_gas_used: U256::from(cumulative_gas),
+		gas_used: Some(receipt.used_gas),
+		contract_address: status.contract_address,
+		logs: receipt
+			.logs
+			.iter()
+			.enumerate()
+			.map(|(i, log)| Log {
+				address: log.address,
+				topics: log.topics.clone(),
+				data: Bytes(log.data.clone()),
+				block_hash: Some(block_hash),
+				block_number: Some(block.header.number),
+				transaction_hash: Some(status.transaction_hash),
+				transaction_index: Some(status.transaction_index.into()),
+				log_index: Some(U256::from(pre_receipts_log_index + i as u32)),
+				transaction_log_index: Some(U256::from(i)),
+				removed: false,
+			})
+			.collect(),
+		status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
+		logs_bloom: receipt.logs_bloom,
+		state_root: None,
+	})
+}
+
 impl<B, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H>
 where
 	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + AuxStore,
@@ -210,29 +255,18 @@ where
 		})
 	}
 
-	// Asumes there is only one mapped canonical block in the AuxStore, otherwise something is wrong
+	// The Ethereum block might be mapped to several Substrate blocks on forks, only the one on
+	// the canonical chain is returned
 	fn load_hash(&self, hash: H256) -> Result<Option<BlockId<B>>> {
-		let hashes = match dvm_consensus::load_block_hash::<B, _>(self.client.as_ref(), hash)
-			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?
-		{
-			Some(hashes) => hashes,
-			None => return Ok(None),
-		};
-		let out: Vec<H256> = hashes
-			.into_iter()
-			.filter_map(|h| {
-				if let Ok(Some(_)) = self.client.header(BlockId::Hash(h)) {
-					Some(h)
-				} else {
-					None
-				}
-			})
-			.collect();
+		let hash =
+			dvm_consensus::canonical_block_hash::<B, _>(self.client.as_ref(), &self.instance, hash)
+				.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?;
 
-		if out.len() == 1 {
-			return Ok(Some(BlockId::Hash(out[0])));
-		}
-		Ok(None)
+		Ok(hash.map(BlockId::Hash))
+	}
+
+	fn block_data(&self, id: &BlockId<B>) -> Result<Option<Arc<EthereumBlockData>>> {
+		self.block_data_cache.get(self.client.as_ref(), id)
 	}
 }
 
@@ -362,26 +396,9 @@ where
 			_ => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(rich_block_build(
-				block,
-				statuses.into_iter().map(|s| Some(s)).collect(),
-				Some(hash),
-				full,
-			))),
-			_ => Ok(None),
-		}
+		Ok(self
+			.block_data(&id)?
+			.map(|data| rich_block_build(&data, full)))
 	}
 
 	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
@@ -390,31 +407,9 @@ where
 			None => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => {
-				let hash =
-					H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-
-				Ok(Some(rich_block_build(
-					block,
-					statuses.into_iter().map(|s| Some(s)).collect(),
-					Some(hash),
-					full,
-				)))
-			}
-			_ => Ok(None),
-		}
+		Ok(self
+			.block_data(&id)?
+			.map(|data| rich_block_build(&data, full)))
 	}
 
 	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
@@ -469,18 +464,9 @@ where
 			_ => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| {
-				internal_err(format!("fetch runtime account basic failed: {:?}", err))
-			})?;
-
-		match block {
-			Some(block) => Ok(Some(U256::from(block.transactions.len()))),
-			None => Ok(None),
-		}
+		Ok(self
+			.block_data(&id)?
+			.map(|data| U256::from(data.block.transactions.len())))
 	}
 
 	fn block_transaction_count_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
@@ -489,18 +475,9 @@ where
 			None => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| {
-				internal_err(format!("fetch runtime account basic failed: {:?}", err))
-			})?;
-
-		match block {
-			Some(block) => Ok(Some(U256::from(block.transactions.len()))),
-			None => Ok(None),
-		}
+		Ok(self
+			.block_data(&id)?
+			.map(|data| U256::from(data.block.transactions.len())))
 	}
 
 	fn block_uncles_count_by_hash(&self, _: H256) -> Result<U256> {
@@ -795,41 +772,27 @@ where
 	}
 
 	fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
-		let (hash, index) =
-			match dvm_consensus::load_transaction_metadata(self.client.as_ref(), hash)
-				.map_err(|err| internal_err(format!("fetch aux store failed: {:?})", err)))?
-			{
-				Some((hash, index)) => (hash, index as usize),
-				None => return Ok(None),
-			};
-
-		let id = match self
-			.load_hash(hash)
-			.map_err(|err| internal_err(format!("{:?}", err)))?
+		let (id, index) = match dvm_consensus::canonical_transaction_metadata::<B, _>(
+			self.client.as_ref(),
+			&self.instance,
+			hash,
+		)
+		.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?
 		{
-			Some(hash) => hash,
-			_ => return Ok(None),
+			Some(metadata) => (
+				BlockId::Hash(metadata.block_hash),
+				metadata.ethereum_index as usize,
+			),
+			None => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				block,
-				statuses[index].clone(),
-			))),
-			_ => Ok(None),
-		}
+		Ok(self.block_data(&id)?.and_then(|data| {
+			Some(transaction_build(
+				data.block.transactions.get(index)?.clone(),
+				&data,
+				data.statuses.get(index)?.clone(),
+			))
+		}))
 	}
 
 	fn transaction_by_block_hash_and_index(
@@ -846,25 +809,13 @@ where
 		};
 		let index = index.value();
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				block,
-				statuses[index].clone(),
-			))),
-			_ => Ok(None),
-		}
+		Ok(self.block_data(&id)?.and_then(|data| {
+			Some(transaction_build(
+				data.block.transactions.get(index)?.clone(),
+				&data,
+				data.statuses.get(index)?.clone(),
+			))
+		}))
 	}
 
 	fn transaction_by_block_number_and_index(
@@ -878,123 +829,46 @@ where
 		};
 		let index = index.value();
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				block,
-				statuses[index].clone(),
-			))),
-			_ => Ok(None),
-		}
+		Ok(self.block_data(&id)?.and_then(|data| {
+			Some(transaction_build(
+				data.block.transactions.get(index)?.clone(),
+				&data,
+				data.statuses.get(index)?.clone(),
+			))
+		}))
 	}
 
 	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
-		let (hash, index) =
-			match dvm_consensus::load_transaction_metadata(self.client.as_ref(), hash)
-				.map_err(|err| internal_err(format!("fetch aux store failed : {:?}", err)))?
-			{
-				Some((hash, index)) => (hash, index as usize),
-				None => return Ok(None),
-			};
-
-		let id = match self
-			.load_hash(hash)
-			.map_err(|err| internal_err(format!("{:?}", err)))?
+		let (id, index) = match dvm_consensus::canonical_transaction_metadata::<B, _>(
+			self.client.as_ref(),
+			&self.instance,
+			hash,
+		)
+		.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?
 		{
-			Some(hash) => hash,
-			_ => return Ok(None),
+			Some(metadata) => (
+				BlockId::Hash(metadata.block_hash),
+				metadata.ethereum_index as usize,
+			),
+			None => return Ok(None),
 		};
 
-		let block = self
-			.client
-			.runtime_api()
-			.current_block(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let receipts = self
-			.client
-			.runtime_api()
-			.current_receipts(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-		let statuses = self
-			.client
-			.runtime_api()
-			.current_transaction_statuses(&id)
-			.map_err(|err| internal_err(format!("call runtime failed: {:?}", err)))?;
-
-		match (block, statuses, receipts) {
-			(Some(block), Some(statuses), Some(receipts)) => {
-				let block_hash =
-					H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-				let receipt = receipts[index].clone();
-				let status = statuses[index].clone();
-				let mut cumulative_receipts = receipts.clone();
-				cumulative_receipts.truncate((status.transaction_index + 1) as usize);
-
-				return Ok(Some(Receipt {
-					transaction_hash: Some(status.transaction_hash),
-					transaction_index: Some(status.transaction_index.into()),
-					block_hash: Some(block_hash),
-					from: Some(status.from),
-					to: status.to,
-					block_number: Some(block.header.number),
-					cumulative_gas_used: {
-						let cumulative_gas: u32 = cumulative_receipts
-							.iter()
-							.map(|r| r.used_gas.as_u32())
-							.sum();
-						U256::from(cumulative_gas)
-					},
-					gas_used: Some(receipt.used_gas),
-					contract_address: status.contract_address,
-					logs: {
-						let mut pre_receipts_log_index = None;
-						if cumulative_receipts.len() > 0 {
-							cumulative_receipts.truncate(cumulative_receipts.len() - 1);
-							pre_receipts_log_index = Some(
-								cumulative_receipts
-									.iter()
-									.map(|r| r.logs.len() as u32)
-									.sum::<u32>(),
-							);
-						}
-						receipt
-							.logs
-							.iter()
-							.enumerate()
-							.map(|(i, log)| Log {
-								address: log.address,
-								topics: log.topics.clone(),
-								data: Bytes(log.data.clone()),
-								block_hash: Some(block_hash),
-								block_number: Some(block.header.number),
-								transaction_hash: Some(hash),
-								transaction_index: Some(status.transaction_index.into()),
-								log_index: Some(U256::from(
-									(pre_receipts_log_index.unwrap_or(0)) + i as u32,
-								)),
-								transaction_log_index: Some(U256::from(i)),
-								removed: false,
-							})
-							.collect()
-					},
-					status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
-					logs_bloom: receipt.logs_bloom,
-					state_root: None,
-				}));
-			}
-			_ => Ok(None),
-		}
+		Ok(self
+			.block_data(&id)?
+			.and_then(|data| receipt_build(&data, index)))
+	}
+
+	fn block_receipts(&self, number: BlockNumber) -> Result<Option<Vec<Receipt>>> {
+		let id = match self.native_block_id(Some(number))? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+
+		Ok(self.block_data(&id)?.map(|data| {
+			(0..data.receipts.len())
+				.filter_map(|index| receipt_build(&data, index))
+				.collect()
+		}))
 	}
 
 	fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> Result<Option<RichBlock>> {
@@ -1010,7 +884,7 @@ where
 	}
 
 	fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
-		let mut blocks_and_statuses = Vec::new();
+		let mut blocks = Vec::new();
 		let mut ret = Vec::new();
 		let params = FilteredParams::new(Some(filter.clone()));
 
@@ -1023,13 +897,8 @@ where
 				_ => return Ok(Vec::new()),
 			};
 
-			let (block, _, statuses) =
-				self.client.runtime_api().current_all(&id).map_err(|err| {
-					internal_err(format!("fetch runtime account basic failed: {:?}", err))
-				})?;
-
-			if let (Some(block), Some(statuses)) = (block, statuses) {
-				blocks_and_statuses.push((block, statuses));
+			if let Some(data) = self.block_data(&id)? {
+				blocks.push(data);
 			}
 		} else {
 			let mut current_number = filter
@@ -1046,13 +915,8 @@ where
 			while current_number >= from_number {
 				let id = BlockId::Number(current_number);
 
-				let (block, _, statuses) =
-					self.client.runtime_api().current_all(&id).map_err(|err| {
-						internal_err(format!("fetch runtime account basic failed: {:?}", err))
-					})?;
-
-				if let (Some(block), Some(statuses)) = (block, statuses) {
-					blocks_and_statuses.push((block, statuses));
+				if let Some(data) = self.block_data(&id)? {
+					blocks.push(data);
 				}
 
 				if current_number == Zero::zero() {
@@ -1063,11 +927,10 @@ where
 			}
 		}
 
-		for (block, statuses) in blocks_and_statuses {
+		for data in blocks {
+			let (block, block_hash) = (&data.block, data.hash);
 			let mut block_log_index: u32 = 0;
-			let block_hash =
-				H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-			for status in statuses.iter() {
+			for status in data.statuses.iter() {
 				let logs = status.logs.clone();
 				let mut transaction_log_index: u32 = 0;
 				let transaction_hash = status.transaction_hash;
@@ -32,6 +32,8 @@ use sha3::{Digest, Keccak256};
 pub use dvm_rpc_core::EthPubSubApiServer;
 use futures::{StreamExt as _, TryStreamExt as _};
 
+use crate::cache::{EthereumBlockData, EthereumBlockDataCache};
+use crate::metrics::RpcMetrics;
 use dvm_rpc_runtime_api::{EthereumRuntimeRPCApi, TransactionStatus};
 use jsonrpc_core::{
 	futures::{Future, Sink},
@@ -68,6 +70,8 @@ pub struct EthPubSubApi<B: BlockT, P, C, BE, H: ExHashT> {
 	client: Arc<C>,
 	network: Arc<NetworkService<B, H>>,
 	subscriptions: SubscriptionManager<HexEncodedIdProvider>,
+	block_data_cache: Arc<EthereumBlockDataCache<B>>,
+	metrics: Option<RpcMetrics>,
 	_marker: PhantomData<(B, BE)>,
 }
 impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApi<B, P, C, BE, H> {
@@ -76,12 +80,16 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApi<B, P, C, BE, H> {
 		client: Arc<C>,
 		network: Arc<NetworkService<B, H>>,
 		subscriptions: SubscriptionManager<HexEncodedIdProvider>,
+		block_data_cache: Arc<EthereumBlockDataCache<B>>,
+		metrics: Option<RpcMetrics>,
 	) -> Self {
 		Self {
 			_pool,
 			client,
 			network,
 			subscriptions,
+			block_data_cache,
+			metrics,
 			_marker: PhantomData,
 		}
 	}
@@ -92,12 +100,12 @@ impl SubscriptionResult {
 	pub fn new() -> Self {
 		SubscriptionResult {}
 	}
-	pub fn new_heads(&self, block: ethereum::Block) -> PubSubResult {
+	pub fn new_heads(&self, data: &EthereumBlockData) -> PubSubResult {
+		let block = &data.block;
+
 		PubSubResult::Header(Box::new(Rich {
 			inner: Header {
-				hash: Some(H256::from_slice(
-					Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
-				)),
+				hash: Some(data.hash),
 				parent_hash: block.header.parent_hash,
 				uncles_hash: block.header.ommers_hash,
 				author: block.header.beneficiary,
@@ -116,38 +124,31 @@ impl SubscriptionResult {
 					Bytes(block.header.mix_hash.as_bytes().to_vec()),
 					Bytes(block.header.nonce.as_bytes().to_vec()),
 				],
-				size: Some(U256::from(rlp::encode(&block).len() as u32)),
+				size: Some(U256::from(rlp::encode(block).len() as u32)),
 			},
 			extra_info: BTreeMap::new(),
 		}))
 	}
-	pub fn logs(
-		&self,
-		block: ethereum::Block,
-		receipts: Vec<ethereum::Receipt>,
-		params: &FilteredParams,
-	) -> Vec<Log> {
-		let block_hash = Some(H256::from_slice(
-			Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
-		));
+	pub fn logs(&self, data: &EthereumBlockData, params: &FilteredParams) -> Vec<Log> {
+		let block = &data.block;
+		let block_hash = Some(data.hash);
 		let mut logs: Vec<Log> = vec![];
 		let mut log_index: u32 = 0;
-		for (receipt_index, receipt) in receipts.into_iter().enumerate() {
+		for (receipt_index, receipt) in data.receipts.iter().enumerate() {
 			let mut transaction_log_index: u32 = 0;
 			let transaction_hash: Option<H256> = if receipt.logs.len() > 0 {
-				Some(H256::from_slice(
-					Keccak256::digest(&rlp::encode(&block.transactions[receipt_index as usize]))
-						.as_slice(),
-				))
+				data.statuses
+					.get(receipt_index)
+					.map(|status| status.transaction_hash)
 			} else {
 				None
 			};
-			for log in receipt.logs {
-				if self.add_log(block_hash.unwrap(), &log, &block, params) {
+			for log in receipt.logs.iter() {
+				if self.add_log(data.hash, log, block, params) {
 					logs.push(Log {
 						address: log.address,
-						topics: log.topics,
-						data: Bytes(log.data),
+						topics: log.topics.clone(),
+						data: Bytes(log.data.clone()),
 						block_hash,
 						block_number: Some(block.header.number),
 						transaction_hash,
@@ -239,6 +240,9 @@ where
 		};
 		let client = self.client.clone();
 		let network = self.network.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		// Dropped along with the subscription, whichever side ends it.
+		let active = self.metrics.as_ref().map(RpcMetrics::subscription);
 		match kind {
 			Kind::Logs => {
 				if let Some(stream) = stream_build!(
@@ -246,18 +250,16 @@ where
 				) {
 					self.subscriptions.add(subscriber, |sink| {
 						let stream = stream
-							.flat_map(move |(block_hash, changes)| {
-								let id = BlockId::Hash(block_hash);
-								let data = changes.iter().last().unwrap().2.unwrap();
-								let receipts: Vec<ethereum::Receipt> =
-									Decode::decode(&mut &data.0[..]).unwrap();
-								let block: ethereum::Block =
-									client.runtime_api().current_block(&id).unwrap().unwrap();
-								futures::stream::iter(SubscriptionResult::new().logs(
-									block,
-									receipts,
-									&filtered_params,
-								))
+							.flat_map(move |(block_hash, _changes)| {
+								let logs = match block_data_cache
+									.get(client.as_ref(), &BlockId::Hash(block_hash))
+								{
+									Ok(Some(data)) => {
+										SubscriptionResult::new().logs(&data, &filtered_params)
+									}
+									_ => vec![],
+								};
+								futures::stream::iter(logs)
 							})
 							.map(|x| {
 								return Ok::<
@@ -269,7 +271,7 @@ where
 
 						sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
 							.send_all(stream)
-							.map(|_| ())
+							.map(move |_| drop(active))
 					});
 				}
 			}
@@ -279,17 +281,22 @@ where
 				) {
 					self.subscriptions.add(subscriber, |sink| {
 						let stream = stream
-							.map(|(_block, changes)| {
-								let data = changes.iter().last().unwrap().2.unwrap();
-								let block: ethereum::Block =
-									Decode::decode(&mut &data.0[..]).unwrap();
-								return Ok::<_, ()>(Ok(SubscriptionResult::new().new_heads(block)));
+							.filter_map(move |(block_hash, _changes)| {
+								let head = match block_data_cache
+									.get(client.as_ref(), &BlockId::Hash(block_hash))
+								{
+									Ok(Some(data)) => Some(Ok::<_, ()>(Ok(
+										SubscriptionResult::new().new_heads(&data),
+									))),
+									_ => None,
+								};
+								futures::future::ready(head)
 							})
 							.compat();
 
 						sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
 							.send_all(stream)
-							.map(|_| ())
+							.map(move |_| drop(active))
 					});
 				}
 			}
@@ -337,7 +344,7 @@ where
 
 						sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
 							.send_all(stream)
-							.map(|_| ())
+							.map(move |_| drop(active))
 					});
 				}
 			}
@@ -368,7 +375,7 @@ where
 							.compat();
 						sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
 							.send_all(stream)
-							.map(|_| ())
+							.map(move |_| drop(active))
 					});
 				}
 			}
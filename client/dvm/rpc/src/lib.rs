@@ -14,11 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
+mod cache;
 mod eth;
 mod eth_pubsub;
+mod metrics;
 
+pub use cache::{EthereumBlockData, EthereumBlockDataCache};
 pub use eth::{EthApi, EthApiServer, NetApi, NetApiServer, Web3Api, Web3ApiServer};
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer, HexEncodedIdProvider};
+pub use metrics::{run_dvm_metrics_worker, with_metrics, RpcMetrics};
+
 use ethereum_types::H160;
 
 use hyperspace_evm::ExitReason;
@@ -0,0 +1,253 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the Ethereum RPC and of the DVM blocks and transactions.
+
+use crate::cache::EthereumBlockDataCache;
+use dvm_rpc_runtime_api::EthereumRuntimeRPCApi;
+use futures::StreamExt;
+use jsonrpc_core::{
+	futures::Future, BoxFuture, Metadata, Params, RemoteProcedure, RpcMethod, Value,
+};
+use log::warn;
+use prometheus_endpoint::{
+	register, CounterVec, Gauge, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, U64,
+};
+use sc_client_api::BlockchainEvents;
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use sp_transaction_pool::{InPoolTransaction, TransactionPool};
+use std::{collections::HashMap, sync::Arc};
+
+/// Latency and errors of the RPC methods, and the active subscriptions.
+#[derive(Clone)]
+pub struct RpcMetrics {
+	calls_time: HistogramVec,
+	calls_failed: CounterVec<U64>,
+	subscriptions: Gauge<U64>,
+}
+
+impl RpcMetrics {
+	/// Register the metrics to `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			calls_time: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"dvm_rpc_calls_time",
+						"Time in seconds spent serving the Ethereum RPC calls",
+					),
+					&["method"],
+				)?,
+				registry,
+			)?,
+			calls_failed: register(
+				CounterVec::new(
+					Opts::new(
+						"dvm_rpc_calls_failed",
+						"Number of Ethereum RPC calls that returned an error",
+					),
+					&["method"],
+				)?,
+				registry,
+			)?,
+			subscriptions: register(
+				Gauge::new(
+					"dvm_rpc_subscriptions",
+					"Number of active Ethereum RPC subscriptions",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Count an active subscription until the returned guard is dropped.
+	pub(crate) fn subscription(&self) -> SubscriptionGuard {
+		self.subscriptions.inc();
+
+		SubscriptionGuard(self.subscriptions.clone())
+	}
+}
+
+pub(crate) struct SubscriptionGuard(Gauge<U64>);
+
+impl Drop for SubscriptionGuard {
+	fn drop(&mut self) {
+		self.0.dec();
+	}
+}
+
+struct InstrumentedMethod<M: Metadata> {
+	name: String,
+	method: Arc<dyn RpcMethod<M>>,
+	metrics: RpcMetrics,
+}
+
+impl<M: Metadata> RpcMethod<M> for InstrumentedMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let timer = self
+			.metrics
+			.calls_time
+			.with_label_values(&[self.name.as_str()])
+			.start_timer();
+		let calls_failed = self
+			.metrics
+			.calls_failed
+			.with_label_values(&[self.name.as_str()]);
+
+		Box::new(self.method.call(params, meta).then(move |result| {
+			timer.observe_duration();
+
+			if result.is_err() {
+				calls_failed.inc();
+			}
+
+			result
+		}))
+	}
+}
+
+/// Record the latency and errors of the methods of `delegate`, it's left as is without metrics.
+pub fn with_metrics<M, D>(
+	delegate: D,
+	metrics: Option<&RpcMetrics>,
+) -> HashMap<String, RemoteProcedure<M>>
+where
+	M: Metadata,
+	D: Into<HashMap<String, RemoteProcedure<M>>>,
+{
+	let methods = delegate.into();
+	let metrics = match metrics {
+		Some(metrics) => metrics,
+		None => return methods,
+	};
+
+	methods
+		.into_iter()
+		.map(|(name, method)| {
+			let method = match method {
+				RemoteProcedure::Method(method) => {
+					RemoteProcedure::Method(Arc::new(InstrumentedMethod {
+						name: name.clone(),
+						method,
+						metrics: metrics.clone(),
+					}) as Arc<dyn RpcMethod<M>>)
+				}
+				method => method,
+			};
+
+			(name, method)
+		})
+		.collect()
+}
+
+/// Gas and transactions of the DVM blocks, and the Ethereum transactions of the pool.
+struct DvmMetrics {
+	block_gas_used: Gauge<U64>,
+	block_transactions: Gauge<U64>,
+	pool_transactions: Gauge<U64>,
+}
+
+impl DvmMetrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			block_gas_used: register(
+				Gauge::new(
+					"dvm_block_gas_used",
+					"Gas used by the Ethereum block of the best block",
+				)?,
+				registry,
+			)?,
+			block_transactions: register(
+				Gauge::new(
+					"dvm_block_transactions",
+					"Number of transactions of the Ethereum block of the best block",
+				)?,
+				registry,
+			)?,
+			pool_transactions: register(
+				Gauge::new(
+					"dvm_pool_ready_transactions",
+					"Number of Ethereum transactions ready in the transaction pool",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Whether a pool transaction is an Ethereum transaction, the Ethereum pallet tags them with
+/// the `(H160, U256)` sender and nonce, where signed extrinsics use `(AccountId, Index)`.
+fn is_ethereum_transaction<T: InPoolTransaction>(transaction: &T) -> bool {
+	const ETHEREUM_TAG_LEN: usize = 20 + 32;
+
+	transaction
+		.provides()
+		.iter()
+		.any(|tag| tag.len() == ETHEREUM_TAG_LEN)
+}
+
+/// Update the DVM metrics on every new best block until the client stops.
+pub async fn run_dvm_metrics_worker<B, C, P>(
+	client: Arc<C>,
+	pool: Arc<P>,
+	block_data_cache: Arc<EthereumBlockDataCache<B>>,
+	registry: Registry,
+) where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockchainEvents<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	P: TransactionPool<Block = B>,
+{
+	let metrics = match DvmMetrics::register(&registry) {
+		Ok(metrics) => metrics,
+		Err(e) => {
+			warn!("Failed to register the DVM metrics: {:?}", e);
+
+			return;
+		}
+	};
+	let mut notifications = client.import_notification_stream();
+
+	while let Some(notification) = notifications.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+
+		match block_data_cache.get(client.as_ref(), &BlockId::Hash(notification.hash)) {
+			Ok(Some(data)) => {
+				metrics
+					.block_gas_used
+					.set(data.block.header.gas_used.low_u64());
+				metrics
+					.block_transactions
+					.set(data.block.transactions.len() as u64);
+			}
+			Ok(None) => {
+				metrics.block_gas_used.set(0);
+				metrics.block_transactions.set(0);
+			}
+			Err(e) => warn!("Failed to fetch the Ethereum block: {:?}", e),
+		}
+
+		metrics.pool_transactions.set(
+			pool.ready()
+				.filter(|transaction| is_ethereum_transaction(&**transaction))
+				.count() as u64,
+		);
+	}
+}
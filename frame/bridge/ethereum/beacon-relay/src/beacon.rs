@@ -0,0 +1,444 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Beacon chain containers used by the Altair light client protocol and their SSZ
+//! `hash_tree_root`.
+//!
+//! Only the containers a light client needs are modeled here, the beacon state and the beacon
+//! block body are proved through merkle branches against their roots.
+
+// --- crates ---
+use codec::{Decode, Encode};
+// --- substrate ---
+use sp_io::hashing::sha2_256;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+// --- hyperspace ---
+use ethereum_primitives::{Bloom, EthereumAddress, EthereumBlockNumber, H256, U256};
+
+pub type Slot = u64;
+pub type Epoch = u64;
+pub type SyncCommitteePeriod = u64;
+pub type ValidatorIndex = u64;
+pub type ForkVersion = [u8; 4];
+pub type DomainType = [u8; 4];
+pub type Domain = [u8; 32];
+
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+pub const DOMAIN_SYNC_COMMITTEE: DomainType = [7, 0, 0, 0];
+
+/// `get_generalized_index(BeaconBlockBody, 'execution_payload')`
+pub const EXECUTION_PAYLOAD_GINDEX: u64 = 25;
+/// `get_generalized_index(BeaconState, 'finalized_checkpoint', 'root')`
+pub const FINALIZED_ROOT_GINDEX: u64 = 105;
+/// `get_generalized_index(BeaconState, 'current_sync_committee')`
+pub const CURRENT_SYNC_COMMITTEE_GINDEX: u64 = 54;
+/// `get_generalized_index(BeaconState, 'next_sync_committee')`
+pub const NEXT_SYNC_COMMITTEE_GINDEX: u64 = 55;
+/// Electra grows the beacon state past 32 fields, every state proof gets one level deeper
+pub const FINALIZED_ROOT_GINDEX_ELECTRA: u64 = 169;
+pub const CURRENT_SYNC_COMMITTEE_GINDEX_ELECTRA: u64 = 86;
+pub const NEXT_SYNC_COMMITTEE_GINDEX_ELECTRA: u64 = 87;
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct BLSPublicKey(pub [u8; 48]);
+impl BLSPublicKey {
+	pub fn hash_tree_root(&self) -> H256 {
+		merkleize(&pack(&self.0))
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct BLSSignature(pub [u8; 96]);
+
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct BeaconBlockHeader {
+	pub slot: Slot,
+	pub proposer_index: ValidatorIndex,
+	pub parent_root: H256,
+	pub state_root: H256,
+	pub body_root: H256,
+}
+impl BeaconBlockHeader {
+	pub fn hash_tree_root(&self) -> H256 {
+		merkleize(&[
+			uint64_leaf(self.slot),
+			uint64_leaf(self.proposer_index),
+			self.parent_root,
+			self.state_root,
+			self.body_root,
+		])
+	}
+}
+
+/// The execution payload header since Capella, the blob gas fields are only merkleized since
+/// Deneb
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct ExecutionPayloadHeader {
+	pub parent_hash: H256,
+	pub fee_recipient: EthereumAddress,
+	pub state_root: H256,
+	pub receipts_root: H256,
+	pub logs_bloom: Bloom,
+	pub prev_randao: H256,
+	pub block_number: EthereumBlockNumber,
+	pub gas_limit: u64,
+	pub gas_used: u64,
+	pub timestamp: u64,
+	pub extra_data: Vec<u8>,
+	pub base_fee_per_gas: U256,
+	pub block_hash: H256,
+	pub transactions_root: H256,
+	pub withdrawals_root: H256,
+	pub blob_gas_used: u64,
+	pub excess_blob_gas: u64,
+}
+impl ExecutionPayloadHeader {
+	pub fn hash_tree_root(&self, deneb: bool) -> H256 {
+		let mut base_fee_per_gas = [0; 32];
+
+		self.base_fee_per_gas
+			.to_little_endian(&mut base_fee_per_gas);
+
+		let mut leaves = vec![
+			self.parent_hash,
+			merkleize(&pack(self.fee_recipient.as_bytes())),
+			self.state_root,
+			self.receipts_root,
+			merkleize(&pack(self.logs_bloom.as_bytes())),
+			self.prev_randao,
+			uint64_leaf(self.block_number),
+			uint64_leaf(self.gas_limit),
+			uint64_leaf(self.gas_used),
+			uint64_leaf(self.timestamp),
+			mix_in_length(
+				merkleize_with_limit(&pack(&self.extra_data), 1),
+				self.extra_data.len(),
+			),
+			H256(base_fee_per_gas),
+			self.block_hash,
+			self.transactions_root,
+			self.withdrawals_root,
+		];
+
+		if deneb {
+			leaves.push(uint64_leaf(self.blob_gas_used));
+			leaves.push(uint64_leaf(self.excess_blob_gas));
+		}
+
+		merkleize(&leaves)
+	}
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct LightClientHeader {
+	pub beacon: BeaconBlockHeader,
+	pub execution: ExecutionPayloadHeader,
+	pub execution_branch: Vec<H256>,
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct SyncCommittee {
+	pub pubkeys: Vec<BLSPublicKey>,
+	pub aggregate_pubkey: BLSPublicKey,
+}
+impl SyncCommittee {
+	pub fn hash_tree_root(&self) -> H256 {
+		let pubkeys = self
+			.pubkeys
+			.iter()
+			.map(BLSPublicKey::hash_tree_root)
+			.collect::<Vec<_>>();
+
+		hash_pair(
+			&merkleize_with_limit(&pubkeys, SYNC_COMMITTEE_SIZE),
+			&self.aggregate_pubkey.hash_tree_root(),
+		)
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct SyncAggregate {
+	/// `Bitvector[SYNC_COMMITTEE_SIZE]`, bit `i` is the `i % 8` bit of the `i / 8` byte
+	pub sync_committee_bits: [u8; SYNC_COMMITTEE_SIZE / 8],
+	pub sync_committee_signature: BLSSignature,
+}
+impl SyncAggregate {
+	pub fn is_participant(&self, index: usize) -> bool {
+		self.sync_committee_bits[index / 8] >> (index % 8) & 1 == 1
+	}
+
+	pub fn num_of_participants(&self) -> usize {
+		self.sync_committee_bits
+			.iter()
+			.map(|byte| byte.count_ones() as usize)
+			.sum()
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct LightClientBootstrap {
+	pub header: LightClientHeader,
+	pub current_sync_committee: SyncCommittee,
+	pub current_sync_committee_branch: Vec<H256>,
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct LightClientUpdate {
+	/// Header attested to by the sync committee
+	pub attested_header: LightClientHeader,
+	/// Next sync committee corresponding to `attested_header.beacon.state_root`
+	pub next_sync_committee: Option<SyncCommittee>,
+	pub next_sync_committee_branch: Vec<H256>,
+	/// Finalized header corresponding to `attested_header.beacon.state_root`
+	pub finalized_header: LightClientHeader,
+	pub finality_branch: Vec<H256>,
+	/// Sync committee aggregate signature
+	pub sync_aggregate: SyncAggregate,
+	/// Slot at which the aggregate signature was created (untrusted)
+	pub signature_slot: Slot,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct Fork {
+	pub version: ForkVersion,
+	pub epoch: Epoch,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct BeaconChainSpec {
+	pub genesis_validators_root: H256,
+	pub genesis_fork_version: ForkVersion,
+	pub altair: Fork,
+	pub bellatrix: Fork,
+	pub capella: Fork,
+	pub deneb: Fork,
+	pub electra: Fork,
+	pub fulu: Fork,
+}
+impl BeaconChainSpec {
+	pub fn mainnet() -> Self {
+		Self {
+			genesis_validators_root: array_bytes::hex2array_unchecked!(
+				"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95",
+				32
+			)
+			.into(),
+			genesis_fork_version: [0, 0, 0, 0],
+			altair: Fork {
+				version: [1, 0, 0, 0],
+				epoch: 74240,
+			},
+			bellatrix: Fork {
+				version: [2, 0, 0, 0],
+				epoch: 144896,
+			},
+			capella: Fork {
+				version: [3, 0, 0, 0],
+				epoch: 194048,
+			},
+			deneb: Fork {
+				version: [4, 0, 0, 0],
+				epoch: 269568,
+			},
+			electra: Fork {
+				version: [5, 0, 0, 0],
+				epoch: 364032,
+			},
+			fulu: Fork {
+				version: [6, 0, 0, 0],
+				epoch: 411392,
+			},
+		}
+	}
+
+	pub fn fork_version(&self, epoch: Epoch) -> ForkVersion {
+		[
+			&self.fulu,
+			&self.electra,
+			&self.deneb,
+			&self.capella,
+			&self.bellatrix,
+			&self.altair,
+		]
+		.iter()
+		.find(|fork| epoch >= fork.epoch)
+		.map(|fork| fork.version)
+		.unwrap_or(self.genesis_fork_version)
+	}
+
+	pub fn finalized_root_gindex(&self, slot: Slot) -> u64 {
+		if compute_epoch_at_slot(slot) >= self.electra.epoch {
+			FINALIZED_ROOT_GINDEX_ELECTRA
+		} else {
+			FINALIZED_ROOT_GINDEX
+		}
+	}
+
+	pub fn current_sync_committee_gindex(&self, slot: Slot) -> u64 {
+		if compute_epoch_at_slot(slot) >= self.electra.epoch {
+			CURRENT_SYNC_COMMITTEE_GINDEX_ELECTRA
+		} else {
+			CURRENT_SYNC_COMMITTEE_GINDEX
+		}
+	}
+
+	pub fn next_sync_committee_gindex(&self, slot: Slot) -> u64 {
+		if compute_epoch_at_slot(slot) >= self.electra.epoch {
+			NEXT_SYNC_COMMITTEE_GINDEX_ELECTRA
+		} else {
+			NEXT_SYNC_COMMITTEE_GINDEX
+		}
+	}
+
+	/// A light client header carries the execution payload header since Capella, headers of the
+	/// former forks are of no use to the relay
+	pub fn is_valid_light_client_header(&self, header: &LightClientHeader) -> bool {
+		let epoch = compute_epoch_at_slot(header.beacon.slot);
+
+		if epoch < self.capella.epoch {
+			return false;
+		}
+
+		is_valid_merkle_branch(
+			header.execution.hash_tree_root(epoch >= self.deneb.epoch),
+			&header.execution_branch,
+			EXECUTION_PAYLOAD_GINDEX,
+			header.beacon.body_root,
+		)
+	}
+
+	pub fn compute_domain(&self, domain_type: DomainType, fork_version: ForkVersion) -> Domain {
+		let mut fork_version_leaf = H256::zero();
+
+		fork_version_leaf[..4].copy_from_slice(&fork_version);
+
+		let fork_data_root = hash_pair(&fork_version_leaf, &self.genesis_validators_root);
+		let mut domain = [0; 32];
+
+		domain[..4].copy_from_slice(&domain_type);
+		domain[4..].copy_from_slice(&fork_data_root[..28]);
+
+		domain
+	}
+
+	/// The sync committee signs the attested header with the fork of the slot before the
+	/// signature slot
+	pub fn sync_committee_signing_root(
+		&self,
+		attested_header: &BeaconBlockHeader,
+		signature_slot: Slot,
+	) -> H256 {
+		let fork_version = self.fork_version(compute_epoch_at_slot(signature_slot.max(1) - 1));
+
+		hash_pair(
+			&attested_header.hash_tree_root(),
+			&H256(self.compute_domain(DOMAIN_SYNC_COMMITTEE, fork_version)),
+		)
+	}
+}
+
+pub fn compute_epoch_at_slot(slot: Slot) -> Epoch {
+	slot / SLOTS_PER_EPOCH
+}
+
+pub fn compute_sync_committee_period_at_slot(slot: Slot) -> SyncCommitteePeriod {
+	compute_epoch_at_slot(slot) / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+/// Check that `leaf` sits at the generalized index `gindex` of the tree rooted at `root`
+pub fn is_valid_merkle_branch(leaf: H256, branch: &[H256], gindex: u64, root: H256) -> bool {
+	let depth = 63 - gindex.leading_zeros() as usize;
+
+	if gindex == 0 || branch.len() != depth {
+		return false;
+	}
+
+	let mut value = leaf;
+
+	for (i, node) in branch.iter().enumerate() {
+		value = if gindex >> i & 1 == 1 {
+			hash_pair(node, &value)
+		} else {
+			hash_pair(&value, node)
+		};
+	}
+
+	value == root
+}
+
+pub(crate) fn hash_pair(left: &H256, right: &H256) -> H256 {
+	let mut buffer = [0; 64];
+
+	buffer[..32].copy_from_slice(left.as_bytes());
+	buffer[32..].copy_from_slice(right.as_bytes());
+
+	sha2_256(&buffer).into()
+}
+
+fn uint64_leaf(value: u64) -> H256 {
+	let mut leaf = H256::zero();
+
+	leaf[..8].copy_from_slice(&value.to_le_bytes());
+
+	leaf
+}
+
+fn pack(bytes: &[u8]) -> Vec<H256> {
+	bytes
+		.chunks(32)
+		.map(|chunk| {
+			let mut leaf = H256::zero();
+
+			leaf[..chunk.len()].copy_from_slice(chunk);
+
+			leaf
+		})
+		.collect()
+}
+
+fn mix_in_length(root: H256, length: usize) -> H256 {
+	hash_pair(&root, &uint64_leaf(length as u64))
+}
+
+fn merkleize(chunks: &[H256]) -> H256 {
+	merkleize_with_limit(chunks, chunks.len())
+}
+
+/// Merkleize `chunks` as the leaves of a tree with `limit` leaves, padding with zero hashes
+fn merkleize_with_limit(chunks: &[H256], limit: usize) -> H256 {
+	let depth = limit.next_power_of_two().trailing_zeros();
+	let mut layer = chunks.to_vec();
+	let mut zero_hash = H256::zero();
+
+	for _ in 0..depth {
+		if layer.len() % 2 == 1 {
+			layer.push(zero_hash);
+		}
+
+		layer = layer
+			.chunks(2)
+			.map(|pair| hash_pair(&pair[0], &pair[1]))
+			.collect();
+		zero_hash = hash_pair(&zero_hash, &zero_hash);
+	}
+
+	layer.pop().unwrap_or(zero_hash)
+}
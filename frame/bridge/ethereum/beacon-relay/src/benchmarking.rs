@@ -0,0 +1,274 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for hyperspace_ethereum_beacon_relay
+//!
+//! The sync committee is made of the deterministic test keys `1..=SYNC_COMMITTEE_SIZE`, so the
+//! updates can be signed by any number of participants. The beacon states and block bodies only
+//! hold the proved fields, every other node of their trees is zero.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+// --- crates ---
+use bls12_381::{
+	hash_to_curve::{ExpandMsgXmd, HashToCurve},
+	G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+};
+use sha2::Sha256;
+// --- substrate ---
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_std::collections::btree_map::BTreeMap;
+// --- hyperspace ---
+use crate::{bls::DST, *};
+
+const SLOTS_PER_SYNC_COMMITTEE_PERIOD: u64 = EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+
+/// The committee of the secret keys `1..=SYNC_COMMITTEE_SIZE`
+fn sync_committee() -> SyncCommittee {
+	let mut pubkeys = vec![G1Projective::generator(); SYNC_COMMITTEE_SIZE];
+
+	for i in 1..SYNC_COMMITTEE_SIZE {
+		pubkeys[i] = pubkeys[i - 1] + G1Projective::generator();
+	}
+
+	let aggregate_pubkey = pubkeys
+		.iter()
+		.fold(G1Projective::identity(), |sum, pubkey| sum + pubkey);
+	let mut affine_pubkeys = vec![G1Affine::identity(); SYNC_COMMITTEE_SIZE];
+
+	G1Projective::batch_normalize(&pubkeys, &mut affine_pubkeys);
+
+	SyncCommittee {
+		pubkeys: affine_pubkeys
+			.iter()
+			.map(|pubkey| BLSPublicKey(pubkey.to_compressed()))
+			.collect(),
+		aggregate_pubkey: BLSPublicKey(G1Affine::from(aggregate_pubkey).to_compressed()),
+	}
+}
+
+/// The first `participants` members of `sync_committee` sign `signing_root`
+fn sync_aggregate(participants: usize, signing_root: H256) -> SyncAggregate {
+	let mut sync_committee_bits = [0; SYNC_COMMITTEE_SIZE / 8];
+
+	for i in 0..participants {
+		sync_committee_bits[i / 8] |= 1 << (i % 8);
+	}
+
+	let secret_keys_sum = (participants * (participants + 1) / 2) as u64;
+	let message = <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
+		signing_root.as_bytes(),
+		DST,
+	);
+
+	SyncAggregate {
+		sync_committee_bits,
+		sync_committee_signature: BLSSignature(
+			G2Affine::from(message * Scalar::from(secret_keys_sum)).to_compressed(),
+		),
+	}
+}
+
+/// The root of a tree holding `leaves` at their generalized indices, and their branches
+fn merkleize_sparse(leaves: &[(u64, H256)]) -> (H256, Vec<Vec<H256>>) {
+	let mut nodes = leaves.iter().cloned().collect::<BTreeMap<_, _>>();
+	let mut gindex = nodes.keys().next_back().cloned().unwrap_or(1);
+
+	while gindex > 1 {
+		if let Some(node) = nodes.get(&gindex).cloned() {
+			let sibling = nodes.get(&(gindex ^ 1)).cloned().unwrap_or_default();
+			let parent = if gindex & 1 == 1 {
+				hash_pair(&sibling, &node)
+			} else {
+				hash_pair(&node, &sibling)
+			};
+
+			nodes.entry(gindex / 2).or_insert(parent);
+		}
+
+		gindex -= 1;
+	}
+
+	let branches = leaves
+		.iter()
+		.map(|(mut gindex, _)| {
+			let mut branch = vec![];
+
+			while gindex > 1 {
+				branch.push(nodes.get(&(gindex ^ 1)).cloned().unwrap_or_default());
+				gindex /= 2;
+			}
+
+			branch
+		})
+		.collect();
+
+	(nodes.get(&1).cloned().unwrap_or_default(), branches)
+}
+
+/// A light client header at `slot` whose beacon state holds `state_leaves`, and their branches
+fn light_client_header(
+	slot: Slot,
+	block_number: EthereumBlockNumber,
+	state_leaves: &[(u64, H256)],
+) -> (LightClientHeader, Vec<Vec<H256>>) {
+	let execution = ExecutionPayloadHeader {
+		block_number,
+		block_hash: H256::from_low_u64_be(block_number),
+		..Default::default()
+	};
+	let (body_root, mut execution_branches) =
+		merkleize_sparse(&[(EXECUTION_PAYLOAD_GINDEX, execution.hash_tree_root(true))]);
+	let (state_root, state_branches) = merkleize_sparse(state_leaves);
+
+	(
+		LightClientHeader {
+			beacon: BeaconBlockHeader {
+				slot,
+				state_root,
+				body_root,
+				..Default::default()
+			},
+			execution,
+			execution_branch: execution_branches.remove(0),
+		},
+		state_branches,
+	)
+}
+
+/// The next execution header stored prunes the oldest one
+fn fill_execution_headers() {
+	let block_hash = H256::repeat_byte(0xff);
+
+	ExecutionHeaderHashes::insert(ExecutionHeaderCursor::get(), block_hash);
+	ExecutionHeaders::insert(block_hash, ExecutionHeaderBrief::default());
+}
+
+/// The first slot of the second sync committee period since Electra
+fn electra_period_start<T: Config>() -> Slot {
+	let electra_slot = T::ChainSpec::get().electra.epoch * SLOTS_PER_EPOCH;
+
+	(compute_sync_committee_period_at_slot(electra_slot) + 1) * SLOTS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+benchmarks! {
+	// The worst case finalizes a header of the next period, rotates the sync committees and
+	// prunes an execution header
+	submit_update {
+		// The least participation accepted is two thirds of the sync committee
+		let p in ((SYNC_COMMITTEE_SIZE as u32 * 2 + 2) / 3) .. SYNC_COMMITTEE_SIZE as u32;
+
+		let chain_spec = T::ChainSpec::get();
+		let store_slot = electra_period_start::<T>();
+		let finalized_slot = store_slot + SLOTS_PER_SYNC_COMMITTEE_PERIOD;
+		let attested_slot = finalized_slot + 2 * SLOTS_PER_EPOCH;
+		let sync_committee = sync_committee();
+		let (finalized_header, _) = light_client_header(finalized_slot, 2, &[]);
+		let (attested_header, mut state_branches) = light_client_header(
+			attested_slot,
+			3,
+			&[
+				(
+					chain_spec.finalized_root_gindex(attested_slot),
+					finalized_header.beacon.hash_tree_root(),
+				),
+				(
+					chain_spec.next_sync_committee_gindex(attested_slot),
+					sync_committee.hash_tree_root(),
+				),
+			],
+		);
+		let signature_slot = attested_slot + 1;
+		let signing_root =
+			chain_spec.sync_committee_signing_root(&attested_header.beacon, signature_slot);
+		let update = LightClientUpdate {
+			attested_header,
+			next_sync_committee: Some(sync_committee.clone()),
+			next_sync_committee_branch: state_branches.remove(1),
+			finalized_header,
+			finality_branch: state_branches.remove(0),
+			sync_aggregate: sync_aggregate(p as usize, signing_root),
+			signature_slot,
+		};
+		let caller: T::AccountId = whitelisted_caller();
+
+		FinalizedHeader::put(BeaconBlockHeader {
+			slot: store_slot,
+			..Default::default()
+		});
+		CurrentSyncCommittee::put(&sync_committee);
+		NextSyncCommittee::put(&sync_committee);
+		fill_execution_headers();
+	}: _(RawOrigin::Signed(caller), update)
+	verify {
+		assert_eq!(
+			<Module<T>>::finalized_header().map(|header| header.slot),
+			Some(finalized_slot)
+		);
+	}
+
+	force_bootstrap {
+		let chain_spec = T::ChainSpec::get();
+		let slot = electra_period_start::<T>();
+		let current_sync_committee = sync_committee();
+		let (header, mut state_branches) = light_client_header(
+			slot,
+			1,
+			&[(
+				chain_spec.current_sync_committee_gindex(slot),
+				current_sync_committee.hash_tree_root(),
+			)],
+		);
+		let bootstrap = LightClientBootstrap {
+			header,
+			current_sync_committee,
+			current_sync_committee_branch: state_branches.remove(0),
+		};
+
+		fill_execution_headers();
+	}: _(RawOrigin::Root, bootstrap)
+	verify {
+		assert_eq!(<Module<T>>::finalized_header().map(|header| header.slot), Some(slot));
+	}
+
+	set_receipt_verify_fee {
+		let fee = T::Currency::minimum_balance();
+	}: _(RawOrigin::Root, fee)
+	verify {
+		assert_eq!(<Module<T>>::receipt_verify_fee(), fee);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// --- substrate ---
+	use frame_support::assert_ok;
+	// --- hyperspace ---
+	use super::*;
+	use crate::mock::{ExtBuilder, Test};
+
+	#[test]
+	fn test_benchmarks() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_submit_update::<Test>());
+			assert_ok!(test_benchmark_force_bootstrap::<Test>());
+			assert_ok!(test_benchmark_set_receipt_verify_fee::<Test>());
+		});
+	}
+}
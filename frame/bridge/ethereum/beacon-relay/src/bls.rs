@@ -0,0 +1,80 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! BLS12-381 signature verification of the beacon chain, public keys in G1 and signatures in G2.
+
+// --- crates ---
+use bls12_381::{
+	hash_to_curve::{ExpandMsgXmd, HashToCurve},
+	multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
+};
+use sha2::Sha256;
+// --- hyperspace ---
+use crate::beacon::{BLSPublicKey, BLSSignature};
+
+/// Domain separation tag of the proof of possession scheme used by the beacon chain
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// `FastAggregateVerify`, every public key signed the same message
+pub fn fast_aggregate_verify(
+	pubkeys: &[BLSPublicKey],
+	message: &[u8],
+	signature: &BLSSignature,
+) -> bool {
+	if pubkeys.is_empty() {
+		return false;
+	}
+
+	let mut aggregate_pubkey = G1Projective::identity();
+
+	for pubkey in pubkeys {
+		if let Some(pubkey) = decompress_pubkey(pubkey) {
+			aggregate_pubkey += pubkey;
+		} else {
+			return false;
+		}
+	}
+
+	let signature = if let Some(signature) =
+		Option::<G2Affine>::from(G2Affine::from_compressed(&signature.0))
+	{
+		signature
+	} else {
+		return false;
+	};
+	let message = G2Affine::from(
+		<G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(message, DST),
+	);
+
+	// e(pk, H(m)) == e(g1, sig)
+	multi_miller_loop(&[
+		(
+			&G1Affine::from(aggregate_pubkey),
+			&G2Prepared::from(message),
+		),
+		(&-G1Affine::generator(), &G2Prepared::from(signature)),
+	])
+	.final_exponentiation()
+		== Gt::identity()
+}
+
+/// `KeyValidate`, the point must be in the subgroup and must not be the identity
+fn decompress_pubkey(pubkey: &BLSPublicKey) -> Option<G1Affine> {
+	Option::<G1Affine>::from(G1Affine::from_compressed(&pubkey.0))
+		.filter(|pubkey| !bool::from(pubkey.is_identity()))
+}
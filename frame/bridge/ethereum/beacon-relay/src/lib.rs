@@ -0,0 +1,534 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! # Hyperspace-ethereum-beacon-relay Module
+//!
+//! Light client module for bridging in the Ethereum proof of stake chain.
+//!
+//! ## Overview
+//!
+//! Since the merge, Ethereum blocks are no longer sealed by Ethash, so the proof of work relays
+//! can't follow the chain any more. This module implements the Altair sync committee light client
+//! protocol instead:
+//!
+//! 	- The module is bootstrapped (genesis or root) with a trusted beacon header and the sync
+//!       committee of its period, proved against the header's state root.
+//! 	- Relayers submit light client updates. An update is accepted if at least two thirds of
+//!       the sync committee signed the attested header, and it carries a finality proof of the
+//!       finalized header against the attested state root.
+//! 	- The next sync committee is learned from the updates of the current period, and it becomes
+//!       the current one once a header of the next period gets finalized.
+//!
+//! The execution payload header of every finalized beacon header is kept, so the receipts of the
+//! finalized execution blocks can be verified through the `EthereumReceipt` trait.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![recursion_limit = "128"]
+
+pub mod beacon;
+pub mod bls;
+pub mod weights;
+
+mod benchmarking;
+// --- hyperspace ---
+pub use weights::WeightInfo;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+mod types {
+	// --- hyperspace ---
+	use crate::*;
+
+	pub type Balance<T> = <CurrencyT<T> as Currency<AccountId<T>>>::Balance;
+
+	type AccountId<T> = <T as frame_system::Config>::AccountId;
+
+	type CurrencyT<T> = <T as Config>::Currency;
+}
+
+// --- crates ---
+use codec::{Decode, Encode};
+// --- substrate ---
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	traits::{Currency, Get},
+};
+use frame_system::{ensure_root, ensure_signed};
+use sp_runtime::{
+	traits::AccountIdConversion, DispatchError, DispatchResult, ModuleId, RuntimeDebug,
+};
+use sp_std::prelude::*;
+// --- hyperspace ---
+use beacon::*;
+use ethereum_primitives::{
+	receipt::{EthereumReceipt, EthereumReceiptProof, EthereumTransactionIndex},
+	EthereumBlockNumber, H256,
+};
+use hyperspace_support::traits::EthereumReceipt as EthereumReceiptT;
+use types::*;
+
+pub trait Config: frame_system::Config {
+	/// The ethereum-beacon-relay's module id, used for deriving its sovereign account ID.
+	type ModuleId: Get<ModuleId>;
+
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+	/// The genesis and the forks of the followed beacon chain.
+	type ChainSpec: Get<BeaconChainSpec>;
+
+	type Currency: Currency<Self::AccountId>;
+
+	/// Number of the latest execution headers kept to verify the receipts against, the older ones
+	/// are pruned.
+	type MaxExecutionHeaders: Get<u32>;
+
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
+}
+
+decl_event! {
+	pub enum Event<T>
+	where
+		<T as frame_system::Config>::AccountId,
+	{
+		/// The light client was bootstrapped. [slot, sync committee period]
+		Bootstrap(Slot, SyncCommitteePeriod),
+		/// A beacon header was finalized. [relayer, slot, execution block number, execution block hash]
+		FinalizeHeader(AccountId, Slot, EthereumBlockNumber, H256),
+		/// The next sync committee became the current one. [sync committee period]
+		RotateSyncCommittee(SyncCommitteePeriod),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// Finalized Header - NOT EXISTED
+		FinalizedHeaderNE,
+		/// Current Sync Committee - NOT EXISTED
+		CurrentSyncCommitteeNE,
+		/// Next Sync Committee - NOT EXISTED
+		NextSyncCommitteeNE,
+		/// Execution Header - NOT EXISTED
+		ExecutionHeaderNE,
+
+		/// Update - TOO OLD
+		UpdateTO,
+		/// Sync Committee Participation - TOO LOW
+		SyncCommitteeParticipationTL,
+
+		/// Next Sync Committee - MISMATCHED
+		NextSyncCommitteeMis,
+
+		/// Bootstrap - DECODE FAILED
+		BootstrapDcF,
+		/// Light Client Header - INVALID
+		LightClientHeaderInv,
+		/// Sync Committee - INVALID
+		SyncCommitteeInv,
+		/// Sync Committee Branch - INVALID
+		SyncCommitteeBranchInv,
+		/// Finality Branch - INVALID
+		FinalityBranchInv,
+		/// Update Slot - INVALID
+		UpdateSlotInv,
+		/// Update Period - INVALID
+		UpdatePeriodInv,
+		/// Ethereum Receipt Proof - INVALID
+		ReceiptProofInv,
+		/// Sync Committee Signature - VERIFICATION FAILED
+		SyncCommitteeSignatureVF,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as HyperspaceEthereumBeaconRelay {
+		/// The latest finalized beacon header
+		pub FinalizedHeader get(fn finalized_header): Option<BeaconBlockHeader>;
+
+		/// Sync committee of the finalized header's period
+		pub CurrentSyncCommittee get(fn current_sync_committee): Option<SyncCommittee>;
+		/// Sync committee of the period after the finalized header's, once it's relayed
+		pub NextSyncCommittee get(fn next_sync_committee): Option<SyncCommittee>;
+
+		/// Execution payload headers of the finalized beacon headers, by execution block hash
+		pub ExecutionHeaders
+			get(fn execution_header)
+			: map hasher(identity) H256 => Option<ExecutionHeaderBrief>;
+		/// Block hashes of the kept `ExecutionHeaders`, a ring of `MaxExecutionHeaders` slots
+		pub ExecutionHeaderHashes
+			get(fn execution_header_hash_at)
+			: map hasher(identity) u32 => Option<H256>;
+		/// Next slot of `ExecutionHeaderHashes`, it holds the oldest execution header
+		pub ExecutionHeaderCursor get(fn execution_header_cursor): u32;
+
+		pub ReceiptVerifyFee get(fn receipt_verify_fee) config(): Balance<T>;
+	}
+	add_extra_genesis {
+		// bootstrap: SCALE encoded `LightClientBootstrap`
+		config(bootstrap): Option<Vec<u8>>;
+		build(|config| {
+			if let Some(bootstrap) = &config.bootstrap {
+				if let Ok(bootstrap) = LightClientBootstrap::decode(&mut &bootstrap[..]) {
+					<Module<T>>::initialize(&bootstrap).unwrap();
+				} else {
+					panic!("{}", <&str>::from(<Error<T>>::BootstrapDcF));
+				}
+			}
+
+			let _ = T::Currency::make_free_balance_be(
+				&<Module<T>>::account_id(),
+				T::Currency::minimum_balance(),
+			);
+		});
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call
+	where
+		origin: T::Origin
+	{
+		type Error = Error<T>;
+
+		const ModuleId: ModuleId = T::ModuleId::get();
+
+		const ChainSpec: BeaconChainSpec = T::ChainSpec::get();
+
+		const MaxExecutionHeaders: u32 = T::MaxExecutionHeaders::get();
+
+		fn deposit_event() = default;
+
+		/// Relay a light client update, finalize its finalized header and learn or rotate the
+		/// sync committees.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of the participants of the sync committee signature,
+		///   a public key decompression each, plus a hash to curve and a pairing check
+		/// - Six storage reads
+		/// - Up to seven storage writes
+		/// - Up to two events
+		/// # </weight>
+		#[weight = T::WeightInfo::submit_update(
+			update.sync_aggregate.num_of_participants() as u32
+		)]
+		pub fn submit_update(origin, update: LightClientUpdate) {
+			let relayer = ensure_signed(origin)?;
+
+			Self::process_update(&relayer, &update)?;
+		}
+
+		// --- root call ---
+
+		/// Reset the light client to a trusted beacon header.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - Three storage reads
+		/// - Up to seven storage writes
+		/// - One event
+		/// # </weight>
+		#[weight = T::WeightInfo::force_bootstrap()]
+		pub fn force_bootstrap(origin, bootstrap: LightClientBootstrap) {
+			ensure_root(origin)?;
+
+			Self::initialize(&bootstrap)?;
+
+			let slot = bootstrap.header.beacon.slot;
+
+			<Module<T>>::deposit_event(RawEvent::Bootstrap(
+				slot,
+				compute_sync_committee_period_at_slot(slot),
+			));
+		}
+
+		/// Set verify receipt fee
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage write
+		/// # </weight>
+		#[weight = T::WeightInfo::set_receipt_verify_fee()]
+		pub fn set_receipt_verify_fee(origin, #[compact] new: Balance<T>) {
+			ensure_root(origin)?;
+			<ReceiptVerifyFee<T>>::put(new);
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// The account ID of the ethereum beacon relay pot.
+	///
+	/// This actually does computation. If you need to keep using it, then make sure you cache the
+	/// value and only call this once.
+	fn account_id() -> T::AccountId {
+		T::ModuleId::get().into_account()
+	}
+
+	/// Trust the bootstrap's header and take its sync committee as the current one.
+	pub fn initialize(bootstrap: &LightClientBootstrap) -> DispatchResult {
+		let chain_spec = T::ChainSpec::get();
+		let header = &bootstrap.header;
+
+		ensure!(
+			chain_spec.is_valid_light_client_header(header),
+			<Error<T>>::LightClientHeaderInv
+		);
+		ensure!(
+			bootstrap.current_sync_committee.pubkeys.len() == SYNC_COMMITTEE_SIZE,
+			<Error<T>>::SyncCommitteeInv
+		);
+		ensure!(
+			is_valid_merkle_branch(
+				bootstrap.current_sync_committee.hash_tree_root(),
+				&bootstrap.current_sync_committee_branch,
+				chain_spec.current_sync_committee_gindex(header.beacon.slot),
+				header.beacon.state_root,
+			),
+			<Error<T>>::SyncCommitteeBranchInv
+		);
+
+		FinalizedHeader::put(&header.beacon);
+		CurrentSyncCommittee::put(&bootstrap.current_sync_committee);
+		NextSyncCommittee::kill();
+
+		Self::store_execution_header(&header.execution);
+
+		Ok(())
+	}
+
+	/// `validate_light_client_update` then `apply_light_client_update`
+	///
+	/// The relay doesn't track optimistic headers, only the updates which finalize a newer header
+	/// or finalize the next sync committee are accepted.
+	fn process_update(relayer: &T::AccountId, update: &LightClientUpdate) -> DispatchResult {
+		let chain_spec = T::ChainSpec::get();
+		let finalized_header = Self::finalized_header().ok_or(<Error<T>>::FinalizedHeaderNE)?;
+		let current_sync_committee =
+			Self::current_sync_committee().ok_or(<Error<T>>::CurrentSyncCommitteeNE)?;
+		let next_sync_committee = Self::next_sync_committee();
+		let attested_header = &update.attested_header;
+		let sync_aggregate = &update.sync_aggregate;
+
+		ensure!(
+			sync_aggregate.num_of_participants() * 3 >= SYNC_COMMITTEE_SIZE * 2,
+			<Error<T>>::SyncCommitteeParticipationTL
+		);
+		ensure!(
+			chain_spec.is_valid_light_client_header(attested_header),
+			<Error<T>>::LightClientHeaderInv
+		);
+		ensure!(
+			update.signature_slot > attested_header.beacon.slot
+				&& attested_header.beacon.slot >= update.finalized_header.beacon.slot,
+			<Error<T>>::UpdateSlotInv
+		);
+
+		let store_period = compute_sync_committee_period_at_slot(finalized_header.slot);
+		let signature_period = compute_sync_committee_period_at_slot(update.signature_slot);
+		let attested_period = compute_sync_committee_period_at_slot(attested_header.beacon.slot);
+		let finalized_period =
+			compute_sync_committee_period_at_slot(update.finalized_header.beacon.slot);
+
+		if next_sync_committee.is_some() {
+			ensure!(
+				signature_period == store_period || signature_period == store_period + 1,
+				<Error<T>>::UpdatePeriodInv
+			);
+		} else {
+			ensure!(
+				signature_period == store_period,
+				<Error<T>>::UpdatePeriodInv
+			);
+		}
+
+		let has_finalized_next_sync_committee = next_sync_committee.is_none()
+			&& update.next_sync_committee.is_some()
+			&& attested_period == store_period
+			&& finalized_period == attested_period;
+
+		ensure!(
+			update.finalized_header.beacon.slot > finalized_header.slot
+				|| has_finalized_next_sync_committee,
+			<Error<T>>::UpdateTO
+		);
+
+		// Verify that the finalized header is the finalized checkpoint of the attested state
+		ensure!(
+			chain_spec.is_valid_light_client_header(&update.finalized_header),
+			<Error<T>>::LightClientHeaderInv
+		);
+		ensure!(
+			is_valid_merkle_branch(
+				update.finalized_header.beacon.hash_tree_root(),
+				&update.finality_branch,
+				chain_spec.finalized_root_gindex(attested_header.beacon.slot),
+				attested_header.beacon.state_root,
+			),
+			<Error<T>>::FinalityBranchInv
+		);
+
+		// Verify that the next sync committee is the one of the attested state
+		if let Some(update_next_sync_committee) = &update.next_sync_committee {
+			if attested_period == store_period {
+				if let Some(next_sync_committee) = &next_sync_committee {
+					ensure!(
+						next_sync_committee == update_next_sync_committee,
+						<Error<T>>::NextSyncCommitteeMis
+					);
+				}
+			}
+
+			ensure!(
+				update_next_sync_committee.pubkeys.len() == SYNC_COMMITTEE_SIZE,
+				<Error<T>>::SyncCommitteeInv
+			);
+			ensure!(
+				is_valid_merkle_branch(
+					update_next_sync_committee.hash_tree_root(),
+					&update.next_sync_committee_branch,
+					chain_spec.next_sync_committee_gindex(attested_header.beacon.slot),
+					attested_header.beacon.state_root,
+				),
+				<Error<T>>::SyncCommitteeBranchInv
+			);
+		}
+
+		// Verify the sync committee aggregate signature
+		{
+			let sync_committee = if signature_period == store_period {
+				&current_sync_committee
+			} else {
+				next_sync_committee
+					.as_ref()
+					.ok_or(<Error<T>>::NextSyncCommitteeNE)?
+			};
+			let participant_pubkeys = sync_committee
+				.pubkeys
+				.iter()
+				.enumerate()
+				.filter(|(i, _)| sync_aggregate.is_participant(*i))
+				.map(|(_, pubkey)| pubkey.clone())
+				.collect::<Vec<_>>();
+			let signing_root = chain_spec
+				.sync_committee_signing_root(&attested_header.beacon, update.signature_slot);
+
+			ensure!(
+				bls::fast_aggregate_verify(
+					&participant_pubkeys,
+					signing_root.as_bytes(),
+					&sync_aggregate.sync_committee_signature,
+				),
+				<Error<T>>::SyncCommitteeSignatureVF
+			);
+		}
+
+		if next_sync_committee.is_none() {
+			NextSyncCommittee::set(update.next_sync_committee.clone());
+		} else if finalized_period == store_period + 1 {
+			CurrentSyncCommittee::set(next_sync_committee);
+			NextSyncCommittee::set(update.next_sync_committee.clone());
+
+			<Module<T>>::deposit_event(RawEvent::RotateSyncCommittee(finalized_period));
+		}
+
+		if update.finalized_header.beacon.slot > finalized_header.slot {
+			let execution = &update.finalized_header.execution;
+
+			FinalizedHeader::put(&update.finalized_header.beacon);
+
+			Self::store_execution_header(execution);
+
+			<Module<T>>::deposit_event(RawEvent::FinalizeHeader(
+				relayer.clone(),
+				update.finalized_header.beacon.slot,
+				execution.block_number,
+				execution.block_hash,
+			));
+		}
+
+		Ok(())
+	}
+
+	/// Keep the execution header in the slot of the oldest one, which gets pruned
+	fn store_execution_header(execution: &ExecutionPayloadHeader) {
+		if ExecutionHeaders::contains_key(execution.block_hash) {
+			return;
+		}
+
+		let cursor = Self::execution_header_cursor();
+
+		if let Some(pruned_block_hash) = ExecutionHeaderHashes::get(cursor) {
+			ExecutionHeaders::remove(pruned_block_hash);
+		}
+
+		ExecutionHeaderHashes::insert(cursor, execution.block_hash);
+		ExecutionHeaderCursor::put((cursor + 1) % T::MaxExecutionHeaders::get().max(1));
+		ExecutionHeaders::insert(
+			execution.block_hash,
+			ExecutionHeaderBrief {
+				block_number: execution.block_number,
+				state_root: execution.state_root,
+				receipts_root: execution.receipts_root,
+			},
+		);
+	}
+}
+
+impl<T: Config> EthereumReceiptT<T::AccountId, Balance<T>> for Module<T> {
+	type EthereumReceiptProofThing = EthereumReceiptProof;
+
+	fn account_id() -> T::AccountId {
+		Self::account_id()
+	}
+
+	fn receipt_verify_fee() -> Balance<T> {
+		Self::receipt_verify_fee()
+	}
+
+	/// The block hash must be the one of a finalized execution payload, and the receipt must be
+	/// proved against its receipts root.
+	fn verify_receipt(
+		proof: &Self::EthereumReceiptProofThing,
+	) -> Result<EthereumReceipt, DispatchError> {
+		let execution_header =
+			Self::execution_header(&proof.header_hash).ok_or(<Error<T>>::ExecutionHeaderNE)?;
+		let receipt =
+			EthereumReceipt::verify_proof_and_generate(&execution_header.receipts_root, &proof)
+				.map_err(|_| <Error<T>>::ReceiptProofInv)?;
+
+		Ok(receipt)
+	}
+
+	fn gen_receipt_index(proof: &Self::EthereumReceiptProofThing) -> EthereumTransactionIndex {
+		(proof.header_hash, proof.index)
+	}
+}
+
+/// The part of a finalized execution payload header the receipts are verified against
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct ExecutionHeaderBrief {
+	/// Block number
+	pub block_number: EthereumBlockNumber,
+	/// State root of the block
+	pub state_root: H256,
+	/// Receipts root of the block
+	pub receipts_root: H256,
+}
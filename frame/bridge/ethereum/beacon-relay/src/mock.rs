@@ -0,0 +1,322 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mock file for ethereum-beacon-relay.
+
+// --- std ---
+use std::fs::File;
+// --- substrate ---
+use frame_support::{impl_outer_origin, parameter_types};
+use sp_runtime::{testing::Header, traits::IdentityLookup};
+// --- hyperspace ---
+use crate::*;
+use ethereum_primitives::{
+	receipt::{LogEntry, TransactionOutcome, TransactionType},
+	U256,
+};
+
+type AccountId = u64;
+type BlockNumber = u64;
+type Balance = u128;
+
+pub type System = frame_system::Module<Test>;
+pub type EthereumBeaconRelay = Module<Test>;
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+hyperspace_support::impl_test_account_data! { deprecated }
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Test;
+parameter_types! {
+	pub const EthereumBeaconRelayModuleId: ModuleId = ModuleId(*b"da/ethbr");
+	pub MainnetChainSpec: BeaconChainSpec = BeaconChainSpec::mainnet();
+	pub const MaxExecutionHeaders: u32 = 2;
+}
+impl Config for Test {
+	type ModuleId = EthereumBeaconRelayModuleId;
+	type Event = ();
+	type ChainSpec = MainnetChainSpec;
+	type Currency = Etp;
+	type MaxExecutionHeaders = MaxExecutionHeaders;
+	type WeightInfo = ();
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Hash = H256;
+	type Hashing = sp_runtime::traits::BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = ();
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+impl hyperspace_balances::Config<EtpInstance> for Test {
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ();
+	type BalanceInfo = AccountData<Balance>;
+	type AccountStore = System;
+	type MaxLocks = ();
+	type OtherCurrencies = ();
+	type WeightInfo = ();
+}
+
+pub struct ExtBuilder {
+	bootstrap: Option<LightClientBootstrap>,
+}
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		Self {
+			bootstrap: Some(mock_bootstrap()),
+		}
+	}
+}
+impl ExtBuilder {
+	pub fn bootstrap(mut self, bootstrap: Option<LightClientBootstrap>) -> Self {
+		self.bootstrap = bootstrap;
+		self
+	}
+
+	pub fn build(self) -> sp_io::TestExternalities {
+		let mut storage = frame_system::GenesisConfig::default()
+			.build_storage::<Test>()
+			.unwrap();
+
+		GenesisConfig::<Test> {
+			bootstrap: self.bootstrap.map(|bootstrap| bootstrap.encode()),
+			..Default::default()
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+		storage.into()
+	}
+}
+
+// The fixtures follow the layout of the beacon node light client API
+// (`/eth/v1/beacon/light_client/{bootstrap,updates}`), the slots are in the Electra fork of
+// mainnet, but they are NOT mainnet data: the containers, the sync committees and their signatures
+// are generated by deterministic test keys, signed under the mainnet domains
+//
+// | fixture                           | finalized slot | signed by period | next sync committee |
+// |-----------------------------------|----------------|------------------|---------------------|
+// | synthetic_bootstrap_12288064.json | 12288064       |                  |                     |
+// | synthetic_update_12288193.json    | 12288128       | 1500             | 1501                |
+// | synthetic_update_12296321.json    | 12296256       | 1501             | 1502                |
+
+/// The bootstrap at the start of the sync committee period 1500
+pub fn mock_bootstrap() -> LightClientBootstrap {
+	let data = data_from_file("./src/test-data/synthetic_bootstrap_12288064.json");
+
+	LightClientBootstrap {
+		header: light_client_header_from_json(&data["header"]),
+		current_sync_committee: sync_committee_from_json(&data["current_sync_committee"]),
+		current_sync_committee_branch: branch_from_json(&data["current_sync_committee_branch"]),
+	}
+}
+
+/// returns: [update of period 1500, update of period 1501]
+pub fn mock_updates() -> [LightClientUpdate; 2] {
+	[
+		update_from_file("./src/test-data/synthetic_update_12288193.json"),
+		update_from_file("./src/test-data/synthetic_update_12296321.json"),
+	]
+}
+
+/// The receipt of the mainnet USDT approval
+/// 0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616, placed as the second
+/// receipt of the finalized execution block of the update of period 1500
+pub fn mock_receipt() -> (EthereumReceipt, EthereumReceiptProof) {
+	let [update, _] = mock_updates();
+	let receipt = EthereumReceipt {
+		tx_type: TransactionType::DynamicFee,
+		..EthereumReceipt::new(
+			TransactionOutcome::StatusCode(1),
+			0xa42aec.into(),
+			vec![LogEntry {
+				address: array_bytes::hex2array_unchecked!(
+					"0xdac17f958d2ee523a2206206994597c13d831ec7",
+					20
+				)
+				.into(),
+				topics: vec![
+					array_bytes::hex2array_unchecked!(
+						"0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+						32
+					)
+					.into(),
+					array_bytes::hex2array_unchecked!(
+						"0x0000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2",
+						32
+					)
+					.into(),
+					array_bytes::hex2array_unchecked!(
+						"0x00000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76",
+						32
+					)
+					.into(),
+				],
+				data: array_bytes::hex2bytes_unchecked(
+					"0x00000000000000000000000000000000000000000052b7d2dcc80cd2e4000000",
+				),
+			}],
+		)
+	};
+	let proof = EthereumReceiptProof {
+		index: 1,
+		proof: array_bytes::hex2bytes_unchecked(RECEIPT_PROOF),
+		header_hash: update.finalized_header.execution.block_hash,
+	};
+
+	(receipt, proof)
+}
+
+fn data_from_file(path: &str) -> serde_json::Value {
+	let response: serde_json::Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+
+	response["data"].clone()
+}
+
+fn update_from_file(path: &str) -> LightClientUpdate {
+	let data = data_from_file(path);
+	let sync_aggregate = &data["sync_aggregate"];
+
+	LightClientUpdate {
+		attested_header: light_client_header_from_json(&data["attested_header"]),
+		next_sync_committee: Some(sync_committee_from_json(&data["next_sync_committee"])),
+		next_sync_committee_branch: branch_from_json(&data["next_sync_committee_branch"]),
+		finalized_header: light_client_header_from_json(&data["finalized_header"]),
+		finality_branch: branch_from_json(&data["finality_branch"]),
+		sync_aggregate: SyncAggregate {
+			sync_committee_bits: array_bytes::hex2array_unchecked!(
+				sync_aggregate["sync_committee_bits"].as_str().unwrap(),
+				64
+			),
+			sync_committee_signature: BLSSignature(array_bytes::hex2array_unchecked!(
+				sync_aggregate["sync_committee_signature"].as_str().unwrap(),
+				96
+			)),
+		},
+		signature_slot: u64_from_json(&data["signature_slot"]),
+	}
+}
+
+fn light_client_header_from_json(header: &serde_json::Value) -> LightClientHeader {
+	let beacon = &header["beacon"];
+	let execution = &header["execution"];
+
+	LightClientHeader {
+		beacon: BeaconBlockHeader {
+			slot: u64_from_json(&beacon["slot"]),
+			proposer_index: u64_from_json(&beacon["proposer_index"]),
+			parent_root: h256_from_json(&beacon["parent_root"]),
+			state_root: h256_from_json(&beacon["state_root"]),
+			body_root: h256_from_json(&beacon["body_root"]),
+		},
+		execution: ExecutionPayloadHeader {
+			parent_hash: h256_from_json(&execution["parent_hash"]),
+			fee_recipient: array_bytes::hex2array_unchecked!(
+				execution["fee_recipient"].as_str().unwrap(),
+				20
+			)
+			.into(),
+			state_root: h256_from_json(&execution["state_root"]),
+			receipts_root: h256_from_json(&execution["receipts_root"]),
+			logs_bloom: array_bytes::hex2array_unchecked!(
+				execution["logs_bloom"].as_str().unwrap(),
+				256
+			)
+			.into(),
+			prev_randao: h256_from_json(&execution["prev_randao"]),
+			block_number: u64_from_json(&execution["block_number"]),
+			gas_limit: u64_from_json(&execution["gas_limit"]),
+			gas_used: u64_from_json(&execution["gas_used"]),
+			timestamp: u64_from_json(&execution["timestamp"]),
+			extra_data: array_bytes::hex2bytes_unchecked(execution["extra_data"].as_str().unwrap()),
+			base_fee_per_gas: U256::from_dec_str(execution["base_fee_per_gas"].as_str().unwrap())
+				.unwrap(),
+			block_hash: h256_from_json(&execution["block_hash"]),
+			transactions_root: h256_from_json(&execution["transactions_root"]),
+			withdrawals_root: h256_from_json(&execution["withdrawals_root"]),
+			blob_gas_used: u64_from_json(&execution["blob_gas_used"]),
+			excess_blob_gas: u64_from_json(&execution["excess_blob_gas"]),
+		},
+		execution_branch: branch_from_json(&header["execution_branch"]),
+	}
+}
+
+fn sync_committee_from_json(sync_committee: &serde_json::Value) -> SyncCommittee {
+	SyncCommittee {
+		pubkeys: sync_committee["pubkeys"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(bls_public_key_from_json)
+			.collect(),
+		aggregate_pubkey: bls_public_key_from_json(&sync_committee["aggregate_pubkey"]),
+	}
+}
+
+fn bls_public_key_from_json(pubkey: &serde_json::Value) -> BLSPublicKey {
+	BLSPublicKey(array_bytes::hex2array_unchecked!(
+		pubkey.as_str().unwrap(),
+		48
+	))
+}
+
+fn branch_from_json(branch: &serde_json::Value) -> Vec<H256> {
+	branch
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(h256_from_json)
+		.collect()
+}
+
+fn h256_from_json(hash: &serde_json::Value) -> H256 {
+	array_bytes::hex2array_unchecked!(hash.as_str().unwrap(), 32).into()
+}
+
+// The beacon API encodes the integers as decimal strings
+fn u64_from_json(number: &serde_json::Value) -> u64 {
+	number.as_str().unwrap().parse().unwrap()
+}
+
+/// The receipts trie of the execution block holds a legacy receipt and the USDT approval
+pub const RECEIPT_PROOF: &'static str = "0xf9020df9020ab853f851a028ce6ece5744cb06f4a71edc08559414698a9084f52695b4cbd3ebadb451fe1e80808080808080a0e58215be848c1293dd381210359d84485553000a82b67410406d183b42adbbdd8080808080808080b901b2f901af31b901ab02f901a70183a42aecb9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000200000000000000000040000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000800000000000000000000000000000000004000000000000000000800000000100000020000000000000000000080000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000010000000000000000000000000000f89df89b94dac17f958d2ee523a2206206994597c13d831ec7f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2a000000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76a000000000000000000000000000000000000000000052b7d2dcc80cd2e4000000";
@@ -0,0 +1,372 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for ethereum-beacon-relay.
+
+// --- substrate ---
+use frame_support::{assert_err, assert_noop, assert_ok};
+use frame_system::RawOrigin;
+use sp_runtime::DispatchError;
+// --- hyperspace ---
+use crate::{mock::*, *};
+
+#[test]
+fn compute_domain_should_work() {
+	let chain_spec = BeaconChainSpec::mainnet();
+
+	// The fork digests of mainnet, which are the first four bytes of the fork data roots
+	for (fork_version, fork_digest) in [
+		([0, 0, 0, 0], [0xb5, 0x30, 0x3f, 0x2a]),
+		([1, 0, 0, 0], [0xaf, 0xca, 0xab, 0xa0]),
+		([2, 0, 0, 0], [0x4a, 0x26, 0xc5, 0x8b]),
+		([3, 0, 0, 0], [0xbb, 0xa4, 0xda, 0x96]),
+		([4, 0, 0, 0], [0x6a, 0x95, 0xa1, 0xa9]),
+	]
+	.iter()
+	{
+		let domain = chain_spec.compute_domain(DOMAIN_SYNC_COMMITTEE, *fork_version);
+
+		assert_eq!(domain[..4], DOMAIN_SYNC_COMMITTEE);
+		assert_eq!(domain[4..8], *fork_digest);
+	}
+}
+
+#[test]
+fn bootstrap_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let bootstrap = mock_bootstrap();
+		let execution = &bootstrap.header.execution;
+
+		assert_eq!(
+			EthereumBeaconRelay::finalized_header(),
+			Some(bootstrap.header.beacon.clone())
+		);
+		assert_eq!(
+			EthereumBeaconRelay::current_sync_committee(),
+			Some(bootstrap.current_sync_committee.clone())
+		);
+		assert_eq!(EthereumBeaconRelay::next_sync_committee(), None);
+		assert_eq!(
+			EthereumBeaconRelay::execution_header(&execution.block_hash),
+			Some(ExecutionHeaderBrief {
+				block_number: execution.block_number,
+				state_root: execution.state_root,
+				receipts_root: execution.receipts_root,
+			})
+		);
+	});
+}
+
+#[test]
+fn force_bootstrap_should_work() {
+	ExtBuilder::default()
+		.bootstrap(None)
+		.build()
+		.execute_with(|| {
+			let bootstrap = mock_bootstrap();
+			let [update, _] = mock_updates();
+
+			assert_err!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update.clone()),
+				<Error<Test>>::FinalizedHeaderNE
+			);
+			assert_noop!(
+				EthereumBeaconRelay::force_bootstrap(Origin::signed(1), bootstrap.clone()),
+				DispatchError::BadOrigin
+			);
+
+			assert_ok!(EthereumBeaconRelay::force_bootstrap(
+				RawOrigin::Root.into(),
+				bootstrap.clone()
+			));
+			assert_eq!(
+				EthereumBeaconRelay::finalized_header(),
+				Some(bootstrap.header.beacon)
+			);
+			assert_ok!(EthereumBeaconRelay::submit_update(
+				Origin::signed(1),
+				update
+			));
+		});
+}
+
+#[test]
+fn bootstrap_with_invalid_sync_committee_should_fail() {
+	ExtBuilder::default()
+		.bootstrap(None)
+		.build()
+		.execute_with(|| {
+			let mut bootstrap = mock_bootstrap();
+
+			bootstrap.current_sync_committee.pubkeys.swap(0, 1);
+
+			assert_noop!(
+				EthereumBeaconRelay::force_bootstrap(RawOrigin::Root.into(), bootstrap.clone()),
+				<Error<Test>>::SyncCommitteeBranchInv
+			);
+
+			bootstrap.current_sync_committee.pubkeys.pop();
+
+			assert_noop!(
+				EthereumBeaconRelay::force_bootstrap(RawOrigin::Root.into(), bootstrap.clone()),
+				<Error<Test>>::SyncCommitteeInv
+			);
+
+			let mut bootstrap = mock_bootstrap();
+
+			bootstrap.header.execution.receipts_root = Default::default();
+
+			assert_noop!(
+				EthereumBeaconRelay::force_bootstrap(RawOrigin::Root.into(), bootstrap),
+				<Error<Test>>::LightClientHeaderInv
+			);
+		});
+}
+
+#[test]
+fn submit_update_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let [update, _] = mock_updates();
+		let execution = &update.finalized_header.execution;
+
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(1),
+			update.clone()
+		));
+		assert_eq!(
+			EthereumBeaconRelay::finalized_header(),
+			Some(update.finalized_header.beacon.clone())
+		);
+		assert_eq!(
+			EthereumBeaconRelay::current_sync_committee(),
+			Some(mock_bootstrap().current_sync_committee)
+		);
+		assert_eq!(
+			EthereumBeaconRelay::next_sync_committee(),
+			update.next_sync_committee
+		);
+		assert_eq!(
+			EthereumBeaconRelay::execution_header(&execution.block_hash)
+				.unwrap()
+				.receipts_root,
+			execution.receipts_root
+		);
+
+		// The update finalizes nothing new
+		assert_noop!(
+			EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+			<Error<Test>>::UpdateTO
+		);
+	});
+}
+
+#[test]
+fn rotate_sync_committee_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let [update_1500, update_1501] = mock_updates();
+
+		// The next sync committee is unknown yet
+		assert_noop!(
+			EthereumBeaconRelay::submit_update(Origin::signed(1), update_1501.clone()),
+			<Error<Test>>::UpdatePeriodInv
+		);
+
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(1),
+			update_1500.clone()
+		));
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(2),
+			update_1501.clone()
+		));
+		assert_eq!(
+			EthereumBeaconRelay::finalized_header(),
+			Some(update_1501.finalized_header.beacon)
+		);
+		assert_eq!(
+			EthereumBeaconRelay::current_sync_committee(),
+			update_1500.next_sync_committee
+		);
+		assert_eq!(
+			EthereumBeaconRelay::next_sync_committee(),
+			update_1501.next_sync_committee
+		);
+	});
+}
+
+#[test]
+fn execution_headers_should_be_pruned() {
+	ExtBuilder::default().build().execute_with(|| {
+		let bootstrap_execution = mock_bootstrap().header.execution;
+		let [update_1500, update_1501] = mock_updates();
+
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(1),
+			update_1500.clone()
+		));
+		assert!(EthereumBeaconRelay::execution_header(&bootstrap_execution.block_hash).is_some());
+
+		// `MaxExecutionHeaders` is 2, the bootstrap's is the oldest
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(1),
+			update_1501.clone()
+		));
+		assert!(EthereumBeaconRelay::execution_header(&bootstrap_execution.block_hash).is_none());
+		assert!(EthereumBeaconRelay::execution_header(
+			&update_1500.finalized_header.execution.block_hash
+		)
+		.is_some());
+		assert!(EthereumBeaconRelay::execution_header(
+			&update_1501.finalized_header.execution.block_hash
+		)
+		.is_some());
+		assert_eq!(
+			EthereumBeaconRelay::execution_header_hash_at(0),
+			Some(update_1501.finalized_header.execution.block_hash)
+		);
+	});
+}
+
+#[test]
+fn update_should_be_signed_by_sync_committee() {
+	ExtBuilder::default().build().execute_with(|| {
+		let [update, _] = mock_updates();
+
+		{
+			let mut update = update.clone();
+
+			update.sync_aggregate.sync_committee_bits = [0; 64];
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::SyncCommitteeParticipationTL
+			);
+		}
+		{
+			let mut update = update.clone();
+
+			// Drop a participant
+			update.sync_aggregate.sync_committee_bits[0] ^= 1;
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::SyncCommitteeSignatureVF
+			);
+		}
+		{
+			let mut update = update.clone();
+
+			update.attested_header.beacon.proposer_index += 1;
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::SyncCommitteeSignatureVF
+			);
+		}
+		{
+			let mut update = update;
+
+			update.signature_slot += EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::UpdatePeriodInv
+			);
+		}
+	});
+}
+
+#[test]
+fn update_should_prove_its_headers() {
+	ExtBuilder::default().build().execute_with(|| {
+		let [update, _] = mock_updates();
+
+		{
+			let mut update = update.clone();
+
+			update.finalized_header.beacon.proposer_index += 1;
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::FinalityBranchInv
+			);
+		}
+		{
+			let mut update = update.clone();
+
+			update.finalized_header.execution.receipts_root = Default::default();
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::LightClientHeaderInv
+			);
+		}
+		{
+			let mut update = update.clone();
+
+			update.next_sync_committee = mock_updates()[1].next_sync_committee.clone();
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::SyncCommitteeBranchInv
+			);
+		}
+		{
+			let mut update = update;
+
+			update.signature_slot = update.attested_header.beacon.slot;
+
+			assert_noop!(
+				EthereumBeaconRelay::submit_update(Origin::signed(1), update),
+				<Error<Test>>::UpdateSlotInv
+			);
+		}
+	});
+}
+
+#[test]
+fn verify_receipt_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let [update, _] = mock_updates();
+		let (receipt, proof) = mock_receipt();
+
+		// The execution block isn't finalized yet
+		assert_err!(
+			<EthereumBeaconRelay as EthereumReceiptT<_, _>>::verify_receipt(&proof),
+			<Error<Test>>::ExecutionHeaderNE
+		);
+
+		assert_ok!(EthereumBeaconRelay::submit_update(
+			Origin::signed(1),
+			update
+		));
+		assert_eq!(
+			<EthereumBeaconRelay as EthereumReceiptT<_, _>>::verify_receipt(&proof).unwrap(),
+			receipt
+		);
+
+		let mut proof = proof;
+
+		proof.index = 0;
+
+		assert_err!(
+			<EthereumBeaconRelay as EthereumReceiptT<_, _>>::verify_receipt(&proof),
+			<Error<Test>>::ReceiptProofInv
+		);
+	});
+}
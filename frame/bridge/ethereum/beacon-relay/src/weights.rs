@@ -0,0 +1,68 @@
+//! Weights for hyperspace_ethereum_beacon_relay
+//!
+//! NOT GENERATED BY THE SUBSTRATE BENCHMARK CLI, the pallet is not in a runtime to run
+//! `benchmarking.rs` against yet.
+//! The computation of `submit_update` and `force_bootstrap` was timed natively, release profile,
+//! on the mainnet containers, 1, 256 and 512 participants, the slowest of the runs is kept:
+//!
+//! | participants | FastAggregateVerify |
+//! | ------------ | ------------------- |
+//! | 1            | 6.0 ms              |
+//! | 256          | 68.2 ms             |
+//! | 512          | 114.7 ms            |
+//!
+//! The hash tree root of a sync committee took 0.2 ms. Wasm execution is not measured, replace
+//! these with the benchmark CLI output once the pallet is wired into the runtime.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for hyperspace_ethereum_beacon_relay.
+pub trait WeightInfo {
+	fn submit_update(p: u32) -> Weight;
+	fn force_bootstrap() -> Weight;
+	fn set_receipt_verify_fee() -> Weight;
+}
+
+/// Weights for hyperspace_ethereum_beacon_relay using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn submit_update(p: u32) -> Weight {
+		(6_500_000_000 as Weight)
+			.saturating_add((245_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn force_bootstrap() -> Weight {
+		(300_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn set_receipt_verify_fee() -> Weight {
+		(10_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn submit_update(p: u32) -> Weight {
+		(6_500_000_000 as Weight)
+			.saturating_add((245_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn force_bootstrap() -> Weight {
+		(300_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn set_receipt_verify_fee() -> Weight {
+		(10_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
@@ -0,0 +1,620 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! # Hyperspace-ethereum-poa-relay Module
+//!
+//! Light client module for bridging in the proof of authority EVM sidechains, which seal their
+//! headers with Clique (EIP-225) or Parlia (BNB Smart Chain).
+//!
+//! ## Overview
+//!
+//! The module follows the chain from a trusted checkpoint header (genesis or root):
+//!
+//! 	- Relayers submit the headers one by one, each one must extend the finalized header or one
+//!       of its relayed descendants, so the unfinalized forks are relayed too.
+//! 	- A header is accepted if it's signed by a validator of its parent's validator set, the
+//!       validator didn't sign `turn_length` of the recent blocks of the fork, and the difficulty
+//!       tells whether it was the validator's turn.
+//! 	- The validator set is taken from the checkpoints' extra data. Clique switches to it right
+//!       after the checkpoint, Parlia once half of the old validators signed a turn after it.
+//! 	- The best header is the one with the highest total difficulty, the in-turn blocks weigh
+//!       more than the out-of-turn ones like in Clique and Parlia.
+//! 	- A header of the best chain is finalized once more than half of the validators signed it
+//!       or one of its descendants, the forks which aren't built on it are pruned then.
+//!
+//! The receipts of the finalized headers can be verified through the `EthereumReceipt` trait, so
+//! the ethereum backing can be set up against the followed chain.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![recursion_limit = "128"]
+
+pub mod weights;
+// --- hyperspace ---
+pub use weights::WeightInfo;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+mod types {
+	// --- hyperspace ---
+	use crate::*;
+
+	pub type Balance<T> = <CurrencyT<T> as Currency<AccountId<T>>>::Balance;
+
+	type AccountId<T> = <T as frame_system::Config>::AccountId;
+
+	type CurrencyT<T> = <T as Config>::Currency;
+}
+
+// --- crates ---
+use codec::{Decode, Encode};
+// --- substrate ---
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	traits::{Currency, Get},
+};
+use frame_system::{ensure_root, ensure_signed};
+use sp_runtime::{
+	traits::AccountIdConversion, DispatchError, DispatchResult, ModuleId, RuntimeDebug,
+};
+use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+// --- hyperspace ---
+use ethereum_primitives::{
+	header::EthereumHeader,
+	poa::{PoAChainSpec, PoAEngine, PoAValidatorSet},
+	receipt::{EthereumReceipt, EthereumReceiptProof, EthereumTransactionIndex},
+	EthereumAddress, EthereumBlockNumber, H256, U256,
+};
+use hyperspace_support::traits::EthereumReceipt as EthereumReceiptT;
+use types::*;
+
+pub trait Config: frame_system::Config {
+	/// The ethereum-poa-relay's module id, used for deriving its sovereign account ID.
+	type ModuleId: Get<ModuleId>;
+
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+	/// The engine and the epoch of the followed chain.
+	type ChainSpec: Get<PoAChainSpec>;
+
+	type Currency: Currency<Self::AccountId>;
+
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
+}
+
+decl_event! {
+	pub enum Event<T>
+	where
+		<T as frame_system::Config>::AccountId,
+	{
+		/// The relay was reset to a checkpoint. [block number, block hash]
+		ResetCheckpoint(EthereumBlockNumber, H256),
+		/// A header was relayed. [relayer, block number, block hash]
+		RelayHeader(AccountId, EthereumBlockNumber, H256),
+		/// A header and its ancestors were finalized. [block number, block hash]
+		FinalizeHeader(EthereumBlockNumber, H256),
+		/// The validator set changed after a header. [block number, validators]
+		ChangeValidators(EthereumBlockNumber, Vec<EthereumAddress>),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// Checkpoint - NOT EXISTED
+		CheckpointNE,
+		/// Finalized Header - NOT EXISTED
+		FinalizedHeaderNE,
+
+		/// Signer - NO PRIVILEGES
+		SignerNP,
+		/// Signer - SIGNED RECENTLY
+		SignerSR,
+
+		/// Parent Header - NOT EXISTED
+		ParentHeaderNE,
+		/// Header - ALREADY EXISTED
+		HeaderAE,
+
+		/// Block Number - MISMATCHED
+		BlockNumberMis,
+		/// Author - MISMATCHED
+		AuthorMis,
+		/// Difficulty - MISMATCHED
+		DifficultyMis,
+
+		/// Rlp - DECODE FAILED
+		RlpDcF,
+		/// Checkpoint - INVALID
+		CheckpointInv,
+		/// Extra Data - INVALID
+		ExtraDataInv,
+		/// Seal - INVALID
+		SealInv,
+		/// Ethereum Receipt Proof - INVALID
+		ReceiptProofInv,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as HyperspaceEthereumPoARelay {
+		/// The block hash of the relayed header with the highest total difficulty
+		pub BestHeader get(fn best_header_hash): H256;
+
+		/// The snapshots of the finalized header and its relayed descendants, by block hash
+		///
+		/// The forks are kept until a header gets finalized, the ones not built on it are pruned.
+		pub Snapshots get(fn snapshot_of): map hasher(identity) H256 => Option<PoASnapshot>;
+		/// The block hashes of `Snapshots`, by block number
+		pub SnapshotHashes
+			get(fn snapshot_hashes_at)
+			: map hasher(identity) EthereumBlockNumber => Vec<H256>;
+
+		/// The latest finalized header
+		pub FinalizedHeader get(fn finalized_header): Option<PoAHeaderBrief>;
+		/// The finalized headers, by block hash
+		pub FinalizedHeaders
+			get(fn finalized_header_of)
+			: map hasher(identity) H256 => Option<PoAHeaderBrief>;
+
+		pub ReceiptVerifyFee get(fn receipt_verify_fee) config(): Balance<T>;
+	}
+	add_extra_genesis {
+		// checkpoint: RLP encoded `EthereumHeader`
+		config(checkpoint): Option<Vec<u8>>;
+		build(|config| {
+			if let Some(checkpoint) = &config.checkpoint {
+				if let Ok(checkpoint) = rlp::decode(checkpoint) {
+					<Module<T>>::initialize(&checkpoint).unwrap();
+				} else {
+					panic!("{}", <&str>::from(<Error<T>>::RlpDcF));
+				}
+			}
+
+			let _ = T::Currency::make_free_balance_be(
+				&<Module<T>>::account_id(),
+				T::Currency::minimum_balance(),
+			);
+		});
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call
+	where
+		origin: T::Origin
+	{
+		type Error = Error<T>;
+
+		const ModuleId: ModuleId = T::ModuleId::get();
+
+		const ChainSpec: PoAChainSpec = T::ChainSpec::get();
+
+		fn deposit_event() = default;
+
+		/// Relay a header after the finalized header or one of its relayed descendants, and
+		/// finalize the headers of the best chain which gather enough signers.
+		///
+		/// # <weight>
+		/// - `O(V + U)`, `V` being the number of validators and `U` the number of the unfinalized
+		///   headers (a public key recovery, up to `V` turns of recent signers, and a walk through
+		///   the unfinalized ancestors if the header becomes the best one)
+		/// - Five storage reads, plus one per unfinalized ancestor
+		/// - Up to four storage writes, plus two per finalized or pruned header
+		/// - Up to three events
+		/// # </weight>
+		#[weight = 200_000_000]
+		pub fn relay_header(origin, header: EthereumHeader) {
+			let relayer = ensure_signed(origin)?;
+
+			Self::import_header(&relayer, &header)?;
+		}
+
+		// --- root call ---
+
+		/// Reset the relay to a trusted checkpoint header, it's taken as finalized and its
+		/// validator set as the current one.
+		///
+		/// # <weight>
+		/// - `O(V + U)`, `V` being the number of validators and `U` the number of the relayed
+		///   headers to clear
+		/// - Five storage writes, plus the clearing
+		/// - One event
+		/// # </weight>
+		#[weight = 100_000_000]
+		pub fn reset_checkpoint(origin, header: EthereumHeader) {
+			ensure_root(origin)?;
+
+			let checkpoint = Self::initialize(&header)?;
+
+			<Module<T>>::deposit_event(RawEvent::ResetCheckpoint(
+				checkpoint.number,
+				checkpoint.hash,
+			));
+		}
+
+		/// Set verify receipt fee
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage write
+		/// # </weight>
+		#[weight = 10_000_000]
+		pub fn set_receipt_verify_fee(origin, #[compact] new: Balance<T>) {
+			ensure_root(origin)?;
+			<ReceiptVerifyFee<T>>::put(new);
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// The account ID of the ethereum poa relay pot.
+	///
+	/// This actually does computation. If you need to keep using it, then make sure you cache the
+	/// value and only call this once.
+	fn account_id() -> T::AccountId {
+		T::ModuleId::get().into_account()
+	}
+
+	/// Trust the checkpoint header and take its validator set as the current one.
+	///
+	/// The checkpoint must be signed, so the relay can't start from the genesis header.
+	pub fn initialize(header: &EthereumHeader) -> Result<PoAHeaderBrief, DispatchError> {
+		let chain_spec = T::ChainSpec::get();
+
+		ensure!(
+			chain_spec.is_checkpoint(header.number),
+			<Error<T>>::CheckpointInv
+		);
+
+		let validator_set = chain_spec
+			.verify_extra_data(header)
+			.map_err(|_| <Error<T>>::ExtraDataInv)?
+			.ok_or(<Error<T>>::CheckpointInv)?;
+		let checkpoint = PoAHeaderBrief {
+			number: header.number,
+			hash: header.re_compute_hash(),
+			signer: chain_spec
+				.recover_signer(header)
+				.map_err(|_| <Error<T>>::SealInv)?,
+			receipts_root: header.receipts_root,
+		};
+
+		let snapshot = PoASnapshot {
+			header: checkpoint.clone(),
+			parent_hash: header.parent_hash,
+			total_difficulty: header.difficulty,
+			validator_set,
+			pending_validator_set: None,
+			recent_signers: vec![checkpoint.signer],
+		};
+
+		Snapshots::remove_all();
+		SnapshotHashes::remove_all();
+		Snapshots::insert(checkpoint.hash, snapshot);
+		SnapshotHashes::insert(checkpoint.number, vec![checkpoint.hash]);
+		BestHeader::put(checkpoint.hash);
+		FinalizedHeader::put(&checkpoint);
+		FinalizedHeaders::insert(checkpoint.hash, &checkpoint);
+
+		Ok(checkpoint)
+	}
+
+	/// The snapshot of the best header
+	pub fn best_snapshot() -> Option<PoASnapshot> {
+		Self::snapshot_of(Self::best_header_hash())
+	}
+
+	/// The validators signing the headers after the best header
+	pub fn validator_set() -> PoAValidatorSet {
+		Self::best_snapshot()
+			.map(|snapshot| snapshot.validator_set)
+			.unwrap_or_default()
+	}
+
+	/// The validator set of the last Parlia checkpoint of the best chain and the header after
+	/// which it's used
+	pub fn pending_validator_set() -> Option<(EthereumBlockNumber, PoAValidatorSet)> {
+		Self::best_snapshot().and_then(|snapshot| snapshot.pending_validator_set)
+	}
+
+	/// Verify the header against its parent's snapshot, then take the best chain, finalize the
+	/// headers it makes final and prune the forks.
+	fn import_header(relayer: &T::AccountId, header: &EthereumHeader) -> DispatchResult {
+		let chain_spec = T::ChainSpec::get();
+		let finalized_header = Self::finalized_header().ok_or(<Error<T>>::CheckpointNE)?;
+		let best_snapshot = Self::best_snapshot().ok_or(<Error<T>>::CheckpointNE)?;
+		let hash = header.re_compute_hash();
+
+		ensure!(!Snapshots::contains_key(hash), <Error<T>>::HeaderAE);
+
+		let PoASnapshot {
+			header: parent_header,
+			total_difficulty,
+			mut validator_set,
+			mut pending_validator_set,
+			mut recent_signers,
+			..
+		} = Self::snapshot_of(header.parent_hash).ok_or(<Error<T>>::ParentHeaderNE)?;
+
+		ensure!(
+			header.number == parent_header.number + 1,
+			<Error<T>>::BlockNumberMis
+		);
+
+		let checkpoint_validator_set = chain_spec
+			.verify_extra_data(header)
+			.map_err(|_| <Error<T>>::ExtraDataInv)?;
+		let signer = chain_spec
+			.recover_signer(header)
+			.map_err(|_| <Error<T>>::SealInv)?;
+
+		ensure!(validator_set.contains(&signer), <Error<T>>::SignerNP);
+
+		if let PoAEngine::Parlia { .. } = chain_spec.engine {
+			ensure!(header.author == signer, <Error<T>>::AuthorMis);
+		}
+
+		ensure!(
+			recent_signers
+				.iter()
+				.rev()
+				.take(validator_set.recent_signers_window())
+				.filter(|recent_signer| **recent_signer == signer)
+				.count() < validator_set.turn_length as usize,
+			<Error<T>>::SignerSR
+		);
+		ensure!(
+			header.difficulty == validator_set.difficulty(header.number, &signer),
+			<Error<T>>::DifficultyMis
+		);
+
+		let brief = PoAHeaderBrief {
+			number: header.number,
+			hash,
+			signer,
+			receipts_root: header.receipts_root,
+		};
+
+		recent_signers.push(signer);
+
+		<Module<T>>::deposit_event(RawEvent::RelayHeader(
+			relayer.clone(),
+			brief.number,
+			brief.hash,
+		));
+
+		// Apply the checkpoint
+		if let Some(checkpoint_validator_set) = checkpoint_validator_set {
+			match chain_spec.engine {
+				PoAEngine::Clique => {
+					pending_validator_set = Some((header.number, checkpoint_validator_set))
+				}
+				PoAEngine::Parlia { .. } => {
+					pending_validator_set = Some((
+						header.number
+							+ validator_set.recent_signers_window() as EthereumBlockNumber,
+						checkpoint_validator_set,
+					))
+				}
+			}
+		}
+		if let Some((number, next_validator_set)) = &pending_validator_set {
+			if *number == header.number {
+				validator_set = next_validator_set.clone();
+				pending_validator_set = None;
+
+				<Module<T>>::deposit_event(RawEvent::ChangeValidators(
+					header.number,
+					validator_set.validators.clone(),
+				));
+			}
+		}
+
+		// Keep the signers in the recent signers window
+		let keep_len = pending_validator_set
+			.as_ref()
+			.map_or(0, |(_, validator_set)| {
+				validator_set.recent_signers_window()
+			})
+			.max(validator_set.recent_signers_window())
+			.max(1);
+
+		if recent_signers.len() > keep_len {
+			recent_signers.drain(..recent_signers.len() - keep_len);
+		}
+
+		let snapshot = PoASnapshot {
+			header: brief,
+			parent_hash: header.parent_hash,
+			total_difficulty: total_difficulty.saturating_add(header.difficulty),
+			validator_set,
+			pending_validator_set,
+			recent_signers,
+		};
+
+		Snapshots::insert(hash, &snapshot);
+		SnapshotHashes::append(header.number, hash);
+
+		// The fork choice, the first relayed one wins a tie
+		if snapshot.total_difficulty > best_snapshot.total_difficulty {
+			BestHeader::put(hash);
+
+			Self::finalize_best_chain(&finalized_header, &snapshot);
+		}
+
+		Ok(())
+	}
+
+	/// Finalize the newest header of the best chain signed or built on by enough validators,
+	/// along with its unfinalized ancestors
+	fn finalize_best_chain(finalized_header: &PoAHeaderBrief, best_snapshot: &PoASnapshot) {
+		let finality_threshold = best_snapshot.validator_set.finality_threshold();
+		let mut signers = BTreeSet::new();
+		let mut maybe_snapshot = Some(best_snapshot.clone());
+
+		while let Some(snapshot) = maybe_snapshot.take() {
+			if snapshot.header.number <= finalized_header.number {
+				return;
+			}
+
+			signers.insert(snapshot.header.signer);
+
+			if signers.len() >= finality_threshold {
+				maybe_snapshot = Some(snapshot);
+
+				break;
+			}
+
+			maybe_snapshot = Self::snapshot_of(snapshot.parent_hash);
+		}
+
+		let new_finalized_header = if let Some(snapshot) = &maybe_snapshot {
+			snapshot.header.clone()
+		} else {
+			return;
+		};
+
+		while let Some(snapshot) = maybe_snapshot.take() {
+			if snapshot.header.number <= finalized_header.number {
+				break;
+			}
+
+			FinalizedHeaders::insert(snapshot.header.hash, &snapshot.header);
+
+			maybe_snapshot = Self::snapshot_of(snapshot.parent_hash);
+		}
+
+		FinalizedHeader::put(&new_finalized_header);
+
+		<Module<T>>::deposit_event(RawEvent::FinalizeHeader(
+			new_finalized_header.number,
+			new_finalized_header.hash,
+		));
+
+		Self::prune_snapshots(finalized_header.number, &new_finalized_header);
+	}
+
+	/// Remove the snapshots of the headers before the finalized one, and of the forks not built
+	/// on it
+	fn prune_snapshots(
+		previous_finalized_number: EthereumBlockNumber,
+		finalized_header: &PoAHeaderBrief,
+	) {
+		for number in previous_finalized_number..=finalized_header.number {
+			for hash in SnapshotHashes::take(number) {
+				if hash != finalized_header.hash {
+					Snapshots::remove(hash);
+				}
+			}
+		}
+
+		SnapshotHashes::insert(finalized_header.number, vec![finalized_header.hash]);
+
+		let mut parent_hashes = vec![finalized_header.hash];
+		let mut number = finalized_header.number + 1;
+
+		loop {
+			let hashes = SnapshotHashes::take(number);
+
+			if hashes.is_empty() {
+				break;
+			}
+
+			let mut kept_hashes = vec![];
+
+			for hash in hashes {
+				if Self::snapshot_of(hash).map_or(false, |snapshot| {
+					parent_hashes.contains(&snapshot.parent_hash)
+				}) {
+					kept_hashes.push(hash);
+				} else {
+					Snapshots::remove(hash);
+				}
+			}
+
+			if !kept_hashes.is_empty() {
+				SnapshotHashes::insert(number, &kept_hashes);
+			}
+
+			parent_hashes = kept_hashes;
+			number += 1;
+		}
+	}
+}
+
+impl<T: Config> EthereumReceiptT<T::AccountId, Balance<T>> for Module<T> {
+	type EthereumReceiptProofThing = EthereumReceiptProof;
+
+	fn account_id() -> T::AccountId {
+		Self::account_id()
+	}
+
+	fn receipt_verify_fee() -> Balance<T> {
+		Self::receipt_verify_fee()
+	}
+
+	/// The block hash must be the one of a finalized header, and the receipt must be proved
+	/// against its receipts root.
+	fn verify_receipt(
+		proof: &Self::EthereumReceiptProofThing,
+	) -> Result<EthereumReceipt, DispatchError> {
+		let header =
+			Self::finalized_header_of(&proof.header_hash).ok_or(<Error<T>>::FinalizedHeaderNE)?;
+		let receipt = EthereumReceipt::verify_proof_and_generate(&header.receipts_root, &proof)
+			.map_err(|_| <Error<T>>::ReceiptProofInv)?;
+
+		Ok(receipt)
+	}
+
+	fn gen_receipt_index(proof: &Self::EthereumReceiptProofThing) -> EthereumTransactionIndex {
+		(proof.header_hash, proof.index)
+	}
+}
+
+/// The state of the relay after a header, its children are verified against it
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PoASnapshot {
+	/// The header
+	pub header: PoAHeaderBrief,
+	/// Block hash of the parent header
+	pub parent_hash: H256,
+	/// Sum of the difficulties from the checkpoint on, the weight of the fork choice
+	pub total_difficulty: U256,
+	/// The validators signing the children
+	pub validator_set: PoAValidatorSet,
+	/// The validator set of the last Parlia checkpoint and the header after which it's used
+	pub pending_validator_set: Option<(EthereumBlockNumber, PoAValidatorSet)>,
+	/// Signers of the latest headers, oldest first, as many as the recent signers windows need
+	pub recent_signers: Vec<EthereumAddress>,
+}
+
+/// The part of a relayed header the relay keeps
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PoAHeaderBrief {
+	/// Block number
+	pub number: EthereumBlockNumber,
+	/// Block hash
+	pub hash: H256,
+	/// The validator which signed the block
+	pub signer: EthereumAddress,
+	/// Receipts root of the block
+	pub receipts_root: H256,
+}
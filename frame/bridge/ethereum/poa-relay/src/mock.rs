@@ -0,0 +1,270 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mock file for ethereum-poa-relay.
+
+// --- std ---
+use std::fs::File;
+// --- substrate ---
+use frame_support::{impl_outer_origin, parameter_types};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{testing::Header, traits::IdentityLookup};
+// --- hyperspace ---
+use crate::*;
+use ethereum_primitives::{
+	poa::EXTRA_SEAL,
+	receipt::{LogEntry, TransactionOutcome, TransactionType},
+};
+
+type AccountId = u64;
+type BlockNumber = u64;
+type Balance = u128;
+
+pub type System = frame_system::Module<Test>;
+pub type EthereumPoARelay = Module<Test>;
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+hyperspace_support::impl_test_account_data! { deprecated }
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Test;
+parameter_types! {
+	pub const EthereumPoARelayModuleId: ModuleId = ModuleId(*b"da/ethpr");
+	pub static ChainSpec: PoAChainSpec = clique_chain_spec();
+}
+impl Config for Test {
+	type ModuleId = EthereumPoARelayModuleId;
+	type Event = ();
+	type ChainSpec = ChainSpec;
+	type Currency = Etp;
+	type WeightInfo = ();
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Hash = H256;
+	type Hashing = sp_runtime::traits::BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = ();
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+impl hyperspace_balances::Config<EtpInstance> for Test {
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ();
+	type BalanceInfo = AccountData<Balance>;
+	type AccountStore = System;
+	type MaxLocks = ();
+	type OtherCurrencies = ();
+	type WeightInfo = ();
+}
+
+pub struct ExtBuilder {
+	chain_spec: PoAChainSpec,
+	checkpoint: Option<EthereumHeader>,
+}
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		Self {
+			chain_spec: clique_chain_spec(),
+			checkpoint: Some(mock_clique_headers().remove(0)),
+		}
+	}
+}
+impl ExtBuilder {
+	pub fn parlia(mut self) -> Self {
+		self.chain_spec = parlia_chain_spec();
+		self.checkpoint = Some(mock_parlia_headers().remove(0));
+		self
+	}
+
+	pub fn checkpoint(mut self, checkpoint: Option<EthereumHeader>) -> Self {
+		self.checkpoint = checkpoint;
+		self
+	}
+
+	pub fn build(self) -> sp_io::TestExternalities {
+		CHAIN_SPEC.with(|v| *v.borrow_mut() = self.chain_spec);
+
+		let mut storage = frame_system::GenesisConfig::default()
+			.build_storage::<Test>()
+			.unwrap();
+
+		GenesisConfig::<Test> {
+			checkpoint: self.checkpoint.map(|checkpoint| rlp::encode(&checkpoint)),
+			..Default::default()
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+		storage.into()
+	}
+}
+
+// The fixtures are synthetic chains in the layout of `eth_getBlockByNumber`, signed by the keys
+// of `validator_key`
+//
+// | fixture           | engine | epoch | checkpoints                       | turn length |
+// |-------------------|--------|-------|-----------------------------------|-------------|
+// | clique_8_24.json  | Clique | 8     | 8, 16 (adds a validator), 24      | 1           |
+// | parlia_16_40.json | Parlia | 16    | 16, 32 (adds a validator, at 35)  | 2           |
+
+pub fn clique_chain_spec() -> PoAChainSpec {
+	PoAChainSpec {
+		engine: PoAEngine::Clique,
+		epoch_length: 8,
+	}
+}
+
+/// The BNB Smart Chain mainnet engine, with the Luban and the Bohr forks from the start
+pub fn parlia_chain_spec() -> PoAChainSpec {
+	PoAChainSpec {
+		engine: PoAEngine::Parlia {
+			chain_id: 56,
+			luban_block: 0,
+			bohr_time: 0,
+		},
+		epoch_length: 16,
+	}
+}
+
+/// The Clique headers 8 to 24, the London base fee is set
+pub fn mock_clique_headers() -> Vec<EthereumHeader> {
+	headers_from_file("./src/test-data/clique_8_24.json")
+}
+
+/// The Parlia headers 16 to 40, the Cancun fields are set
+pub fn mock_parlia_headers() -> Vec<EthereumHeader> {
+	headers_from_file("./src/test-data/parlia_16_40.json")
+}
+
+/// The secret key of the `i`th validator of the `engine`'s fixture
+pub fn validator_key(engine: &str, i: usize) -> secp256k1::SecretKey {
+	secp256k1::SecretKey::parse(&keccak_256(
+		format!("{} validator {}", engine, i).as_bytes(),
+	))
+	.unwrap()
+}
+
+pub fn validator_address(key: &secp256k1::SecretKey) -> EthereumAddress {
+	EthereumAddress::from_slice(
+		&keccak_256(&secp256k1::PublicKey::from_secret_key(key).serialize()[1..])[12..],
+	)
+}
+
+/// The secret key of the fixture validator with the address
+pub fn validator_key_of(engine: &str, address: &EthereumAddress) -> secp256k1::SecretKey {
+	(0..4)
+		.map(|i| validator_key(engine, i))
+		.find(|key| validator_address(key) == *address)
+		.unwrap()
+}
+
+/// Sign the header again, after it's tampered with
+pub fn seal(header: &mut EthereumHeader, key: &secp256k1::SecretKey) {
+	let seal_hash = ChainSpec::get().seal_hash(header).unwrap();
+	let (signature, recovery_id) =
+		secp256k1::sign(&secp256k1::Message::parse(seal_hash.as_fixed_bytes()), key);
+	let signature_at = header.extra_data.len() - EXTRA_SEAL;
+
+	header.extra_data[signature_at..signature_at + 64].copy_from_slice(&signature.serialize());
+	header.extra_data[signature_at + 64] = recovery_id.serialize();
+	header.hash = None;
+}
+
+/// The receipt of the mainnet USDT approval
+/// 0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616, placed as the second
+/// receipt of the Clique block 10
+pub fn mock_receipt() -> (EthereumReceipt, EthereumReceiptProof) {
+	let receipt = EthereumReceipt {
+		tx_type: TransactionType::DynamicFee,
+		..EthereumReceipt::new(
+			TransactionOutcome::StatusCode(1),
+			0xa42aec.into(),
+			vec![LogEntry {
+				address: array_bytes::hex2array_unchecked!(
+					"0xdac17f958d2ee523a2206206994597c13d831ec7",
+					20
+				)
+				.into(),
+				topics: vec![
+					array_bytes::hex2array_unchecked!(
+						"0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+						32
+					)
+					.into(),
+					array_bytes::hex2array_unchecked!(
+						"0x0000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2",
+						32
+					)
+					.into(),
+					array_bytes::hex2array_unchecked!(
+						"0x00000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76",
+						32
+					)
+					.into(),
+				],
+				data: array_bytes::hex2bytes_unchecked(
+					"0x00000000000000000000000000000000000000000052b7d2dcc80cd2e4000000",
+				),
+			}],
+		)
+	};
+	let proof = EthereumReceiptProof {
+		index: 1,
+		proof: array_bytes::hex2bytes_unchecked(RECEIPT_PROOF),
+		header_hash: mock_clique_headers()[2].hash(),
+	};
+
+	(receipt, proof)
+}
+
+fn headers_from_file(path: &str) -> Vec<EthereumHeader> {
+	let headers: Vec<serde_json::Value> =
+		serde_json::from_reader(File::open(path).unwrap()).unwrap();
+
+	headers
+		.iter()
+		.map(|header| EthereumHeader::from_str_unchecked(&header.to_string()))
+		.collect()
+}
+
+/// The receipts trie of the block holds a legacy receipt and the USDT approval
+pub const RECEIPT_PROOF: &'static str = "0xf9020df9020ab853f851a028ce6ece5744cb06f4a71edc08559414698a9084f52695b4cbd3ebadb451fe1e80808080808080a0e58215be848c1293dd381210359d84485553000a82b67410406d183b42adbbdd8080808080808080b901b2f901af31b901ab02f901a70183a42aecb9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000200000000000000000040000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000800000000000000000000000000000000004000000000000000000800000000100000020000000000000000000080000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000010000000000000000000000000000f89df89b94dac17f958d2ee523a2206206994597c13d831ec7f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2a000000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76a000000000000000000000000000000000000000000052b7d2dcc80cd2e4000000";
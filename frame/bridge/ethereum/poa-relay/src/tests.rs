@@ -0,0 +1,459 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for ethereum-poa-relay.
+
+// --- substrate ---
+use frame_support::{assert_err, assert_noop, assert_ok};
+use frame_system::RawOrigin;
+use sp_runtime::DispatchError;
+// --- hyperspace ---
+use crate::{mock::*, *};
+
+fn signer_key_of(engine: &str, header: &EthereumHeader) -> secp256k1::SecretKey {
+	validator_key_of(engine, &ChainSpec::get().recover_signer(header).unwrap())
+}
+
+#[test]
+fn reset_checkpoint_should_work() {
+	ExtBuilder::default()
+		.checkpoint(None)
+		.build()
+		.execute_with(|| {
+			let headers = mock_clique_headers();
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), headers[1].clone()),
+				<Error<Test>>::CheckpointNE
+			);
+			assert_noop!(
+				EthereumPoARelay::reset_checkpoint(Origin::signed(1), headers[0].clone()),
+				DispatchError::BadOrigin
+			);
+			assert_noop!(
+				EthereumPoARelay::reset_checkpoint(RawOrigin::Root.into(), headers[1].clone()),
+				<Error<Test>>::CheckpointInv
+			);
+
+			assert_ok!(EthereumPoARelay::reset_checkpoint(
+				RawOrigin::Root.into(),
+				headers[0].clone()
+			));
+			assert_eq!(
+				EthereumPoARelay::validator_set(),
+				PoAValidatorSet::new(
+					(0..3)
+						.map(|i| validator_address(&validator_key("clique", i)))
+						.collect(),
+					1
+				)
+			);
+			assert_eq!(
+				EthereumPoARelay::finalized_header().unwrap().hash,
+				headers[0].hash()
+			);
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				headers[1].clone()
+			));
+		});
+}
+
+#[test]
+fn relay_clique_headers_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+
+		for header in &headers[1..] {
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				header.clone()
+			));
+		}
+
+		// The checkpoint 16 adds the fourth validator
+		assert_eq!(EthereumPoARelay::validator_set().validators.len(), 4);
+		assert!(EthereumPoARelay::validator_set()
+			.contains(&validator_address(&validator_key("clique", 3))));
+
+		// The headers 24, 23 and 22 are signed by three distinct validators out of four
+		assert_eq!(EthereumPoARelay::finalized_header().unwrap().number, 22);
+
+		for header in &headers[..=14] {
+			assert!(EthereumPoARelay::finalized_header_of(header.hash()).is_some());
+		}
+		for header in &headers[15..] {
+			assert!(EthereumPoARelay::finalized_header_of(header.hash()).is_none());
+		}
+
+		// The finalized header and its descendants are kept
+		assert_eq!(
+			(21..=25)
+				.map(|number| EthereumPoARelay::snapshot_hashes_at(number).len())
+				.collect::<Vec<_>>(),
+			vec![0, 1, 1, 1, 0]
+		);
+		assert_eq!(EthereumPoARelay::best_header_hash(), headers[16].hash());
+	});
+}
+
+#[test]
+fn relay_parlia_headers_should_work() {
+	ExtBuilder::default().parlia().build().execute_with(|| {
+		let headers = mock_parlia_headers();
+
+		for header in &headers[1..=16] {
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				header.clone()
+			));
+		}
+
+		// The validator set of the checkpoint 32 takes over after the header 35
+		// (`(3 / 2 + 1) * 2 - 1` headers later)
+		assert_eq!(EthereumPoARelay::validator_set().validators.len(), 3);
+		assert_eq!(
+			EthereumPoARelay::pending_validator_set()
+				.map(|(number, validator_set)| (number, validator_set.validators.len())),
+			Some((35, 4))
+		);
+
+		for header in &headers[17..=18] {
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				header.clone()
+			));
+		}
+
+		assert_eq!(EthereumPoARelay::validator_set().validators.len(), 3);
+
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[19].clone()
+		));
+		assert_eq!(EthereumPoARelay::validator_set().validators.len(), 4);
+		assert_eq!(EthereumPoARelay::validator_set().turn_length, 2);
+		assert_eq!(EthereumPoARelay::pending_validator_set(), None);
+
+		for header in &headers[20..] {
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				header.clone()
+			));
+		}
+
+		// The headers 40, 39 and 37 are signed by three distinct validators out of four
+		assert_eq!(EthereumPoARelay::finalized_header().unwrap().number, 37);
+		// The recent signers window is `(4 / 2 + 1) * 2 - 1` headers
+		assert_eq!(
+			EthereumPoARelay::best_snapshot()
+				.unwrap()
+				.recent_signers
+				.len(),
+			5
+		);
+	});
+}
+
+#[test]
+fn header_should_extend_relayed_header() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), headers[2].clone()),
+			<Error<Test>>::ParentHeaderNE
+		);
+
+		{
+			let mut header = headers[1].clone();
+
+			header.number += 1;
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::BlockNumberMis
+			);
+		}
+
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[1].clone()
+		));
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), headers[1].clone()),
+			<Error<Test>>::HeaderAE
+		);
+	});
+}
+
+#[test]
+fn heaviest_fork_should_be_followed() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+		let mut fork_header = headers[1].clone();
+
+		// An out-of-turn block 9 by the validator which signed neither the checkpoint nor the
+		// in-turn block 9
+		let signers = [
+			ChainSpec::get().recover_signer(&headers[0]).unwrap(),
+			ChainSpec::get().recover_signer(&headers[1]).unwrap(),
+		];
+		let key = (0..3)
+			.map(|i| validator_key("clique", i))
+			.find(|key| !signers.contains(&validator_address(key)))
+			.unwrap();
+
+		fork_header.difficulty = 1.into();
+		seal(&mut fork_header, &key);
+
+		let fork_hash = fork_header.re_compute_hash();
+
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			fork_header
+		));
+		assert_eq!(EthereumPoARelay::best_header_hash(), fork_hash);
+
+		// The in-turn block weighs more
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[1].clone()
+		));
+		assert_eq!(EthereumPoARelay::best_header_hash(), headers[1].hash());
+		assert_eq!(EthereumPoARelay::snapshot_hashes_at(9).len(), 2);
+		assert_eq!(
+			EthereumPoARelay::snapshot_of(headers[1].hash())
+				.unwrap()
+				.total_difficulty,
+			headers[0].difficulty + headers[1].difficulty
+		);
+
+		// The block 10 finalizes the in-turn block 9 and prunes the fork
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[2].clone()
+		));
+		assert_eq!(EthereumPoARelay::finalized_header().unwrap().number, 9);
+		assert!(EthereumPoARelay::finalized_header_of(headers[1].hash()).is_some());
+		assert!(EthereumPoARelay::finalized_header_of(fork_hash).is_none());
+		assert!(EthereumPoARelay::snapshot_of(fork_hash).is_none());
+		assert!(EthereumPoARelay::snapshot_of(headers[0].hash()).is_none());
+		assert_eq!(
+			EthereumPoARelay::snapshot_hashes_at(9),
+			vec![headers[1].hash()]
+		);
+	});
+}
+
+#[test]
+fn header_should_be_signed_by_validator() {
+	ExtBuilder::default().build().execute_with(|| {
+		let header = mock_clique_headers().remove(1);
+
+		{
+			let mut header = header.clone();
+
+			header.gas_used += 1.into();
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::SignerNP
+			);
+		}
+		{
+			let mut header = header.clone();
+
+			// The fourth validator joins at the checkpoint 16
+			seal(&mut header, &validator_key("clique", 3));
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::SignerNP
+			);
+		}
+		{
+			let mut header = header.clone();
+
+			*header.extra_data.last_mut().unwrap() = 4;
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::SealInv
+			);
+		}
+		{
+			let mut header = header.clone();
+
+			header.extra_data.truncate(64);
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::ExtraDataInv
+			);
+		}
+		{
+			let mut header = header.clone();
+			let key = signer_key_of("clique", &header);
+
+			// Only the checkpoints list the signers
+			header
+				.extra_data
+				.splice(32..32, validator_address(&key).0.iter().cloned());
+			seal(&mut header, &key);
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::ExtraDataInv
+			);
+		}
+	});
+	ExtBuilder::default().parlia().build().execute_with(|| {
+		let mut header = mock_parlia_headers().remove(1);
+		let key = signer_key_of("parlia", &header);
+
+		header.author = Default::default();
+		seal(&mut header, &key);
+
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), header),
+			<Error<Test>>::AuthorMis
+		);
+	});
+}
+
+#[test]
+fn signer_should_not_sign_recently() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+		let mut header = headers[1].clone();
+
+		// Out of its turn, right after signing the checkpoint
+		header.difficulty = 1.into();
+		seal(&mut header, &signer_key_of("clique", &headers[0]));
+
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), header),
+			<Error<Test>>::SignerSR
+		);
+	});
+	ExtBuilder::default().parlia().build().execute_with(|| {
+		let headers = mock_parlia_headers();
+
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[1].clone()
+		));
+
+		let mut header = headers[2].clone();
+		let key = signer_key_of("parlia", &headers[1]);
+
+		// A third block, after a full turn of two
+		header.author = validator_address(&key);
+		header.difficulty = 1.into();
+		seal(&mut header, &key);
+
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), header),
+			<Error<Test>>::SignerSR
+		);
+	});
+}
+
+#[test]
+fn difficulty_should_match_turn() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+		let header = headers[1].clone();
+
+		{
+			let mut header = header.clone();
+
+			header.difficulty = 1.into();
+			seal(&mut header, &signer_key_of("clique", &headers[1]));
+
+			assert_noop!(
+				EthereumPoARelay::relay_header(Origin::signed(1), header),
+				<Error<Test>>::DifficultyMis
+			);
+		}
+
+		let mut header = header;
+
+		// The validator which signed neither the checkpoint nor the in-turn block
+		let signers = [
+			ChainSpec::get().recover_signer(&headers[0]).unwrap(),
+			ChainSpec::get().recover_signer(&headers[1]).unwrap(),
+		];
+		let key = (0..3)
+			.map(|i| validator_key("clique", i))
+			.find(|key| !signers.contains(&validator_address(key)))
+			.unwrap();
+
+		seal(&mut header, &key);
+
+		assert_noop!(
+			EthereumPoARelay::relay_header(Origin::signed(1), header.clone()),
+			<Error<Test>>::DifficultyMis
+		);
+
+		header.difficulty = 1.into();
+		seal(&mut header, &key);
+
+		assert_ok!(EthereumPoARelay::relay_header(Origin::signed(1), header));
+	});
+}
+
+#[test]
+fn verify_receipt_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let headers = mock_clique_headers();
+		let (receipt, proof) = mock_receipt();
+
+		for header in &headers[1..=2] {
+			assert_ok!(EthereumPoARelay::relay_header(
+				Origin::signed(1),
+				header.clone()
+			));
+		}
+
+		// The block 10 is signed by a single validator yet
+		assert_err!(
+			<EthereumPoARelay as EthereumReceiptT<_, _>>::verify_receipt(&proof),
+			<Error<Test>>::FinalizedHeaderNE
+		);
+
+		assert_ok!(EthereumPoARelay::relay_header(
+			Origin::signed(1),
+			headers[3].clone()
+		));
+		assert_eq!(
+			<EthereumPoARelay as EthereumReceiptT<_, _>>::verify_receipt(&proof).unwrap(),
+			receipt
+		);
+
+		let mut proof = proof;
+
+		proof.index = 0;
+
+		assert_err!(
+			<EthereumPoARelay as EthereumReceiptT<_, _>>::verify_receipt(&proof),
+			<Error<Test>>::ReceiptProofInv
+		);
+	});
+}
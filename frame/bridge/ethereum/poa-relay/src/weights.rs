@@ -0,0 +1,2 @@
+pub trait WeightInfo {}
+impl WeightInfo for () {}
@@ -72,6 +72,8 @@ use sp_std::borrow::ToOwned;
 use sp_std::{convert::From, marker::PhantomData, prelude::*};
 // --- hyperspace ---
 use crate::mmr::{leaf_index_to_mmr_size, leaf_index_to_pos, MMRMerge, MerkleProof};
+use hyperspace_header_mmr_primitives::verify_consistency_proof;
+pub use hyperspace_header_mmr_primitives::ConsistencyProof;
 
 use hyperspace_relay_primitives::relayer_game::*;
 use hyperspace_support::{
@@ -89,6 +91,10 @@ use types::*;
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/dags_merkle_roots.rs"));
 
+/// Number of confirmed parcels pruned per block while `ConfirmedHeaderParcels` holds more than
+/// `ConfirmedDepth` parcels.
+pub const PRUNING_BATCH: u32 = 64;
+
 pub trait Config: frame_system::Config {
 	/// The ethereum-relay's module id, used for deriving its sovereign account ID.
 	type ModuleId: Get<ModuleId>;
@@ -148,6 +154,8 @@ decl_event! {
 		GameOver(EthereumBlockNumber),
 		/// The specific confirmed parcel removed. [ethereum block number]
 		RemoveConfirmedParcel(EthereumBlockNumber),
+		/// The confirmed depth changed. [confirmed depth]
+		ConfirmedDepthChanged(u32),
 		/// EthereumReceipt verification. [account, ethereum receipt, ethereum header]
 		VerifyReceipt(AccountId, EthereumReceipt, EthereumHeader),
 		/// A relay header parcel got pended. [ethereum block number]
@@ -175,6 +183,10 @@ decl_error! {
 		HeaderHashInv,
 		/// MMR - INVALID
 		MMRInv,
+		/// MMR Consistency - INVALID
+		MMRConsistencyInv,
+		/// MMR Consistency Proof - NOT EXISTED
+		MMRConsistencyNE,
 		/// Header Hash - MISMATCHED
 		HeaderHashMis,
 		/// Confirmed Header - NOT EXISTED
@@ -217,8 +229,26 @@ decl_storage! {
 			get(fn best_confirmed_block_number)
 			: EthereumBlockNumber;
 
+		/// Number of the latest confirmed parcels kept in `ConfirmedHeaderParcels`
+		///
+		/// The MMR roots of the older ones are moved to `ArchivedMMRRoots`
 		pub ConfirmedDepth get(fn confirmed_depth) config(): u32 = 10;
 
+		/// MMR roots of the confirmed parcels pruned from `ConfirmedHeaderParcels`
+		///
+		/// Used to verify the receipts against the MMR of an old confirmed block
+		pub ArchivedMMRRoots
+			get(fn archived_mmr_root_of)
+			: map hasher(identity) EthereumBlockNumber => Option<H256>;
+
+		/// Whether `ConfirmedHeaderParcels` still holds more than `ConfirmedDepth` parcels
+		///
+		/// The extra parcels are pruned by `PRUNING_BATCH` in `on_initialize`
+		pub PruningConfirmedParcels get(fn pruning_confirmed_parcels): bool;
+
+		/// Storage version of the pallet, `Releases::V1_0_0` if not set
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V2_0_0): Releases;
+
 		/// Dags merkle roots of ethereum epoch (each epoch is 30000)
 		pub DagsMerkleRoots
 			get(fn dag_merkle_root)
@@ -288,10 +318,26 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			if !StorageVersion::exists() {
+				StorageVersion::put(Releases::V2_0_0);
+				PruningConfirmedParcels::put(true);
+
+				T::DbWeight::get().reads_writes(1, 2)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
 		fn on_initialize(now: BlockNumber<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+
+			if Self::pruning_confirmed_parcels() {
+				weight += Self::prune_confirmed_parcels(PRUNING_BATCH);
+			}
+
 			// TODO: handle error
-			// TODO: weight
-			Self::system_approve_pending_relay_header_parcels(now).unwrap_or(0)
+			weight + Self::system_approve_pending_relay_header_parcels(now).unwrap_or(0)
 		}
 
 		// TODO: weight
@@ -368,7 +414,8 @@ decl_module! {
 			));
 		}
 
-		#[weight = 100_000_000]
+		// Confirming the parcel might prune the oldest confirmed one
+		#[weight = 100_000_000 + T::DbWeight::get().reads_writes(5, 7)]
 		pub fn vote_pending_relay_header_parcel(
 			origin,
 			ethereum_block_number: EthereumBlockNumber,
@@ -521,6 +568,7 @@ decl_module! {
 				}
 
 				ConfirmedHeaderParcels::remove(confirmed_block_number);
+				ArchivedMMRRoots::remove(confirmed_block_number);
 				BestConfirmedBlockNumber::put(confirmed_block_numbers
 					.iter()
 					.max()
@@ -541,6 +589,21 @@ decl_module! {
 			ConfirmedHeaderParcels::remove_all();
 			ConfirmedBlockNumbers::kill();
 			BestConfirmedBlockNumber::kill();
+			ArchivedMMRRoots::remove_all();
+			PruningConfirmedParcels::kill();
+		}
+
+		/// Set the number of the latest confirmed parcels kept in `ConfirmedHeaderParcels`
+		///
+		/// The extra parcels are pruned from the next block on
+		#[weight = 10_000_000]
+		pub fn set_confirmed_depth(origin, confirmed_depth: u32) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+
+			ConfirmedDepth::put(confirmed_depth);
+			PruningConfirmedParcels::put(true);
+
+			Self::deposit_event(RawEvent::ConfirmedDepthChanged(confirmed_depth));
 		}
 
 		#[weight = 10_000_000]
@@ -627,15 +690,74 @@ impl<T: Config> Module<T> {
 		.unwrap_or(false)
 	}
 
+	/// Verify that the MMR of `old_last_leaf` is a prefix of the MMR of `new_last_leaf`
+	pub fn verify_mmr_consistency(
+		old_last_leaf: u64,
+		old_mmr_root: H256,
+		new_last_leaf: u64,
+		new_mmr_root: H256,
+		mmr_consistency_proof: ConsistencyProof<H256>,
+	) -> bool {
+		let ConsistencyProof { old_peaks, items } = mmr_consistency_proof;
+
+		verify_consistency_proof::<MMRMerge>(
+			old_mmr_root.into(),
+			leaf_index_to_mmr_size(old_last_leaf),
+			new_mmr_root.into(),
+			leaf_index_to_mmr_size(new_last_leaf),
+			ConsistencyProof {
+				old_peaks: old_peaks.into_iter().map(Into::into).collect(),
+				items: items.into_iter().map(Into::into).collect(),
+			},
+		)
+	}
+
+	/// The MMR root of the confirmed parcel, archived or not
+	pub fn confirmed_mmr_root_of(confirmed_block_number: EthereumBlockNumber) -> Option<H256> {
+		Self::confirmed_header_parcel_of(confirmed_block_number)
+			.map(|confirmed_header_parcel| confirmed_header_parcel.mmr_root)
+			.or_else(|| Self::archived_mmr_root_of(confirmed_block_number))
+	}
+
+	/// Prune up to `max` of the oldest confirmed parcels beyond `ConfirmedDepth`, keeping their
+	/// MMR roots in `ArchivedMMRRoots`
+	///
+	/// The best confirmed parcel is always kept, the relayer game extends from it
+	pub fn prune_confirmed_parcels(max: u32) -> Weight {
+		let confirmed_depth = Self::confirmed_depth().max(1) as usize;
+		let mut confirmed_block_numbers = Self::confirmed_block_numbers();
+		let extra = confirmed_block_numbers
+			.len()
+			.saturating_sub(confirmed_depth);
+		let pruned = extra.min(max as usize);
+
+		for confirmed_block_number in confirmed_block_numbers.drain(..pruned) {
+			if let Some(confirmed_header_parcel) =
+				ConfirmedHeaderParcels::take(confirmed_block_number)
+			{
+				ArchivedMMRRoots::insert(confirmed_block_number, confirmed_header_parcel.mmr_root);
+			}
+		}
+
+		if pruned != 0 {
+			ConfirmedBlockNumbers::put(confirmed_block_numbers);
+		}
+		if extra == pruned {
+			PruningConfirmedParcels::kill();
+		} else {
+			PruningConfirmedParcels::put(true);
+		}
+
+		T::DbWeight::get().reads_writes(2 + pruned as Weight, 2 + 2 * pruned as Weight)
+	}
+
 	pub fn update_confirmeds_with_reason(
 		relay_header_parcel: EthereumRelayHeaderParcel,
 		reason: Vec<u8>,
-	) {
+	) -> Weight {
 		let relay_block_number = relay_header_parcel.header.number;
 
 		ConfirmedBlockNumbers::mutate(|confirmed_block_numbers| {
-			// TODO: remove old numbers according to `ConfirmedDepth`
-
 			confirmed_block_numbers.push(relay_block_number);
 
 			BestConfirmedBlockNumber::put(relay_block_number);
@@ -646,24 +768,32 @@ impl<T: Config> Module<T> {
 			relay_block_number,
 			reason,
 		));
+
+		// Only the parcel pushed out by this one, the others are pruned in `on_initialize`
+		T::DbWeight::get().reads_writes(1, 3) + Self::prune_confirmed_parcels(1)
 	}
 
 	pub fn confirm_relay_header_parcel_with_reason(
 		relay_header_parcel: EthereumRelayHeaderParcel,
 		reason: Vec<u8>,
-	) {
+	) -> Weight {
 		if relay_header_parcel.header.number > Self::best_confirmed_block_number() {
-			Self::update_confirmeds_with_reason(relay_header_parcel, reason);
+			T::DbWeight::get().reads(1)
+				+ Self::update_confirmeds_with_reason(relay_header_parcel, reason)
+		} else {
+			T::DbWeight::get().reads(1)
 		}
 	}
 
 	pub fn system_approve_pending_relay_header_parcels(
 		now: BlockNumber<T>,
 	) -> Result<Weight, DispatchError> {
+		let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
 		<PendingRelayHeaderParcels<T>>::mutate(|parcels| {
 			parcels.retain(|(at, parcel, _)| {
 				if *at == now {
-					Self::confirm_relay_header_parcel_with_reason(
+					weight += Self::confirm_relay_header_parcel_with_reason(
 						parcel.to_owned(),
 						b"Not Enough Technical Member Online, Confirmed By System".to_vec(),
 					);
@@ -675,8 +805,7 @@ impl<T: Config> Module<T> {
 			})
 		});
 
-		// TODO: weight
-		Ok(0)
+		Ok(weight)
 	}
 }
 
@@ -729,6 +858,7 @@ impl<T: Config> Relayable for Module<T> {
 		let Self::RelayProofs {
 			ethash_proof,
 			mmr_proof,
+			mmr_consistency_proof,
 		} = relay_proofs;
 
 		ensure!(
@@ -740,11 +870,30 @@ impl<T: Config> Relayable for Module<T> {
 		let mmr_root = array_bytes::dyn2array!(mmr_root, 32).into();
 
 		if let Some(best_confirmed_block_number) = optional_best_confirmed_relay_header_id {
-			let maybe_best_confirmed_block_header_hash =
+			let best_confirmed_relay_header_parcel =
 				Self::confirmed_header_parcel_of(best_confirmed_block_number)
-					.ok_or(<Error<T>>::ConfirmedHeaderNE)?
-					.header
-					.hash;
+					.ok_or(<Error<T>>::ConfirmedHeaderNE)?;
+
+			if let Some(best_confirmed_last_leaf) = best_confirmed_block_number.checked_sub(1) {
+				let mmr_consistency_proof = mmr_consistency_proof
+					.as_ref()
+					.ok_or(<Error<T>>::MMRConsistencyNE)?;
+
+				// The confirmed MMR must be a prefix of the proposed one
+				ensure!(
+					Self::verify_mmr_consistency(
+						best_confirmed_last_leaf,
+						best_confirmed_relay_header_parcel.mmr_root,
+						last_leaf,
+						mmr_root,
+						mmr_consistency_proof.to_owned(),
+					),
+					<Error<T>>::MMRConsistencyInv
+				);
+			}
+
+			let maybe_best_confirmed_block_header_hash =
+				best_confirmed_relay_header_parcel.header.hash;
 			let best_confirmed_block_header_hash =
 				maybe_best_confirmed_block_header_hash.ok_or(<Error<T>>::HeaderHashInv)?;
 
@@ -920,9 +1069,8 @@ impl<T: Config> EthereumReceiptT<AccountId<T>, EtpBalance<T>> for Module<T> {
 		);
 
 		// Verify header member to last confirmed block using mmr proof
-		let mmr_root = Self::confirmed_header_parcel_of(mmr_proof.last_leaf_index + 1)
-			.ok_or(<Error<T>>::ConfirmedHeaderNE)?
-			.mmr_root;
+		let mmr_root = Self::confirmed_mmr_root_of(mmr_proof.last_leaf_index + 1)
+			.ok_or(<Error<T>>::ConfirmedHeaderNE)?;
 
 		ensure!(
 			Self::verify_mmr(
@@ -980,6 +1128,10 @@ impl RelayHeaderParcelInfo for EthereumRelayHeaderParcel {
 pub struct EthereumRelayProofs {
 	pub ethash_proof: Vec<EthashProof>,
 	pub mmr_proof: Vec<H256>,
+	/// Proof that the MMR of the best confirmed parcel is a prefix of the MMR of the relayed
+	/// parcel, mandatory once a parcel is confirmed.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub mmr_consistency_proof: Option<ConsistencyProof<H256>>,
 }
 
 #[cfg_attr(any(feature = "deserialize", test), derive(serde::Deserialize))]
@@ -990,6 +1142,23 @@ pub struct MMRProof {
 	pub proof: Vec<H256>,
 }
 
+// A value placed in storage that represents the current version of the EthereumRelay storage.
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run storage
+// migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+	/// Every confirmed parcel in `ConfirmedHeaderParcels`.
+	V1_0_0,
+	/// The latest `ConfirmedDepth` confirmed parcels in `ConfirmedHeaderParcels`, the MMR roots
+	/// of the others in `ArchivedMMRRoots`.
+	V2_0_0,
+}
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V2_0_0
+	}
+}
+
 #[derive(Encode, Decode, Clone, Eq, PartialEq)]
 pub struct CheckEthereumRelayHeaderParcel<T: Config>(PhantomData<T>);
 impl<T: Config> CheckEthereumRelayHeaderParcel<T> {
@@ -119,6 +119,7 @@ impl hyperspace_balances::Config<EtpInstance> for Test {
 pub struct ExtBuilder {
 	best_confirmed_block_number: EthereumBlockNumber,
 	confirm_period: BlockNumber,
+	confirmed_depth: u32,
 }
 impl ExtBuilder {
 	pub fn best_confirmed_block_number(
@@ -136,6 +137,12 @@ impl ExtBuilder {
 		self
 	}
 
+	pub fn confirmed_depth(mut self, confirmed_depth: u32) -> Self {
+		self.confirmed_depth = confirmed_depth;
+
+		self
+	}
+
 	pub fn set_associated_constants(&self) {
 		BEST_CONFIRMED_BLOCK_NUMBER.with(|v| v.replace(self.best_confirmed_block_number));
 		CONFIRM_PERIOD.with(|v| v.replace(self.confirm_period));
@@ -157,6 +164,7 @@ impl ExtBuilder {
 				"../../../../bin/res/ethereum/dags-merkle-roots.json",
 				"DAG_MERKLE_ROOTS_PATH",
 			),
+			confirmed_depth: self.confirmed_depth,
 			..Default::default()
 		}
 		.assimilate_storage(&mut storage)
@@ -170,6 +178,7 @@ impl Default for ExtBuilder {
 		Self {
 			best_confirmed_block_number: BEST_CONFIRMED_BLOCK_NUMBER.with(|v| *v.borrow()),
 			confirm_period: CONFIRM_PERIOD.with(|v| *v.borrow()),
+			confirmed_depth: 10,
 		}
 	}
 }
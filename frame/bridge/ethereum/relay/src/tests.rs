@@ -17,10 +17,21 @@
 // along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
 
 // --- substrate ---
-use frame_support::assert_ok;
+use frame_support::{assert_err, assert_noop, assert_ok, traits::OnRuntimeUpgrade};
+use frame_system::RawOrigin;
 // --- hyperspace ---
 use crate::{mock::*, test_data::*, *};
 
+fn mock_relay_header_parcel(number: EthereumBlockNumber) -> EthereumRelayHeaderParcel {
+	let mut relay_header_parcel: EthereumRelayHeaderParcel =
+		serde_json::from_str(LAST_CONFIRM).unwrap();
+
+	relay_header_parcel.header.number = number;
+	relay_header_parcel.mmr_root = ethereum_primitives::H256::repeat_byte(number as _);
+
+	relay_header_parcel
+}
+
 #[test]
 fn store_relay_header_parcel_should_work() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -55,15 +66,34 @@ fn verify_relay_proofs_should_work() {
 
 		let ethereum_relay_header_parcel_103: EthereumRelayHeaderParcel =
 			serde_json::from_str(HEADER_103).unwrap();
-		let ethereum_relay_proofs_103: EthereumRelayProofs =
+		let mut ethereum_relay_proofs_103: EthereumRelayProofs =
 			serde_json::from_str(PROOFS_103).unwrap();
 
-		assert_ok!(EthereumRelay::verify_relay_proofs(
-			&103,
-			&ethereum_relay_header_parcel_103,
-			&ethereum_relay_proofs_103,
-			Some(&100)
-		));
+		// The consistency with the confirmed MMR is mandatory
+		assert_err!(
+			EthereumRelay::verify_relay_proofs(
+				&103,
+				&ethereum_relay_header_parcel_103,
+				&ethereum_relay_proofs_103,
+				Some(&100)
+			),
+			<Error<Test>>::MMRConsistencyNE
+		);
+
+		ethereum_relay_proofs_103.mmr_consistency_proof = Some(ConsistencyProof {
+			old_peaks: vec![],
+			items: vec![],
+		});
+
+		assert_err!(
+			EthereumRelay::verify_relay_proofs(
+				&103,
+				&ethereum_relay_header_parcel_103,
+				&ethereum_relay_proofs_103,
+				Some(&100)
+			),
+			<Error<Test>>::MMRConsistencyInv
+		);
 
 		let ethereum_relay_header_parcel_102: EthereumRelayHeaderParcel =
 			serde_json::from_str(HEADER_102).unwrap();
@@ -171,6 +201,199 @@ fn try_confirm_relay_header_parcel_should_work() {
 		});
 }
 
+#[test]
+fn confirmed_parcels_should_be_pruned() {
+	ExtBuilder::default()
+		.confirmed_depth(3)
+		.build()
+		.execute_with(|| {
+			for number in 1..=5 {
+				EthereumRelay::confirm_relay_header_parcel_with_reason(
+					mock_relay_header_parcel(number),
+					vec![],
+				);
+			}
+
+			assert_eq!(EthereumRelay::confirmed_block_numbers(), vec![3, 4, 5]);
+			assert_eq!(EthereumRelay::best_confirmed_block_number(), 5);
+			assert!(!EthereumRelay::pruning_confirmed_parcels());
+
+			for number in 0..=2 {
+				assert!(EthereumRelay::confirmed_header_parcel_of(number).is_none());
+				assert!(EthereumRelay::archived_mmr_root_of(number).is_some());
+			}
+			for number in 3..=5 {
+				assert!(EthereumRelay::confirmed_header_parcel_of(number).is_some());
+				assert!(EthereumRelay::archived_mmr_root_of(number).is_none());
+			}
+			for number in 1..=5 {
+				assert_eq!(
+					EthereumRelay::confirmed_mmr_root_of(number),
+					Some(ethereum_primitives::H256::repeat_byte(number as _))
+				);
+			}
+			assert_eq!(EthereumRelay::confirmed_mmr_root_of(6), None);
+
+			// A malicious archived parcel can still be removed
+			assert_ok!(EthereumRelay::remove_confirmed_parcel_of(
+				RawOrigin::Root.into(),
+				1
+			));
+			assert_eq!(EthereumRelay::confirmed_mmr_root_of(1), None);
+		});
+}
+
+#[test]
+fn set_confirmed_depth_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		for number in 1..=5 {
+			EthereumRelay::confirm_relay_header_parcel_with_reason(
+				mock_relay_header_parcel(number),
+				vec![],
+			);
+		}
+
+		assert_eq!(EthereumRelay::confirmed_block_numbers().len(), 6);
+		assert_noop!(
+			EthereumRelay::set_confirmed_depth(Origin::signed(1), 0),
+			DispatchError::BadOrigin
+		);
+
+		// The best confirmed parcel is always kept
+		assert_ok!(EthereumRelay::set_confirmed_depth(
+			RawOrigin::Root.into(),
+			0
+		));
+		assert!(EthereumRelay::pruning_confirmed_parcels());
+
+		run_to_block(1);
+
+		assert_eq!(EthereumRelay::confirmed_block_numbers(), vec![5]);
+		assert!(!EthereumRelay::pruning_confirmed_parcels());
+		assert!(EthereumRelay::confirmed_header_parcel_of(5).is_some());
+	});
+}
+
+#[test]
+fn migration_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let best_confirmed_block_number = PRUNING_BATCH as EthereumBlockNumber + 20;
+
+		// Every parcel ever confirmed, as before the `ConfirmedDepth` pruning
+		for number in 1..=best_confirmed_block_number {
+			ConfirmedHeaderParcels::insert(number, mock_relay_header_parcel(number));
+			ConfirmedBlockNumbers::append(number);
+		}
+		BestConfirmedBlockNumber::put(best_confirmed_block_number);
+		StorageVersion::kill();
+
+		EthereumRelay::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+		assert!(EthereumRelay::pruning_confirmed_parcels());
+
+		run_to_block(1);
+
+		assert_eq!(
+			EthereumRelay::confirmed_block_numbers().len(),
+			best_confirmed_block_number as usize + 1 - PRUNING_BATCH as usize
+		);
+		assert!(EthereumRelay::pruning_confirmed_parcels());
+
+		run_to_block(2);
+
+		assert_eq!(
+			EthereumRelay::confirmed_block_numbers(),
+			(best_confirmed_block_number - 9..=best_confirmed_block_number).collect::<Vec<_>>()
+		);
+		assert!(!EthereumRelay::pruning_confirmed_parcels());
+
+		for number in 1..=best_confirmed_block_number {
+			assert_eq!(
+				EthereumRelay::confirmed_mmr_root_of(number),
+				Some(ethereum_primitives::H256::repeat_byte(number as _))
+			);
+		}
+
+		// Only once
+		EthereumRelay::on_runtime_upgrade();
+
+		assert!(!EthereumRelay::pruning_confirmed_parcels());
+	});
+}
+
+#[test]
+fn migration_should_work_on_v1_storage() {
+	let builder = ExtBuilder::default();
+
+	builder.set_associated_constants();
+
+	// A live chain with the storage of `Releases::V1_0_0`, never built by this genesis config
+	let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap()
+		.into();
+
+	ext.execute_with(|| {
+		let best_confirmed_block_number = 30;
+
+		for number in 1..=best_confirmed_block_number {
+			ConfirmedHeaderParcels::insert(number, mock_relay_header_parcel(number));
+			ConfirmedBlockNumbers::append(number);
+		}
+		BestConfirmedBlockNumber::put(best_confirmed_block_number);
+
+		assert!(!StorageVersion::exists());
+		assert!(!ConfirmedDepth::exists());
+
+		EthereumRelay::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+		assert!(EthereumRelay::pruning_confirmed_parcels());
+		// Nothing moves before the next block
+		assert_eq!(
+			EthereumRelay::confirmed_block_numbers().len(),
+			best_confirmed_block_number as usize
+		);
+
+		run_to_block(1);
+
+		// The default depth of the storage declaration
+		assert_eq!(
+			EthereumRelay::confirmed_block_numbers(),
+			(best_confirmed_block_number - 9..=best_confirmed_block_number).collect::<Vec<_>>()
+		);
+		assert!(!EthereumRelay::pruning_confirmed_parcels());
+		assert_eq!(
+			EthereumRelay::best_confirmed_block_number(),
+			best_confirmed_block_number
+		);
+
+		for number in 1..=best_confirmed_block_number {
+			let archived = number <= best_confirmed_block_number - 10;
+
+			assert_eq!(
+				EthereumRelay::confirmed_header_parcel_of(number).is_some(),
+				!archived
+			);
+			assert_eq!(
+				EthereumRelay::archived_mmr_root_of(number).is_some(),
+				archived
+			);
+			assert_eq!(
+				EthereumRelay::confirmed_mmr_root_of(number),
+				Some(ethereum_primitives::H256::repeat_byte(number as _))
+			);
+		}
+
+		// The next upgrades leave the migrated storage alone
+		EthereumRelay::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+		assert!(!EthereumRelay::pruning_confirmed_parcels());
+	});
+}
+
 // #[test]
 // fn mmr() {
 // 	// 102 header hash
@@ -685,6 +685,23 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 		T::RelayableChain::game_over(&game_id);
 	}
 
+	/// The proofs of a game were verified against the best confirmed header of its start, the
+	/// MMR consistency proof included, a winner beyond a newer best confirmed header is unproven
+	pub fn confirmable(
+		game_id: &RelayHeaderId<T, I>,
+		relay_header_parcel: RelayHeaderParcel<T, I>,
+	) -> Option<RelayHeaderParcel<T, I>> {
+		if Self::best_confirmed_header_id_of(game_id)
+			== T::RelayableChain::best_confirmed_relay_header_id()
+		{
+			Some(relay_header_parcel)
+		} else {
+			trace!(target: "relayer-game", "   >  Best Confirmed Header - MOVED");
+
+			None
+		}
+	}
+
 	pub fn update_games(game_ids: Vec<RelayHeaderId<T, I>>) -> DispatchResult {
 		let now = <frame_system::Module<T>>::block_number();
 		let mut relay_header_parcels = vec![];
@@ -716,7 +733,8 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 					if let Some(relay_header_parcel) =
 						Self::settle_without_challenge(relay_affirmations.pop().unwrap())
 					{
-						relay_header_parcels.push(relay_header_parcel);
+						relay_header_parcels
+							.extend(Self::confirmable(&game_id, relay_header_parcel));
 					}
 				}
 				// No relayer response for the latest round
@@ -732,7 +750,8 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 					if let Some(relay_header_parcel) =
 						Self::settle_with_challenge(&game_id, relay_affirmations.pop().unwrap())
 					{
-						relay_header_parcels.push(relay_header_parcel);
+						relay_header_parcels
+							.extend(Self::confirmable(&game_id, relay_header_parcel));
 					} else {
 						// Should never enter this condition
 
@@ -750,7 +769,8 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 
 						// A whole chain gave, start continuous verification
 						if let Some(relay_header_parcel) = Self::on_chain_arbitrate(&game_id) {
-							relay_header_parcels.push(relay_header_parcel);
+							relay_header_parcels
+								.extend(Self::confirmable(&game_id, relay_header_parcel));
 						} else {
 							Self::settle_abandon(&game_id);
 						}
@@ -840,7 +860,8 @@ impl<T: Config<I>, I: Instance> RelayerGameProtocol for Module<T, I> {
 		ensure!(
 			<RelayHeaderParcelToResolve<T, I>>::decode_len()
 				.map(|length| length as u8)
-				.unwrap_or(0) < T::RelayerGameAdjustor::max_active_games(),
+				.unwrap_or(0)
+				< T::RelayerGameAdjustor::max_active_games(),
 			<Error<T, I>>::ActiveGamesTM
 		);
 
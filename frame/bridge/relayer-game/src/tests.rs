@@ -523,6 +523,38 @@ fn settle_without_challenge_should_work() {
 	})
 }
 
+#[test]
+fn settle_after_best_confirmed_moved_should_not_confirm() {
+	ExtBuilder::default().build().execute_with(|| {
+		let relay_header_parcels = MockRelayHeader::gen_continous(1, vec![1, 1], true);
+
+		assert_ok!(RelayerGame::affirm(
+			&1,
+			relay_header_parcels[0].clone(),
+			None
+		));
+
+		run_to_block(4);
+
+		assert_ok!(RelayerGame::affirm(
+			&2,
+			relay_header_parcels[1].clone(),
+			None
+		));
+
+		run_to_block(7);
+
+		assert_eq!(Relay::best_confirmed_block_number(), 1);
+
+		run_to_block(10);
+
+		assert_eq!(Relay::best_confirmed_block_number(), 1);
+		assert!(Relay::confirmed_header_of(relay_header_parcels[1].number).is_none());
+		assert_eq!(Etp::usable_balance(&2), 200);
+		assert!(Etp::locks(2).is_empty());
+	})
+}
+
 #[test]
 fn settle_with_challenge_should_work() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -145,6 +145,10 @@ pub trait EthApi {
 	#[rpc(name = "eth_getTransactionReceipt")]
 	fn transaction_receipt(&self, _: H256) -> Result<Option<Receipt>>;
 
+	/// Returns the receipts of all the transactions of a block, in block order.
+	#[rpc(name = "eth_getBlockReceipts")]
+	fn block_receipts(&self, _: BlockNumber) -> Result<Option<Vec<Receipt>>>;
+
 	/// Returns an uncles at given block and index.
 	#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
 	fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> Result<Option<RichBlock>>;
@@ -264,10 +264,17 @@ impl<T: Config> frame_support::unsigned::ValidateUnsigned for Module<T> {
 			}
 
 			let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
-			if account_data.balance < fee {
+			if hyperspace_evm::Module::<T>::fee_balance(&origin) < fee {
 				return InvalidTransaction::Payment.into();
 			}
 
+			// Rejected here rather than at dispatch, where the failure would be free.
+			if transaction.action == TransactionAction::Create
+				&& !hyperspace_evm::Module::<T>::can_deploy(&origin)
+			{
+				return InvalidTransaction::Call.into();
+			}
+
 			let mut builder =
 				ValidTransactionBuilder::default().and_provides((origin, transaction.nonce));
 
@@ -140,6 +140,8 @@ parameter_types! {
 	pub const TransactionByteFee: u64 = 1;
 	pub const ChainId: u64 = 22;
 	pub const EVMModuleId: ModuleId = ModuleId(*b"py/evmpa");
+	// Same as `DVMAccountBasicMapping`.
+	pub UnitsPerBalance: U256 = U256::from(10_000_000_000u64);
 }
 
 pub struct HashedAddressMapping;
@@ -160,11 +162,20 @@ impl hyperspace_evm::Config for Test {
 	type AddressMapping = HashedAddressMapping;
 	type EtpCurrency = Etp;
 	type DnaCurrency = Dna;
+	type UnitsPerBalance = UnitsPerBalance;
 	type Event = ();
 	type Precompiles = hyperspace_evm_precompile::HyperspacePrecompiles<Self>;
 	type ChainId = ChainId;
 	type Runner = hyperspace_evm::runner::stack::Runner<Self>;
 	type AccountBasicMapping = DVMAccountBasicMapping<Self>;
+	type Randomness = ();
+	type RandomnessAsDifficulty = ();
+	type HardforkSchedule = ();
+	type StorageDepositPerSlot = ();
+	type StorageDepositPerByte = ();
+	type DeletionWeightLimit = ();
+	type OnChargeTransaction = ();
+	type DeploymentOrigin = frame_system::EnsureRoot<Self::AccountId>;
 }
 
 parameter_types! {
@@ -147,6 +147,39 @@ fn transaction_with_invalid_nonce_should_not_work() {
 	});
 }
 
+#[test]
+fn creation_not_permitted_by_deployment_policy_should_not_work() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let create = sign_transaction(alice, default_erc20_creation_unsigned_transaction());
+		let call = sign_transaction(alice, default_withdraw_unsigned_transaction());
+
+		assert_ok!(Evm::set_deployment_policy(
+			Origin::root(),
+			hyperspace_evm::DeploymentPolicy::AllowList
+		));
+		assert_err!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(create.clone())
+			),
+			InvalidTransaction::Call
+		);
+		assert_ok!(Ethereum::validate_unsigned(
+			TransactionSource::External,
+			&Call::transact(call)
+		));
+
+		assert_ok!(Evm::add_deployer(Origin::root(), alice.address));
+		assert_ok!(Ethereum::validate_unsigned(
+			TransactionSource::External,
+			&Call::transact(create)
+		));
+	});
+}
+
 #[test]
 fn contract_constructor_should_get_executed() {
 	let (pairs, mut ext) = new_test_ext(1);
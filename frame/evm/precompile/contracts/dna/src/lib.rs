@@ -18,6 +18,8 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod mock;
 mod util;
 
 use codec::Decode;
@@ -36,13 +38,14 @@ use sp_std::marker::PhantomData;
 use sp_std::prelude::*;
 use sp_std::vec::Vec;
 
-use hyperspace_evm::{AddressMapping, Config, Runner};
+use hyperspace_evm::{AddressMapping, Config, FeeCurrency, Module, Runner};
 use hyperspace_evm_primitives::Precompile;
 
 type AccountId<T> = <T as frame_system::Config>::AccountId;
 
 const TRANSFER_AND_CALL_ACTION: &[u8] = b"transfer_and_call(address,address,uint256)";
 const WITHDRAW_ACTION: &[u8] = b"withdraw(bytes32,uint256)";
+const PAY_FEE_IN_DNA_ACTION: &[u8] = b"pay_fee_in_dna(bool)";
 const DNA_PRECOMPILE: &str = "0000000000000000000000000000000000000016";
 /// Dna Precompile Contract is used to support the exchange of DNA native asset between hyperspace and dvm contract
 ///
@@ -52,7 +55,7 @@ pub struct Dna<T: Config> {
 }
 
 impl<T: Config> Precompile for Dna<T> {
-	/// There are two actions, one is `transfer_and_call` and the other is `withdraw`
+	/// There are three actions, `transfer_and_call`, `withdraw` and `pay_fee_in_dna`
 	/// 1. Transfer_and_call Action, triggered by the user sending a transaction to the dna precompile
 	/// 	   special evm address, eg(0000000000000000000000000000000000000016). and transfer the sender's
 	///     dna balance to the deployed wdna contract in dvm. The input contain two parts:
@@ -62,6 +65,10 @@ impl<T: Config> Precompile for Dna<T> {
 	///     within the wdna contract, and transfer the balance from wdna balanceof to the hyperspace network. The input contain two parts:
 	///     - p1: The to account id, a withdraw hyperspace public key.
 	///     - p2: The withdraw value
+	/// 3. PayFeeInDna Action, the user sends transaction to the dna precompile to choose whether
+	///     the fees of the sender are paid in DNA, at the rate set by governance. It can't be reached
+	///     through `DELEGATECALL` or `CALLCODE`. The input contain one part:
+	///     - p1: Pay in DNA if true, in ETP otherwise
 	fn execute(
 		input: &[u8],
 		target_limit: Option<u64>,
@@ -118,6 +125,22 @@ impl<T: Config> Precompile for Dna<T> {
 				.map_err(|_| ExitError::Other("Withdraw in Dna precompile failed".into()))?;
 				Ok((ExitSucceed::Returned, vec![], 20000))
 			}
+			Action::PayFeeInDna(pay_in_dna) => {
+				// Under a `DELEGATECALL` the caller is the one of the delegating contract, it must
+				// not be able to choose the fee currency of its own caller.
+				ensure!(
+					context.address == H160::from_str(DNA_PRECOMPILE).unwrap(),
+					ExitError::Other("PayFeeInDna must be called directly".into())
+				);
+
+				let currency = if pay_in_dna {
+					FeeCurrency::Dna
+				} else {
+					FeeCurrency::Etp
+				};
+				Module::<T>::set_fee_currency_of(&context.caller, currency);
+				Ok((ExitSucceed::Returned, vec![], 20000))
+			}
 		}
 	}
 }
@@ -128,18 +151,27 @@ pub enum Action<T: frame_system::Config> {
 	TransferAndCall(CallData),
 	/// Withdraw from wdna contract to substrate account
 	Withdraw(WithdrawData<T>),
+	/// Choose whether the fees of the caller are paid in dna
+	PayFeeInDna(bool),
 }
 
 /// which action depends on the function selector
 pub fn which_action<T: frame_system::Config>(input_data: &[u8]) -> Result<Action<T>, ExitError> {
 	let transfer_and_call_action = &sha3::Keccak256::digest(&TRANSFER_AND_CALL_ACTION)[0..4];
 	let withdraw_action = &sha3::Keccak256::digest(&WITHDRAW_ACTION)[0..4];
+	let pay_fee_in_dna_action = &sha3::Keccak256::digest(&PAY_FEE_IN_DNA_ACTION)[0..4];
 	if &input_data[0..4] == transfer_and_call_action {
 		let decoded_data = CallData::decode(&input_data[4..])?;
 		return Ok(Action::TransferAndCall(decoded_data));
 	} else if &input_data[0..4] == withdraw_action {
 		let decoded_data = WithdrawData::decode(&input_data[4..])?;
 		return Ok(Action::Withdraw(decoded_data));
+	} else if &input_data[0..4] == pay_fee_in_dna_action {
+		let tokens = ethabi::decode(&[ParamType::Bool], &input_data[4..])
+			.map_err(|_| ExitError::Other("ethabi decoded error".into()))?;
+		if let Some(Token::Bool(pay_in_dna)) = tokens.first() {
+			return Ok(Action::PayFeeInDna(*pay_in_dna));
+		}
 	}
 	Err(ExitError::Other("Invalid Action！".into()))
 }
@@ -215,6 +247,7 @@ fn make_call_data(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::mock::*;
 	use std::str::FromStr;
 
 	#[test]
@@ -233,4 +266,31 @@ mod tests {
 			array_bytes::bytes2hex("0x", make_call_data(mock_address, mock_value_2).unwrap());
 		assert_ne!(encoded_str, expected_str);
 	}
+
+	#[test]
+	fn pay_fee_in_dna_only_called_directly() {
+		new_test_ext().execute_with(|| {
+			let caller = H160::from_low_u64_be(1);
+			let mut input = sha3::Keccak256::digest(&PAY_FEE_IN_DNA_ACTION)[0..4].to_vec();
+			input.extend_from_slice(&ethabi::encode(&[Token::Bool(true)]));
+			let execute = |address| {
+				<Dna<Test> as Precompile>::execute(
+					&input,
+					None,
+					&Context {
+						address,
+						caller,
+						apparent_value: Default::default(),
+					},
+				)
+			};
+
+			// Delegated by another contract.
+			assert!(execute(H160::from_low_u64_be(2)).is_err());
+			assert_eq!(Module::<Test>::fee_currency_of(&caller), FeeCurrency::Etp);
+
+			assert!(execute(H160::from_str(DNA_PRECOMPILE).unwrap()).is_ok());
+			assert_eq!(Module::<Test>::fee_currency_of(&caller), FeeCurrency::Dna);
+		});
+	}
 }
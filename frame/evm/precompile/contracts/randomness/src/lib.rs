@@ -0,0 +1,180 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::{ensure, traits::Randomness as RandomnessT};
+use sha3::Digest;
+use sp_std::marker::PhantomData;
+use sp_std::prelude::*;
+use sp_std::vec::Vec;
+
+use hyperspace_evm::Config;
+use hyperspace_evm_primitives::Precompile;
+
+#[cfg(test)]
+mod mock;
+
+const RANDOMNESS_ACTION: &[u8] = b"randomness(bytes32)";
+const RANDOM_SEED_ACTION: &[u8] = b"randomSeed()";
+/// The gas charged for one randomness query, it's a storage read plus a hash.
+const RANDOMNESS_COST: u64 = 3000;
+
+/// Randomness Precompile Contract, used to read the on-chain randomness configured by
+/// `hyperspace_evm::Config::Randomness` (BABE VRF output on Hyperspace)
+///
+/// The contract address: 0000000000000000000000000000000000000017
+pub struct Randomness<T: Config> {
+	_maker: PhantomData<T>,
+}
+
+impl<T: Config> Precompile for Randomness<T> {
+	/// There are two actions, `randomness` and `randomSeed`, both return a `bytes32`
+	/// 1. Randomness Action, `randomness(bytes32 subject)`, the subject is mixed into the
+	///     randomness so that different use cases in the same block get independent values.
+	/// 2. RandomSeed Action, `randomSeed()`, the raw seed, equal to the `DIFFICULTY` opcode
+	///     value when `RandomnessAsDifficulty` is enabled.
+	///
+	/// The value is fixed for a block, so it must not be used where the block author
+	/// withholding a block is an acceptable attack.
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		if let Some(target_gas) = target_gas {
+			ensure!(target_gas >= RANDOMNESS_COST, ExitError::OutOfGas);
+		}
+
+		let output = match which_action(input)? {
+			Action::Randomness(subject) => T::Randomness::random(&subject),
+			Action::RandomSeed => T::Randomness::random_seed(),
+		};
+
+		Ok((
+			ExitSucceed::Returned,
+			output.as_ref().to_vec(),
+			RANDOMNESS_COST,
+		))
+	}
+}
+
+/// Action about Randomness precompile
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+	/// Randomness mixed with the given subject
+	Randomness([u8; 32]),
+	/// The raw random seed of the current block
+	RandomSeed,
+}
+
+/// which action depends on the function selector
+pub fn which_action(input_data: &[u8]) -> Result<Action, ExitError> {
+	ensure!(
+		input_data.len() >= 4,
+		ExitError::Other("Invalid input data length".into())
+	);
+
+	let randomness_action = &sha3::Keccak256::digest(&RANDOMNESS_ACTION)[0..4];
+	let random_seed_action = &sha3::Keccak256::digest(&RANDOM_SEED_ACTION)[0..4];
+	if &input_data[0..4] == randomness_action {
+		ensure!(
+			input_data.len() == 36,
+			ExitError::Other("Invalid input data length".into())
+		);
+
+		let mut subject = [0u8; 32];
+		subject.copy_from_slice(&input_data[4..36]);
+
+		return Ok(Action::Randomness(subject));
+	} else if &input_data[0..4] == random_seed_action {
+		return Ok(Action::RandomSeed);
+	}
+	Err(ExitError::Other("Invalid Action！".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use sp_core::{H160, H256};
+
+	#[test]
+	fn test_which_action() {
+		let mut input = sha3::Keccak256::digest(&RANDOMNESS_ACTION)[0..4].to_vec();
+		input.extend_from_slice(&[7u8; 32]);
+		assert_eq!(which_action(&input).unwrap(), Action::Randomness([7u8; 32]));
+
+		input.pop();
+		assert!(which_action(&input).is_err());
+
+		let input = sha3::Keccak256::digest(&RANDOM_SEED_ACTION)[0..4].to_vec();
+		assert_eq!(which_action(&input).unwrap(), Action::RandomSeed);
+
+		assert!(which_action(&[0u8; 2]).is_err());
+		assert!(which_action(&[0u8; 4]).is_err());
+	}
+
+	#[test]
+	fn execute_returns_the_randomness_of_the_runtime() {
+		new_test_ext().execute_with(|| {
+			let context = Context {
+				address: H160::from_low_u64_be(0x17),
+				caller: H160::default(),
+				apparent_value: Default::default(),
+			};
+			let execute = |input: &[u8]| {
+				<Randomness<Test> as Precompile>::execute(input, Some(RANDOMNESS_COST), &context)
+					.unwrap()
+			};
+
+			let mut input = sha3::Keccak256::digest(&RANDOMNESS_ACTION)[0..4].to_vec();
+			input.extend_from_slice(&[7u8; 32]);
+
+			assert_eq!(
+				execute(&input),
+				(
+					ExitSucceed::Returned,
+					MockRandomness::random(&[7u8; 32]).as_bytes().to_vec(),
+					RANDOMNESS_COST
+				)
+			);
+
+			let input = sha3::Keccak256::digest(&RANDOM_SEED_ACTION)[0..4].to_vec();
+
+			assert_eq!(
+				execute(&input),
+				(
+					ExitSucceed::Returned,
+					H256::repeat_byte(7).as_bytes().to_vec(),
+					RANDOMNESS_COST
+				)
+			);
+
+			assert_eq!(
+				<Randomness<Test> as Precompile>::execute(
+					&input,
+					Some(RANDOMNESS_COST - 1),
+					&context
+				),
+				Err(ExitError::OutOfGas)
+			);
+		});
+	}
+}
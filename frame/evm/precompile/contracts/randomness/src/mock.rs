@@ -0,0 +1,159 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Test utilities
+
+use codec::{Decode, Encode};
+use frame_support::{impl_outer_origin, traits::Randomness as RandomnessT};
+use sha3::Digest;
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	AccountId32, RuntimeDebug,
+};
+
+use hyperspace_evm::{
+	runner::stack::Runner, AddressMapping, EnsureAddressTruncated, FeeCalculator,
+	RawAccountBasicMapping,
+};
+
+type Balance = u64;
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+hyperspace_support::impl_test_account_data! {}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Test;
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = ();
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+impl hyperspace_balances::Config<EtpInstance> for Test {
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ();
+	type BalanceInfo = AccountData<Balance>;
+	type AccountStore = frame_system::Module<Test>;
+	type MaxLocks = ();
+	type OtherCurrencies = ();
+	type WeightInfo = ();
+}
+impl hyperspace_balances::Config<DnaInstance> for Test {
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ();
+	type BalanceInfo = AccountData<Balance>;
+	type AccountStore = frame_system::Module<Test>;
+	type MaxLocks = ();
+	type OtherCurrencies = ();
+	type WeightInfo = ();
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ();
+	type WeightInfo = ();
+}
+
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> U256 {
+		0.into()
+	}
+}
+
+pub struct TruncatedAddressMapping;
+impl AddressMapping<AccountId32> for TruncatedAddressMapping {
+	fn into_account_id(address: H160) -> AccountId32 {
+		let mut data = [0u8; 32];
+		data[0..20].copy_from_slice(&address[..]);
+		AccountId32::from(data)
+	}
+}
+
+/// Keccak of the subject, the seed is `0x0707..07`.
+pub struct MockRandomness;
+impl RandomnessT<H256> for MockRandomness {
+	fn random(subject: &[u8]) -> H256 {
+		H256::from_slice(&sha3::Keccak256::digest(subject))
+	}
+
+	fn random_seed() -> H256 {
+		H256::repeat_byte(7)
+	}
+}
+
+impl hyperspace_evm::Config for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = ();
+	type CallOrigin = EnsureAddressTruncated;
+	type WithdrawOrigin = EnsureAddressTruncated;
+	type AddressMapping = TruncatedAddressMapping;
+	type EtpCurrency = hyperspace_balances::Module<Test, EtpInstance>;
+	type DnaCurrency = hyperspace_balances::Module<Test, DnaInstance>;
+	type UnitsPerBalance = ();
+	type Event = ();
+	type Precompiles = ();
+	type ChainId = ();
+	type Runner = Runner<Self>;
+	type AccountBasicMapping = RawAccountBasicMapping<Self>;
+	type Randomness = MockRandomness;
+	type RandomnessAsDifficulty = ();
+	type HardforkSchedule = ();
+	type StorageDepositPerSlot = ();
+	type StorageDepositPerByte = ();
+	type DeletionWeightLimit = ();
+	type OnChargeTransaction = ();
+	type DeploymentOrigin = frame_system::EnsureRoot<Self::AccountId>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap()
+		.into()
+}
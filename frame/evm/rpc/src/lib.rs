@@ -0,0 +1,99 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Node-specific RPC methods for interaction with EVM.
+
+// --- hyperspace ---
+pub use hyperspace_evm_rpc_runtime_api::EVMApi as EVMRuntimeApi;
+
+// --- core ---
+use core::fmt::Debug;
+// --- std ---
+use std::sync::Arc;
+// --- crates ---
+use codec::Codec;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+// --- substrate ---
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{H160, U256};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, MaybeDisplay, MaybeFromStr},
+};
+// --- hyperspace ---
+use hyperspace_evm_rpc_runtime_api::RuntimeDispatchInfo;
+
+const RUNTIME_ERROR: i64 = -1;
+
+#[rpc]
+pub trait EVMApi<Response> {
+	#[rpc(name = "evm_storageDeposit")]
+	fn storage_deposit(&self, address: H160) -> Result<Response>;
+
+	/// The balance the fees of `address` are paid from, in the same units as `eth_getBalance`.
+	#[rpc(name = "evm_feeBalance")]
+	fn fee_balance(&self, address: H160) -> Result<U256>;
+}
+
+pub struct EVM<Client, Block> {
+	client: Arc<Client>,
+	_marker: std::marker::PhantomData<Block>,
+}
+
+impl<Client, Block> EVM<Client, Block> {
+	pub fn new(client: Arc<Client>) -> Self {
+		Self {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<Client, Block, Balance> EVMApi<RuntimeDispatchInfo<Balance>> for EVM<Client, Block>
+where
+	Client: 'static + Send + Sync + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	Client::Api: EVMRuntimeApi<Block, Balance>,
+	Block: BlockT,
+	Balance: Debug + Codec + MaybeDisplay + MaybeFromStr,
+{
+	fn storage_deposit(&self, address: H160) -> Result<RuntimeDispatchInfo<Balance>> {
+		let api = self.client.runtime_api();
+		let best = self.client.info().best_hash;
+		let at = BlockId::hash(best);
+
+		api.storage_deposit_of(&at, address).map_err(|e| Error {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to query storage deposit.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn fee_balance(&self, address: H160) -> Result<U256> {
+		let api = self.client.runtime_api();
+		let best = self.client.info().best_hash;
+		let at = BlockId::hash(best);
+
+		api.fee_balance_of(&at, address).map_err(|e| Error {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to query fee balance.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
@@ -0,0 +1,160 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! The geth genesis `alloc` format, to move EVM state between chains.
+
+// --- std ---
+use std::{collections::BTreeMap, fs::File, path::Path, str::FromStr};
+// --- crates ---
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+// --- substrate ---
+use sp_core::{H160, H256, U256};
+// --- hyperspace ---
+use crate::GenesisAccount;
+
+/// An account of a geth genesis `alloc`.
+#[derive(Clone, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct GethAccount {
+	/// Account balance, hex or decimal.
+	#[serde(with = "hex_or_decimal")]
+	pub balance: U256,
+	/// Account nonce, hex or decimal.
+	#[serde(
+		default,
+		with = "hex_or_decimal",
+		skip_serializing_if = "U256::is_zero"
+	)]
+	pub nonce: U256,
+	/// Account code.
+	#[serde(
+		default,
+		with = "sp_core::bytes",
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub code: Vec<u8>,
+	/// Non-zero storage slots.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl From<GethAccount> for GenesisAccount {
+	fn from(account: GethAccount) -> Self {
+		Self {
+			nonce: account.nonce,
+			balance: account.balance,
+			storage: account.storage,
+			code: account.code,
+		}
+	}
+}
+
+impl From<GenesisAccount> for GethAccount {
+	fn from(account: GenesisAccount) -> Self {
+		Self {
+			balance: account.balance,
+			nonce: account.nonce,
+			code: account.code,
+			storage: account.storage,
+		}
+	}
+}
+
+/// A geth genesis `alloc`, the accounts keyed by address.
+#[derive(Clone, Default, PartialEq, Eq, Debug, Serialize)]
+#[serde(transparent)]
+pub struct GethAlloc(pub BTreeMap<H160, GethAccount>);
+
+impl GethAlloc {
+	/// Parse either a whole geth genesis file, whose `alloc` is taken, or a bare `alloc`.
+	pub fn from_json(data: &str) -> Result<Self, String> {
+		let mut value: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+		if let Some(alloc) = value.get_mut("alloc") {
+			value = alloc.take();
+		}
+
+		// geth takes the addresses with or without the `0x` prefix
+		let accounts: BTreeMap<String, GethAccount> =
+			serde_json::from_value(value).map_err(|e| e.to_string())?;
+
+		accounts
+			.into_iter()
+			.map(
+				|(address, account)| match sp_core::bytes::from_hex(&address) {
+					Ok(bytes) if bytes.len() == 20 => Ok((H160::from_slice(&bytes), account)),
+					_ => Err(format!("invalid address {}", address)),
+				},
+			)
+			.collect::<Result<_, _>>()
+			.map(Self)
+	}
+
+	/// Read a geth genesis or `alloc` file, see `from_json`.
+	pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+		let path = path.as_ref();
+		let data = std::fs::read_to_string(path)
+			.map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+		Self::from_json(&data)
+	}
+
+	/// Write the `alloc` to a file, pretty printed.
+	pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), String> {
+		let path = path.as_ref();
+		let file = File::create(path)
+			.map_err(|e| format!("failed to create {}: {}", path.display(), e))?;
+
+		serde_json::to_writer_pretty(file, self).map_err(|e| e.to_string())
+	}
+
+	/// The accounts to build the EVM genesis with.
+	pub fn into_genesis_accounts(self) -> BTreeMap<H160, GenesisAccount> {
+		self.0
+			.into_iter()
+			.map(|(address, account)| (address, account.into()))
+			.collect()
+	}
+}
+
+mod hex_or_decimal {
+	use super::*;
+
+	pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&format!("{:#x}", value))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+		match Value::deserialize(deserializer)? {
+			Value::String(s) => match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+				Some(hex) => U256::from_str(hex)
+					.map_err(|e| D::Error::custom(format!("invalid quantity {}: {:?}", s, e))),
+				None => U256::from_dec_str(&s)
+					.map_err(|e| D::Error::custom(format!("invalid quantity {}: {:?}", s, e))),
+			},
+			Value::Number(n) => n
+				.as_u64()
+				.map(U256::from)
+				.ok_or_else(|| D::Error::custom(format!("invalid number {}", n))),
+			v => Err(D::Error::custom(format!(
+				"expected a quantity, found {}",
+				v
+			))),
+		}
+	}
+}
@@ -20,6 +20,8 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+pub mod geth;
 pub mod runner;
 mod tests;
 
@@ -30,27 +32,46 @@ pub use hyperspace_evm_primitives::{
 };
 pub use evm::{ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
 
-#[cfg(feature = "std")]
 use codec::{Decode, Encode};
 use evm::Config as EvmConfig;
-use frame_support::dispatch::DispatchResultWithPostInfo;
-use frame_support::traits::{Currency, Get};
+use frame_support::dispatch::{DispatchResult, DispatchResultWithPostInfo};
+use frame_support::traits::{
+	BalanceStatus, Currency, EnsureOrigin, ExistenceRequirement, FindAuthor, Get, Imbalance,
+	OnUnbalanced, Randomness, ReservableCurrency, WithdrawReasons,
+};
 use frame_support::weights::{Pays, PostDispatchInfo, Weight};
-use frame_support::{decl_error, decl_event, decl_module, decl_storage};
-use frame_system::RawOrigin;
+use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure};
+use frame_system::{ensure_root, ensure_signed, RawOrigin};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_core::{Hasher, H160, H256, U256};
 use sp_runtime::{
-	traits::{BadOrigin, UniqueSaturatedInto},
-	AccountId32,
+	traits::{BadOrigin, Saturating, UniqueSaturatedInto, Zero},
+	AccountId32, FixedPointNumber, FixedU128, PerThing, Perbill, RuntimeDebug,
 };
-use sp_std::vec::Vec;
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+// --- hyperspace ---
+use hyperspace_evm_rpc_runtime_api::RuntimeDispatchInfo;
+use hyperspace_support::{impl_rpc, traits::OnUnbalancedDna};
 
 /// Type alias for currency balance.
 pub type BalanceOf<T> =
 	<<T as Config>::EtpCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+/// Type alias for negative imbalance of the currency.
+pub type NegativeImbalanceOf<T> = <<T as Config>::EtpCurrency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// Type alias for DNA balance.
+pub type DnaBalanceOf<T> =
+	<<T as Config>::DnaCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Type alias for negative imbalance of DNA.
+pub type DnaNegativeImbalanceOf<T> = <<T as Config>::DnaCurrency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
 /// Config that outputs the current transaction gas price.
 pub trait FeeCalculator {
 	/// Return the minimal required gas price.
@@ -192,6 +213,22 @@ impl AddressMapping<AccountId32> for ConcatAddressMapping {
 		AccountId32::from(data)
 	}
 }
+impl ConcatAddressMapping {
+	/// The EVM address an account id was mapped from, `None` if it's not a mapped account id.
+	pub fn into_address(account_id: &AccountId32) -> Option<H160> {
+		let data: &[u8; 32] = account_id.as_ref();
+		let checksum: u8 = data[1..31].iter().fold(data[0], |sum, &byte| sum ^ byte);
+
+		if &data[0..4] == b"dvm:"
+			&& data[4..11].iter().all(|&byte| byte == 0)
+			&& data[31] == checksum
+		{
+			Some(H160::from_slice(&data[11..31]))
+		} else {
+			None
+		}
+	}
+}
 
 pub trait AccountBasicMapping {
 	fn account_basic(address: &H160) -> Account;
@@ -250,7 +287,307 @@ impl GasWeightMapping for () {
 	}
 }
 
+/// The EIP-3529 refund reduction on top of Istanbul.
+///
+/// The `SELFDESTRUCT` refund and the refund quotient are applied by the runner, see
+/// [`EvmHardfork::refunds_selfdestruct`] and [`EvmHardfork::refund_quotient`], as is EIP-3541,
+/// see [`EvmHardfork::rejects_ef_code`].
+const fn eip3529() -> EvmConfig {
+	EvmConfig {
+		refund_sstore_clears: 4800,
+		..EvmConfig::istanbul()
+	}
+}
+
+/// Lift the EIP-170 contract-size limit of `config`.
+const fn without_contract_size_limit(config: EvmConfig) -> EvmConfig {
+	EvmConfig {
+		create_contract_limit: None,
+		..config
+	}
+}
+
 static ISTANBUL_CONFIG: EvmConfig = EvmConfig::istanbul();
+static ISTANBUL_UNLIMITED_CONFIG: EvmConfig = without_contract_size_limit(EvmConfig::istanbul());
+static EIP3529_CONFIG: EvmConfig = eip3529();
+static EIP3529_UNLIMITED_CONFIG: EvmConfig = without_contract_size_limit(eip3529());
+
+/// The EVM hardforks supported by the module, in activation order.
+///
+/// Neither Berlin nor London, evm 0.22 has no access lists to price EIP-2929 with and no base fee
+/// (EIP-1559, EIP-3198).
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, RuntimeDebug)]
+pub enum EvmHardfork {
+	Istanbul,
+	/// Istanbul with the refund reduction (EIP-3529) and the `0xEF` code rejection (EIP-3541) of
+	/// London.
+	Eip3529,
+}
+
+impl EvmHardfork {
+	/// Get the EVM config of this hardfork, with or without the EIP-170 contract-size limit.
+	pub fn config(&self, contract_size_limit: bool) -> &'static EvmConfig {
+		match (self, contract_size_limit) {
+			(EvmHardfork::Istanbul, true) => &ISTANBUL_CONFIG,
+			(EvmHardfork::Istanbul, false) => &ISTANBUL_UNLIMITED_CONFIG,
+			(EvmHardfork::Eip3529, true) => &EIP3529_CONFIG,
+			(EvmHardfork::Eip3529, false) => &EIP3529_UNLIMITED_CONFIG,
+		}
+	}
+
+	/// Whether new code starting with the `0xEF` byte is rejected (EIP-3541).
+	pub fn rejects_ef_code(&self) -> bool {
+		*self >= EvmHardfork::Eip3529
+	}
+
+	/// Whether `SELFDESTRUCT` is refunded, removed by EIP-3529.
+	pub fn refunds_selfdestruct(&self) -> bool {
+		*self < EvmHardfork::Eip3529
+	}
+
+	/// The refund is capped at the used gas divided by this quotient, a fifth since EIP-3529.
+	pub fn refund_quotient(&self) -> u64 {
+		if *self >= EvmHardfork::Eip3529 {
+			5
+		} else {
+			2
+		}
+	}
+}
+
+/// Select the EVM hardfork by block number.
+pub trait HardforkSchedule<BlockNumber> {
+	/// The hardfork active at block `number`.
+	fn hardfork_at(number: BlockNumber) -> EvmHardfork;
+
+	/// Whether the EIP-170 contract-size limit is enforced.
+	fn contract_size_limit() -> bool {
+		true
+	}
+}
+
+impl<BlockNumber> HardforkSchedule<BlockNumber> for () {
+	fn hardfork_at(_: BlockNumber) -> EvmHardfork {
+		EvmHardfork::Istanbul
+	}
+}
+
+/// Activate EIP-3529 at `Eip3529Block`, Istanbul before it.
+pub struct ScheduledHardforks<Eip3529Block>(sp_std::marker::PhantomData<Eip3529Block>);
+
+impl<BlockNumber, Eip3529Block> HardforkSchedule<BlockNumber> for ScheduledHardforks<Eip3529Block>
+where
+	BlockNumber: PartialOrd,
+	Eip3529Block: Get<BlockNumber>,
+{
+	fn hardfork_at(number: BlockNumber) -> EvmHardfork {
+		if number >= Eip3529Block::get() {
+			EvmHardfork::Eip3529
+		} else {
+			EvmHardfork::Istanbul
+		}
+	}
+}
+
+/// The currency an account pays its EVM transaction fees in.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum FeeCurrency {
+	/// The EVM balance, which is backed by `EtpCurrency`.
+	Etp,
+	/// `DnaCurrency`, converted at the `DnaFeeRate` set by governance.
+	Dna,
+}
+impl Default for FeeCurrency {
+	fn default() -> Self {
+		FeeCurrency::Etp
+	}
+}
+
+/// Who may deploy contracts, both by transactions and by the `CREATE`/`CREATE2` opcodes.
+///
+/// The deployer of a contract created by another contract is the creating contract.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum DeploymentPolicy<Balance> {
+	/// Anyone may deploy.
+	Open,
+	/// Only the deployers added by the `DeploymentOrigin` may deploy.
+	AllowList,
+	/// Only the deployers with at least the given deposit reserved for them may deploy.
+	Deposit(Balance),
+}
+impl<Balance> Default for DeploymentPolicy<Balance> {
+	fn default() -> Self {
+		DeploymentPolicy::Open
+	}
+}
+
+/// The fee withdrawn from the source, in the currency it pays in.
+pub enum FeeImbalance<T: Config> {
+	/// Withdrawn from `EtpCurrency`.
+	Etp(NegativeImbalanceOf<T>),
+	/// Withdrawn from `DnaCurrency`.
+	Dna(DnaNegativeImbalanceOf<T>),
+}
+
+impl<T: Config> FeeImbalance<T> {
+	/// The value of the imbalance in EVM balance units.
+	pub fn value(&self) -> U256 {
+		match self {
+			FeeImbalance::Etp(imbalance) => Module::<T>::etp_to_evm(imbalance.peek()),
+			FeeImbalance::Dna(imbalance) => Module::<T>::dna_to_evm(imbalance.peek()),
+		}
+	}
+
+	/// Split off `value` EVM balance units, converted to whole units of the currency.
+	pub fn split(self, value: U256, round_up: bool) -> (Self, Self) {
+		match self {
+			FeeImbalance::Etp(imbalance) => {
+				let (first, second) = imbalance.split(Module::<T>::evm_to_etp(value, round_up));
+
+				(FeeImbalance::Etp(first), FeeImbalance::Etp(second))
+			}
+			FeeImbalance::Dna(imbalance) => {
+				let (first, second) = imbalance.split(Module::<T>::evm_to_dna(value, round_up));
+
+				(FeeImbalance::Dna(first), FeeImbalance::Dna(second))
+			}
+		}
+	}
+
+	/// Credit the imbalance to `who`, it's burned if it can't create the account.
+	pub fn resolve(self, who: &H160) {
+		let who = T::AddressMapping::into_account_id(*who);
+
+		match self {
+			FeeImbalance::Etp(imbalance) => T::EtpCurrency::resolve_creating(&who, imbalance),
+			FeeImbalance::Dna(imbalance) => T::DnaCurrency::resolve_creating(&who, imbalance),
+		}
+	}
+}
+
+/// Handle withdrawing, refunding and depositing of the EVM transaction fee.
+///
+/// The fee is always given in EVM balance units, `currency` is the one the source pays in. The
+/// withdrawn fee is handed back to `correct_and_deposit_fee`, so that the refund is made in the
+/// currency that was withdrawn and the paid fee is moved rather than issued.
+pub trait OnChargeEVMTransaction<T: Config> {
+	/// Withdraw the maximum fee from the source before the execution.
+	fn withdraw_fee(
+		who: &H160,
+		fee: U256,
+		currency: FeeCurrency,
+	) -> Result<FeeImbalance<T>, Error<T>>;
+
+	/// Refund the unused part of `already_withdrawn` to the source and handle the
+	/// `corrected_fee` actually paid.
+	fn correct_and_deposit_fee(who: &H160, corrected_fee: U256, already_withdrawn: FeeImbalance<T>);
+}
+
+/// Only refund the unused fee, the paid fee is burned.
+impl<T: Config> OnChargeEVMTransaction<T> for () {
+	fn withdraw_fee(
+		who: &H160,
+		fee: U256,
+		currency: FeeCurrency,
+	) -> Result<FeeImbalance<T>, Error<T>> {
+		Module::<T>::withdraw_fee_in(who, fee, currency)
+	}
+
+	fn correct_and_deposit_fee(
+		who: &H160,
+		corrected_fee: U256,
+		already_withdrawn: FeeImbalance<T>,
+	) {
+		let (_, refund) = already_withdrawn.split(corrected_fee, true);
+
+		refund.resolve(who);
+	}
+}
+
+/// The shares of the paid EVM fee, the rest is burned.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct FeeDistribution {
+	/// Paid to the block author.
+	pub author: Perbill,
+	/// Paid to the treasury.
+	pub treasury: Perbill,
+}
+
+impl FeeDistribution {
+	fn share(ratio: Perbill, fee: U256) -> U256 {
+		fee.saturating_mul(ratio.deconstruct().into()) / U256::from(Perbill::ACCURACY)
+	}
+}
+
+/// Split the paid fee between the block author, the treasury and a burn, following the
+/// `FeeSplit` set by governance.
+///
+/// The shares are split off the withdrawn fee, in the currency it was paid in. The author share
+/// is paid to the EVM account found by `FindAuthor`, it's burned if there is no author. The
+/// treasury share is handed to `Treasury`, the remainders below one currency unit are burned.
+pub struct EVMFeeSplit<FindAuthor, Treasury>(sp_std::marker::PhantomData<(FindAuthor, Treasury)>);
+
+impl<T, FA, OU> OnChargeEVMTransaction<T> for EVMFeeSplit<FA, OU>
+where
+	T: Config,
+	FA: FindAuthor<H160>,
+	OU: OnUnbalanced<NegativeImbalanceOf<T>> + OnUnbalancedDna<DnaNegativeImbalanceOf<T>>,
+{
+	fn withdraw_fee(
+		who: &H160,
+		fee: U256,
+		currency: FeeCurrency,
+	) -> Result<FeeImbalance<T>, Error<T>> {
+		Module::<T>::withdraw_fee_in(who, fee, currency)
+	}
+
+	fn correct_and_deposit_fee(
+		who: &H160,
+		corrected_fee: U256,
+		already_withdrawn: FeeImbalance<T>,
+	) {
+		let (paid, refund) = already_withdrawn.split(corrected_fee, true);
+
+		refund.resolve(who);
+
+		let fee = paid.value();
+
+		if fee.is_zero() {
+			return;
+		}
+
+		let split = Module::<T>::fee_split();
+		let digest = <frame_system::Module<T>>::digest();
+		let pre_runtime_digests = digest.logs().iter().filter_map(|d| d.as_pre_runtime());
+		let author = FA::find_author(pre_runtime_digests);
+		let (to_author, paid) = match author {
+			Some(author) => {
+				let (share, paid) = paid.split(FeeDistribution::share(split.author, fee), false);
+				let value = share.value();
+
+				share.resolve(&author);
+
+				(value, paid)
+			}
+			None => (U256::zero(), paid),
+		};
+		let (share, burned) = paid.split(FeeDistribution::share(split.treasury, fee), false);
+		let to_treasury = share.value();
+		let to_burn = burned.value();
+
+		match share {
+			FeeImbalance::Etp(imbalance) => <OU as OnUnbalanced<_>>::on_unbalanced(imbalance),
+			FeeImbalance::Dna(imbalance) => <OU as OnUnbalancedDna<_>>::on_unbalanced(imbalance),
+		}
+
+		Module::<T>::deposit_event(Event::<T>::FeeDistributed(
+			author.unwrap_or_default(),
+			to_author,
+			to_treasury,
+			to_burn,
+		));
+	}
+}
 
 /// EVM module trait
 pub trait Config: frame_system::Config + pallet_timestamp::Config {
@@ -267,9 +604,11 @@ pub trait Config: frame_system::Config + pallet_timestamp::Config {
 	/// Mapping from address to account id.
 	type AddressMapping: AddressMapping<Self::AccountId>;
 	/// Etp Currency type
-	type EtpCurrency: Currency<Self::AccountId>;
+	type EtpCurrency: ReservableCurrency<Self::AccountId>;
 	/// Dna Currency type
 	type DnaCurrency: Currency<Self::AccountId>;
+	/// The EVM balance units that make one unit of the currencies, used to convert fees.
+	type UnitsPerBalance: Get<U256>;
 
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
@@ -281,10 +620,31 @@ pub trait Config: frame_system::Config + pallet_timestamp::Config {
 	type Runner: Runner<Self>;
 	/// The account basic mapping way
 	type AccountBasicMapping: AccountBasicMapping;
+	/// The on-chain randomness exposed to contracts by the randomness precompile.
+	type Randomness: Randomness<Self::Hash>;
+	/// Return `Randomness::random_seed` as the `DIFFICULTY` opcode value instead of zero.
+	type RandomnessAsDifficulty: Get<bool>;
+	/// The hardfork schedule, selects the EVM config by block number.
+	type HardforkSchedule: HardforkSchedule<Self::BlockNumber>;
+	/// The deposit reserved for each non-zero storage slot of a contract.
+	type StorageDepositPerSlot: Get<BalanceOf<Self>>;
+	/// The deposit reserved for each byte of contract code.
+	type StorageDepositPerByte: Get<BalanceOf<Self>>;
+	/// The maximum weight spent on removing the storage of self-destructed contracts per block.
+	type DeletionWeightLimit: Get<Weight>;
+	/// Handler of the transaction fee.
+	type OnChargeTransaction: OnChargeEVMTransaction<Self>;
+	/// The origin which sets the deployment policy and manages the deployers.
+	type DeploymentOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The hardfork active at the current block.
+	fn hardfork() -> EvmHardfork {
+		Self::HardforkSchedule::hardfork_at(frame_system::Module::<Self>::block_number())
+	}
 
 	/// EVM config used in the module.
 	fn config() -> &'static EvmConfig {
-		&ISTANBUL_CONFIG
+		Self::hardfork().config(Self::HardforkSchedule::contract_size_limit())
 	}
 }
 
@@ -302,11 +662,48 @@ pub struct GenesisAccount {
 	pub code: Vec<u8>,
 }
 
+/// The storage a contract occupies and the deposit reserved for it on the contract account.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct StorageDeposit<AccountId, Balance> {
+	/// Number of non-zero storage slots.
+	pub slots: u32,
+	/// Length of the contract code.
+	pub code_len: u32,
+	/// The deposit currently reserved, for the code and all the slots.
+	pub deposit: Balance,
+	/// Who paid the deposit of the code and how much.
+	pub code_payer: Option<(AccountId, Balance)>,
+}
+
 decl_storage! {
 	trait Store for Module<T: Config> as HyperspaceEVM {
 		AccountCodes get(fn account_codes): map hasher(blake2_128_concat) H160 => Vec<u8>;
 		AccountStorages get(fn account_storages):
 			double_map hasher(blake2_128_concat) H160, hasher(blake2_128_concat) H256 => H256;
+		/// Storage deposit of contracts, contracts created before the deposit was introduced
+		/// have no record and are exempt.
+		StorageDeposits get(fn storage_deposit):
+			map hasher(blake2_128_concat) H160
+			=> Option<StorageDeposit<T::AccountId, BalanceOf<T>>>;
+		/// Who paid the deposit of a contract storage slot and how much.
+		SlotDepositPayers get(fn slot_deposit_payer):
+			double_map hasher(blake2_128_concat) H160, hasher(blake2_128_concat) H256
+			=> Option<(T::AccountId, BalanceOf<T>)>;
+		/// Self-destructed contracts whose storage is still being removed.
+		DeletionQueue get(fn queued_for_deletion): map hasher(blake2_128_concat) H160 => bool;
+		/// The shares of the paid fee used by `EVMFeeSplit`.
+		FeeSplit get(fn fee_split): FeeDistribution;
+		/// The currency each account opted to pay its fees in.
+		FeeCurrencies get(fn fee_currency_of): map hasher(blake2_128_concat) H160 => FeeCurrency;
+		/// The DNA charged for one ETP of fee, paying fees in DNA is disabled if it's not set.
+		DnaFeeRate get(fn dna_fee_rate): Option<FixedU128>;
+		/// Who may deploy contracts.
+		ContractDeployment get(fn deployment_policy): DeploymentPolicy<BalanceOf<T>>;
+		/// The deployers allowed by the `AllowList` policy.
+		Deployers get(fn is_deployer): map hasher(blake2_128_concat) H160 => bool;
+		/// The deposits reserved for deployers by the `Deposit` policy, with who reserved them.
+		DeployerDeposits get(fn deployer_deposit):
+			map hasher(blake2_128_concat) H160 => Option<(T::AccountId, BalanceOf<T>)>;
 	}
 
 	add_extra_genesis {
@@ -331,6 +728,7 @@ decl_event! {
 	/// EVM events
 	pub enum Event<T> where
 		<T as frame_system::Config>::AccountId,
+		Balance = BalanceOf<T>,
 	{
 		/// Ethereum events from contracts.
 		Log(Log),
@@ -346,6 +744,28 @@ decl_event! {
 		BalanceDeposit(AccountId, H160, U256),
 		/// A withdrawal has been made from a given address. \[sender, address, value\]
 		BalanceWithdraw(AccountId, H160, U256),
+		/// A storage deposit has been reserved for a contract. \[payer, contract, value\]
+		StorageDepositReserved(AccountId, H160, Balance),
+		/// A storage deposit has been refunded from a contract. \[payer, contract, value\]
+		StorageDepositRefunded(AccountId, H160, Balance),
+		/// The fee split has been set. \[author, treasury\]
+		FeeSplitSet(Perbill, Perbill),
+		/// A paid fee has been distributed. \[author, to author, to treasury, burned\]
+		FeeDistributed(H160, U256, U256, U256),
+		/// An account has chosen the currency it pays fees in. \[address, currency\]
+		FeeCurrencySet(H160, FeeCurrency),
+		/// The DNA fee rate has been set, `None` disables paying fees in DNA. \[rate\]
+		DnaFeeRateSet(Option<FixedU128>),
+		/// The deployment policy has been set. \[policy\]
+		DeploymentPolicySet(DeploymentPolicy<Balance>),
+		/// A deployer has been added to the allow list. \[deployer\]
+		DeployerAdded(H160),
+		/// A deployer has been removed from the allow list. \[deployer\]
+		DeployerRemoved(H160),
+		/// A deployer deposit has been reserved. \[payer, deployer, value\]
+		DeployerDepositReserved(AccountId, H160, Balance),
+		/// A deployer deposit has been released. \[payer, deployer, value\]
+		DeployerDepositReleased(AccountId, H160, Balance),
 	}
 }
 
@@ -363,6 +783,20 @@ decl_error! {
 		GasPriceTooLow,
 		/// Nonce is invalid
 		InvalidNonce,
+		/// Not enough balance to pay the storage deposit
+		StorageDepositLow,
+		/// The fee shares add up to more than the whole
+		InvalidFeeSplit,
+		/// The DNA fee rate is zero
+		InvalidFeeRate,
+		/// The deployment policy doesn't permit the deployer to deploy contracts
+		DeploymentNotPermitted,
+		/// The deployment policy doesn't require a deposit
+		DeployerDepositNotRequired,
+		/// A deposit is reserved for the deployer already
+		DeployerDepositExists,
+		/// No deposit is reserved for the deployer by the sender
+		DeployerDepositNotFound,
 	}
 }
 
@@ -372,6 +806,115 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_initialize(_: T::BlockNumber) -> Weight {
+			Self::process_deletion_queue(T::DeletionWeightLimit::get())
+		}
+
+		/// Set the shares of the paid fee going to the block author and the treasury, the rest is
+		/// burned.
+		#[weight = 10_000_000]
+		fn set_fee_split(origin, author: Perbill, treasury: Perbill) {
+			ensure_root(origin)?;
+			ensure!(
+				author.deconstruct().saturating_add(treasury.deconstruct()) <= Perbill::ACCURACY,
+				<Error<T>>::InvalidFeeSplit
+			);
+
+			FeeSplit::put(FeeDistribution { author, treasury });
+
+			Self::deposit_event(Event::<T>::FeeSplitSet(author, treasury));
+		}
+
+		/// Set the DNA charged for one ETP of fee, `None` disables paying fees in DNA.
+		#[weight = 10_000_000]
+		fn set_dna_fee_rate(origin, rate: Option<FixedU128>) {
+			ensure_root(origin)?;
+			ensure!(rate.map_or(true, |rate| !rate.is_zero()), <Error<T>>::InvalidFeeRate);
+
+			DnaFeeRate::set(rate);
+
+			Self::deposit_event(Event::<T>::DnaFeeRateSet(rate));
+		}
+
+		/// Choose the currency the fees of `address` are paid in.
+		///
+		/// DNA is only charged while the `DnaFeeRate` is set, ETP is charged otherwise.
+		#[weight = 10_000_000]
+		fn set_fee_currency(origin, address: H160, currency: FeeCurrency) {
+			T::CallOrigin::ensure_address_origin(&address, origin)?;
+
+			Self::set_fee_currency_of(&address, currency);
+		}
+
+		/// Set who may deploy contracts.
+		///
+		/// The existing deployers and deposits are kept, so that switching back restores them.
+		#[weight = 10_000_000]
+		fn set_deployment_policy(origin, policy: DeploymentPolicy<BalanceOf<T>>) {
+			T::DeploymentOrigin::ensure_origin(origin)?;
+
+			ContractDeployment::<T>::put(policy.clone());
+
+			Self::deposit_event(Event::<T>::DeploymentPolicySet(policy));
+		}
+
+		/// Allow `deployer` to deploy contracts under the `AllowList` policy.
+		#[weight = 10_000_000]
+		fn add_deployer(origin, deployer: H160) {
+			T::DeploymentOrigin::ensure_origin(origin)?;
+
+			Deployers::insert(deployer, true);
+
+			Self::deposit_event(Event::<T>::DeployerAdded(deployer));
+		}
+
+		/// Disallow `deployer` to deploy contracts under the `AllowList` policy.
+		#[weight = 10_000_000]
+		fn remove_deployer(origin, deployer: H160) {
+			T::DeploymentOrigin::ensure_origin(origin)?;
+
+			Deployers::remove(deployer);
+
+			Self::deposit_event(Event::<T>::DeployerRemoved(deployer));
+		}
+
+		/// Reserve the deposit required by the `Deposit` policy for `deployer` from the sender.
+		///
+		/// Anyone may reserve it, e.g. for a factory contract.
+		#[weight = 10_000_000]
+		fn reserve_deployer_deposit(origin, deployer: H160) {
+			let who = ensure_signed(origin)?;
+			let value = match Self::deployment_policy() {
+				DeploymentPolicy::Deposit(value) => value,
+				_ => return Err(<Error<T>>::DeployerDepositNotRequired.into()),
+			};
+
+			ensure!(
+				!DeployerDeposits::<T>::contains_key(deployer),
+				<Error<T>>::DeployerDepositExists
+			);
+
+			T::EtpCurrency::reserve(&who, value)?;
+			DeployerDeposits::<T>::insert(deployer, (who.clone(), value));
+
+			Self::deposit_event(Event::<T>::DeployerDepositReserved(who, deployer, value));
+		}
+
+		/// Release the deposit the sender reserved for `deployer`.
+		#[weight = 10_000_000]
+		fn release_deployer_deposit(origin, deployer: H160) {
+			let who = ensure_signed(origin)?;
+			let value = match Self::deployer_deposit(deployer) {
+				Some((payer, value)) if payer == who => value,
+				_ => return Err(<Error<T>>::DeployerDepositNotFound.into()),
+			};
+
+			T::EtpCurrency::unreserve(&who, value);
+			DeployerDeposits::<T>::remove(deployer);
+
+			Self::deposit_event(Event::<T>::DeployerDepositReleased(who, deployer, value));
+		}
+
 		/// Issue an EVM call operation. This is similar to a message call transaction in Ethereum.
 		#[weight = T::GasWeightMapping::gas_to_weight(*gas_limit)]
 		fn call(
@@ -508,9 +1051,237 @@ decl_module! {
 }
 
 impl<T: Config> Module<T> {
+	/// Whether the deployment policy permits `deployer` to deploy contracts.
+	pub fn can_deploy(deployer: &H160) -> bool {
+		match Self::deployment_policy() {
+			DeploymentPolicy::Open => true,
+			DeploymentPolicy::AllowList => Self::is_deployer(deployer),
+			DeploymentPolicy::Deposit(required) => {
+				Self::deployer_deposit(deployer).map_or(false, |(_, deposit)| deposit >= required)
+			}
+		}
+	}
+
 	fn remove_account(address: &H160) {
 		AccountCodes::remove(address);
-		AccountStorages::remove_prefix(address);
+		// The storage can be arbitrarily large, it's removed in chunks by
+		// `process_deletion_queue` in the following blocks.
+		DeletionQueue::insert(address, true);
+		// The deposit stays reserved until it's settled.
+		Self::mutate_storage_deposit(address, |info| {
+			info.slots = 0;
+			info.code_len = 0;
+		});
+	}
+
+	/// Mutate the storage usage of a contract, it's a no-op for contracts without a record.
+	pub(crate) fn mutate_storage_deposit(
+		address: &H160,
+		f: impl FnOnce(&mut StorageDeposit<T::AccountId, BalanceOf<T>>),
+	) {
+		StorageDeposits::<T>::mutate(address, |maybe_info| {
+			if let Some(info) = maybe_info {
+				f(info);
+			}
+		});
+	}
+
+	impl_rpc! {
+		fn storage_deposit_rpc(address: H160) -> RuntimeDispatchInfo<BalanceOf<T>> {
+			let info = Self::storage_deposit(address).unwrap_or_default();
+
+			RuntimeDispatchInfo {
+				deposit: info.deposit,
+				slots: info.slots,
+				code_len: info.code_len,
+			}
+		}
+	}
+
+	/// Settle the storage deposit of the slots and the code an execution touched.
+	///
+	/// A new slot or code is paid by `payer`, a cleared one is refunded to whoever paid for it.
+	pub fn settle_storage_deposits(
+		payer: &H160,
+		contracts: &BTreeSet<H160>,
+		slots: &BTreeSet<(H160, H256)>,
+	) -> DispatchResult {
+		let payer = T::AddressMapping::into_account_id(*payer);
+
+		for (contract, index) in slots {
+			Self::settle_slot_deposit(&payer, contract, index)?;
+		}
+		for contract in contracts {
+			Self::settle_code_deposit(&payer, contract)?;
+		}
+
+		Ok(())
+	}
+
+	/// Reserve the deposit of a new slot from the payer or refund the one of a cleared slot.
+	fn settle_slot_deposit(payer: &T::AccountId, contract: &H160, index: &H256) -> DispatchResult {
+		let mut info = match Self::storage_deposit(contract) {
+			Some(info) => info,
+			None => return Ok(()),
+		};
+		let value = T::StorageDepositPerSlot::get();
+
+		match (
+			AccountStorages::contains_key(contract, index),
+			Self::slot_deposit_payer(contract, index),
+		) {
+			(true, None) if !value.is_zero() => {
+				Self::reserve_storage_deposit(payer, contract, value)?;
+
+				info.deposit = info.deposit.saturating_add(value);
+				SlotDepositPayers::<T>::insert(contract, index, (payer.clone(), value));
+			}
+			(false, Some((who, value))) => {
+				Self::refund_storage_deposit(&who, contract, value);
+
+				info.deposit = info.deposit.saturating_sub(value);
+				SlotDepositPayers::<T>::remove(contract, index);
+			}
+			_ => return Ok(()),
+		}
+
+		Self::put_storage_deposit(contract, info);
+
+		Ok(())
+	}
+
+	/// Reserve the deposit of new code from the payer or refund the one of removed code.
+	fn settle_code_deposit(payer: &T::AccountId, contract: &H160) -> DispatchResult {
+		let mut info = match Self::storage_deposit(contract) {
+			Some(info) => info,
+			None => return Ok(()),
+		};
+
+		match (info.code_len, info.code_payer.take()) {
+			(0, Some((who, value))) => {
+				Self::refund_storage_deposit(&who, contract, value);
+
+				info.deposit = info.deposit.saturating_sub(value);
+			}
+			(code_len, None) if code_len > 0 => {
+				let value = T::StorageDepositPerByte::get().saturating_mul(code_len.into());
+
+				if !value.is_zero() {
+					Self::reserve_storage_deposit(payer, contract, value)?;
+
+					info.deposit = info.deposit.saturating_add(value);
+					info.code_payer = Some((payer.clone(), value));
+				}
+			}
+			(_, code_payer) => info.code_payer = code_payer,
+		}
+
+		Self::put_storage_deposit(contract, info);
+
+		Ok(())
+	}
+
+	/// Move the deposit from the payer to the contract account and reserve it there, so that it
+	/// stays with the contract until the storage is freed.
+	fn reserve_storage_deposit(
+		payer: &T::AccountId,
+		contract: &H160,
+		value: BalanceOf<T>,
+	) -> DispatchResult {
+		let contract_id = T::AddressMapping::into_account_id(*contract);
+
+		T::EtpCurrency::transfer(payer, &contract_id, value, ExistenceRequirement::KeepAlive)
+			.map_err(|_| Error::<T>::StorageDepositLow)?;
+		T::EtpCurrency::reserve(&contract_id, value).map_err(|_| Error::<T>::StorageDepositLow)?;
+
+		Self::deposit_event(Event::<T>::StorageDepositReserved(
+			payer.clone(),
+			*contract,
+			value,
+		));
+
+		Ok(())
+	}
+
+	/// Return the deposit reserved on the contract account to who paid it.
+	///
+	/// A payer that no longer exists can't receive it, the contract keeps it free instead.
+	fn refund_storage_deposit(who: &T::AccountId, contract: &H160, value: BalanceOf<T>) {
+		let contract_id = T::AddressMapping::into_account_id(*contract);
+
+		match T::EtpCurrency::repatriate_reserved(&contract_id, who, value, BalanceStatus::Free) {
+			Ok(missing) => Self::deposit_event(Event::<T>::StorageDepositRefunded(
+				who.clone(),
+				*contract,
+				value.saturating_sub(missing),
+			)),
+			Err(_) => {
+				T::EtpCurrency::unreserve(&contract_id, value);
+			}
+		}
+	}
+
+	/// Store the storage deposit record, or remove it once nothing is left to track.
+	fn put_storage_deposit(contract: &H160, info: StorageDeposit<T::AccountId, BalanceOf<T>>) {
+		if info.deposit.is_zero() && info.slots == 0 && info.code_len == 0 {
+			StorageDeposits::<T>::remove(contract);
+		} else {
+			StorageDeposits::<T>::insert(contract, info);
+		}
+	}
+
+	/// Remove the storage of self-destructed contracts, until the weight limit is reached. Returns
+	/// the consumed weight.
+	///
+	/// The queue is a map, so contracts are drained in the order of their hashed keys, not in the
+	/// order they were queued in.
+	pub fn process_deletion_queue(weight_limit: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// The read that finds the queue exhausted.
+		let mut weight = db_weight.reads(1);
+		// The slot and its deposit payer, the refund reads and writes two accounts.
+		let slot_weight = db_weight.reads_writes(4, 4).max(1);
+
+		for (address, _) in DeletionQueue::iter() {
+			// One more read to find out that the prefix is exhausted, and the deposit record.
+			weight = weight.saturating_add(db_weight.reads_writes(2, 1));
+
+			let limit = weight_limit.saturating_sub(weight) / slot_weight;
+
+			if limit == 0 {
+				break;
+			}
+
+			let mut refunded = BalanceOf::<T>::zero();
+			let removed = AccountStorages::drain_prefix(address)
+				.take(limit as usize)
+				.map(|(index, _)| {
+					if let Some((who, value)) = SlotDepositPayers::<T>::take(address, index) {
+						Self::refund_storage_deposit(&who, &address, value);
+						refunded = refunded.saturating_add(value);
+					}
+				})
+				.count() as Weight;
+
+			if !refunded.is_zero() {
+				if let Some(mut info) = Self::storage_deposit(address) {
+					info.deposit = info.deposit.saturating_sub(refunded);
+					Self::put_storage_deposit(&address, info);
+				}
+			}
+
+			weight = weight.saturating_add(slot_weight.saturating_mul(removed));
+
+			if removed < limit {
+				DeletionQueue::remove(address);
+
+				weight = weight.saturating_add(db_weight.writes(1));
+			} else {
+				break;
+			}
+		}
+
+		weight
 	}
 
 	/// Check whether an account is empty.
@@ -555,4 +1326,135 @@ impl<T: Config> Module<T> {
 			},
 		);
 	}
+
+	/// Choose the currency the fees of `address` are paid in.
+	pub fn set_fee_currency_of(address: &H160, currency: FeeCurrency) {
+		if currency == FeeCurrency::default() {
+			FeeCurrencies::remove(address);
+		} else {
+			FeeCurrencies::insert(address, currency);
+		}
+
+		Self::deposit_event(Event::<T>::FeeCurrencySet(*address, currency));
+	}
+
+	/// The currency the fees of `address` are actually paid in, DNA only if the account opted in
+	/// and the `DnaFeeRate` is set.
+	pub fn fee_currency(address: &H160) -> FeeCurrency {
+		match Self::fee_currency_of(address) {
+			FeeCurrency::Dna if Self::dna_fee_rate().is_some() => FeeCurrency::Dna,
+			_ => FeeCurrency::Etp,
+		}
+	}
+
+	/// The balance `address` pays its fees from, in EVM balance units.
+	pub fn fee_balance(address: &H160) -> U256 {
+		match Self::fee_currency(address) {
+			FeeCurrency::Etp => T::AccountBasicMapping::account_basic(address).balance,
+			FeeCurrency::Dna => Self::dna_to_evm(T::DnaCurrency::free_balance(
+				&T::AddressMapping::into_account_id(*address),
+			)),
+		}
+	}
+
+	/// Whether `address` can pay `value` and `fee`, the value is always paid from the EVM
+	/// balance.
+	pub fn can_pay(address: &H160, value: U256, fee: U256, currency: FeeCurrency) -> bool {
+		let balance = T::AccountBasicMapping::account_basic(address).balance;
+
+		match currency {
+			FeeCurrency::Etp => value
+				.checked_add(fee)
+				.map_or(false, |payment| balance >= payment),
+			FeeCurrency::Dna => balance >= value && Self::fee_balance(address) >= fee,
+		}
+	}
+
+	/// Withdraw fee in the given currency, rounded up to whole units of it.
+	///
+	/// The fee is withdrawn like any other fee, it fails if the account can't pay it or would be
+	/// reaped by paying it.
+	pub fn withdraw_fee_in(
+		address: &H160,
+		value: U256,
+		currency: FeeCurrency,
+	) -> Result<FeeImbalance<T>, Error<T>> {
+		let account_id = T::AddressMapping::into_account_id(*address);
+		let withdrawn = match currency {
+			FeeCurrency::Etp => T::EtpCurrency::withdraw(
+				&account_id,
+				Self::evm_to_etp(value, true),
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map(FeeImbalance::Etp),
+			FeeCurrency::Dna => T::DnaCurrency::withdraw(
+				&account_id,
+				Self::evm_to_dna(value, true),
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map(FeeImbalance::Dna),
+		};
+
+		withdrawn.map_err(|_| Error::<T>::WithdrawFailed)
+	}
+
+	/// Convert EVM balance units to ETP.
+	///
+	/// Rounded up when charging, so that a fee is never paid with less than its value.
+	pub fn evm_to_etp(value: U256, round_up: bool) -> BalanceOf<T> {
+		let (mut etp, remainder) = value.div_mod(T::UnitsPerBalance::get().max(U256::one()));
+
+		if round_up && !remainder.is_zero() {
+			etp = etp.saturating_add(U256::one());
+		}
+
+		etp.min(U256::from(u128::max_value()))
+			.low_u128()
+			.unique_saturated_into()
+	}
+
+	/// Convert ETP to EVM balance units.
+	pub fn etp_to_evm(value: BalanceOf<T>) -> U256 {
+		U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value))
+			.saturating_mul(T::UnitsPerBalance::get().max(U256::one()))
+	}
+
+	/// Convert EVM balance units to DNA at the `DnaFeeRate`, zero if it's not set.
+	///
+	/// Rounded up when charging, so that a fee is never paid with less than its value.
+	pub fn evm_to_dna(value: U256, round_up: bool) -> DnaBalanceOf<T> {
+		let rate = match Self::dna_fee_rate() {
+			Some(rate) => rate,
+			None => return Zero::zero(),
+		};
+		let divisor = T::UnitsPerBalance::get()
+			.max(U256::one())
+			.saturating_mul(FixedU128::accuracy().into());
+		let (mut dna, remainder) = value
+			.saturating_mul(rate.into_inner().into())
+			.div_mod(divisor);
+
+		if round_up && !remainder.is_zero() {
+			dna = dna.saturating_add(U256::one());
+		}
+
+		dna.min(U256::from(u128::max_value()))
+			.low_u128()
+			.unique_saturated_into()
+	}
+
+	/// Convert DNA to EVM balance units at the `DnaFeeRate`, zero if it's not set.
+	pub fn dna_to_evm(value: DnaBalanceOf<T>) -> U256 {
+		let rate = match Self::dna_fee_rate() {
+			Some(rate) => rate,
+			None => return U256::zero(),
+		};
+
+		U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value))
+			.saturating_mul(T::UnitsPerBalance::get().max(U256::one()))
+			.saturating_mul(FixedU128::accuracy().into())
+			/ U256::from(rate.into_inner())
+	}
 }
@@ -20,7 +20,7 @@
 use crate::runner::Runner as RunnerT;
 use crate::{
 	AccountBasicMapping, AccountCodes, AccountStorages, AddressMapping, Config, Error, Event,
-	FeeCalculator, Module, PrecompileSet,
+	FeeCalculator, Module, OnChargeEVMTransaction, PrecompileSet, StorageDeposits,
 };
 use hyperspace_evm_primitives::{Account, CallInfo, CreateInfo, ExecutionInfo, Log, Vicinity};
 use evm::backend::Backend as BackendT;
@@ -28,13 +28,18 @@ use evm::executor::{StackExecutor, StackState as StackStateT, StackSubstateMetad
 use evm::{ExitError, ExitReason, Transfer};
 use frame_support::{
 	debug, ensure,
-	storage::{StorageDoubleMap, StorageMap},
-	traits::Get,
+	storage::{StorageDoubleMap, StorageMap, StorageValue},
+	traits::{Get, Randomness},
 };
 use sha3::{Digest, Keccak256};
 use sp_core::{H160, H256, U256};
 use sp_runtime::traits::UniqueSaturatedInto;
-use sp_std::{boxed::Box, collections::btree_set::BTreeSet, marker::PhantomData, mem, vec::Vec};
+use sp_std::{
+	boxed::Box, cmp, collections::btree_set::BTreeSet, marker::PhantomData, mem, vec::Vec,
+};
+
+/// The refund of the first `SELFDESTRUCT` of an account before EIP-3529, `R_selfdestruct`.
+const SELFDESTRUCT_REFUND: u64 = 24000;
 
 #[derive(Default)]
 pub struct Runner<T: Config> {
@@ -82,25 +87,37 @@ impl<T: Config> Runner<T> {
 		let total_fee = gas_price
 			.checked_mul(U256::from(gas_limit))
 			.ok_or(Error::<T>::FeeOverflow)?;
-		let total_payment = value
-			.checked_add(total_fee)
-			.ok_or(Error::<T>::PaymentOverflow)?;
+		ensure!(
+			value.checked_add(total_fee).is_some(),
+			Error::<T>::PaymentOverflow
+		);
 		let source_account = T::AccountBasicMapping::account_basic(&source);
+		// Fixed for the whole execution, so that the refund goes back to the same currency.
+		let fee_currency = Module::<T>::fee_currency(&source);
 		ensure!(
-			source_account.balance >= total_payment,
+			Module::<T>::can_pay(&source, value, total_fee, fee_currency),
 			Error::<T>::BalanceLow
 		);
 
-		Module::<T>::withdraw_fee(&source, total_fee);
-
 		if let Some(nonce) = nonce {
 			ensure!(source_account.nonce == nonce, Error::<T>::InvalidNonce);
 		}
 
+		let withdrawn_fee = T::OnChargeTransaction::withdraw_fee(&source, total_fee, fee_currency)?;
+
+		sp_io::storage::start_transaction();
+
 		let (reason, retv) = f(&mut executor);
+		let used_gas = Self::used_gas(&executor, gas_limit);
 
-		let used_gas = U256::from(executor.used_gas());
-		let actual_fee = executor.fee(gas_price);
+		let mut state = executor.into_state();
+		// A top level create of rejected code is an exceptional halt, all the gas is consumed.
+		let used_gas = if state.creation_rejected {
+			U256::from(gas_limit)
+		} else {
+			U256::from(used_gas)
+		};
+		let actual_fee = used_gas.saturating_mul(gas_price);
 		debug::debug!(
 			target: "evm",
 			"Execution {:?} [source: {:?}, value: {}, gas_limit: {}, actual_fee: {}]",
@@ -110,19 +127,58 @@ impl<T: Config> Runner<T> {
 			gas_limit,
 			actual_fee
 		);
-		Module::<T>::deposit_fee(&source, total_fee.saturating_sub(actual_fee));
-
-		let state = executor.into_state();
 
-		for address in state.substate.deletes {
+		// Rejected code is dropped, the account is removed like a self-destructed one.
+		for address in state
+			.substate
+			.deletes
+			.iter()
+			.chain(&state.substate.rejected)
+		{
 			debug::debug!(
 				target: "evm",
 				"Deleting account at {:?}",
 				address
 			);
-			Module::<T>::remove_account(&address)
+			Module::<T>::remove_account(address);
+			state.deposit_touched.insert(*address);
 		}
 
+		// The storage deposit is settled together with the state changes, if the source can not
+		// afford it the execution fails as if it ran out of funds.
+		let reason = if state.creation_rejected {
+			Self::rollback(&source, &mut state);
+
+			ExitReason::Error(ExitError::Other(
+				"Code starting with 0xEF is rejected (EIP-3541)".into(),
+			))
+		} else {
+			match Module::<T>::settle_storage_deposits(
+				&source,
+				&state.deposit_touched,
+				&state.slots_touched,
+			) {
+				Ok(()) => {
+					sp_io::storage::commit_transaction();
+
+					reason
+				}
+				Err(e) => {
+					debug::debug!(
+						target: "evm",
+						"Storage deposit failed {:?} [source: {:?}]",
+						e,
+						source
+					);
+					Self::rollback(&source, &mut state);
+
+					ExitReason::Error(ExitError::OutOfFund)
+				}
+			}
+		};
+
+		T::OnChargeTransaction::correct_and_deposit_fee(&source, actual_fee, withdrawn_fee);
+
 		for log in &state.substate.logs {
 			debug::trace!(
 				target: "evm",
@@ -147,6 +203,51 @@ impl<T: Config> Runner<T> {
 			logs: state.substate.logs,
 		})
 	}
+
+	/// The gas used by the execution, after the refund of the active hardfork.
+	///
+	/// evm 0.22 caps the refund at half of the gas used, below that cap the refund it applied is
+	/// the whole refund, so a lower cap can be applied on top of it (EIP-3529). The `SELFDESTRUCT`
+	/// refund is granted once per account and its grant is reverted together with the account
+	/// deletion, so the committed deletions are the refunded ones. If evm 0.22 capped the refund,
+	/// what is left after removing them is a lower bound, which can only overcharge.
+	fn used_gas(
+		executor: &StackExecutor<'_, SubstrateStackState<'_, '_, T>>,
+		gas_limit: u64,
+	) -> u64 {
+		let total_used_gas = gas_limit.saturating_sub(executor.gas());
+		let mut refunded_gas = total_used_gas.saturating_sub(executor.used_gas());
+
+		if !T::hardfork().refunds_selfdestruct() {
+			let selfdestructs = executor.state().substate.deletes.len() as u64;
+
+			refunded_gas = refunded_gas.saturating_sub(SELFDESTRUCT_REFUND * selfdestructs);
+		}
+
+		total_used_gas
+			- cmp::min(
+				total_used_gas / T::hardfork().refund_quotient(),
+				refunded_gas,
+			)
+	}
+
+	/// Roll back the state changes of the execution, the fee is still charged.
+	fn rollback(source: &H160, state: &mut SubstrateStackState<'_, '_, T>) {
+		sp_io::storage::rollback_transaction();
+		// The nonce increase was rolled back too, keep it or the transaction can be replayed.
+		frame_system::Module::<T>::inc_account_nonce(&T::AddressMapping::into_account_id(*source));
+		state.substate.logs.clear();
+	}
+
+	/// Fail a successful top level create if its code was rejected (EIP-3541).
+	fn reject_ef_code(
+		executor: &mut StackExecutor<'_, SubstrateStackState<'_, '_, T>>,
+		address: &H160,
+	) {
+		let state = executor.state_mut();
+
+		state.creation_rejected = state.substate.rejected.contains(address);
+	}
 }
 
 impl<T: Config> RunnerT<T> for Runner<T> {
@@ -182,6 +283,11 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 		nonce: Option<U256>,
 		config: &evm::Config,
 	) -> Result<CreateInfo, Self::Error> {
+		ensure!(
+			Module::<T>::can_deploy(&source),
+			Error::<T>::DeploymentNotPermitted
+		);
+
 		Self::execute(
 			source,
 			value,
@@ -191,10 +297,10 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 			config,
 			|executor| {
 				let address = executor.create_address(evm::CreateScheme::Legacy { caller: source });
-				(
-					executor.transact_create(source, value, init, gas_limit),
-					address,
-				)
+				let reason = executor.transact_create(source, value, init, gas_limit);
+				Self::reject_ef_code(executor, &address);
+
+				(reason, address)
 			},
 		)
 	}
@@ -209,6 +315,11 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 		nonce: Option<U256>,
 		config: &evm::Config,
 	) -> Result<CreateInfo, Self::Error> {
+		ensure!(
+			Module::<T>::can_deploy(&source),
+			Error::<T>::DeploymentNotPermitted
+		);
+
 		let code_hash = H256::from_slice(Keccak256::digest(&init).as_slice());
 		Self::execute(
 			source,
@@ -223,10 +334,10 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 					code_hash,
 					salt,
 				});
-				(
-					executor.transact_create2(source, value, init, salt, gas_limit),
-					address,
-				)
+				let reason = executor.transact_create2(source, value, init, salt, gas_limit);
+				Self::reject_ef_code(executor, &address);
+
+				(reason, address)
 			},
 		)
 	}
@@ -235,6 +346,7 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 struct SubstrateStackSubstate<'config> {
 	metadata: StackSubstateMetadata<'config>,
 	deletes: BTreeSet<H160>,
+	rejected: BTreeSet<H160>,
 	logs: Vec<Log>,
 	parent: Option<Box<SubstrateStackSubstate<'config>>>,
 }
@@ -253,6 +365,7 @@ impl<'config> SubstrateStackSubstate<'config> {
 			metadata: self.metadata.spit_child(gas_limit, is_static),
 			parent: None,
 			deletes: BTreeSet::new(),
+			rejected: BTreeSet::new(),
 			logs: Vec::new(),
 		};
 		mem::swap(&mut entering, self);
@@ -269,6 +382,7 @@ impl<'config> SubstrateStackSubstate<'config> {
 		self.metadata.swallow_commit(exited.metadata)?;
 		self.logs.append(&mut exited.logs);
 		self.deletes.append(&mut exited.deletes);
+		self.rejected.append(&mut exited.rejected);
 
 		sp_io::storage::commit_transaction();
 		Ok(())
@@ -310,6 +424,10 @@ impl<'config> SubstrateStackSubstate<'config> {
 		self.deletes.insert(address);
 	}
 
+	pub fn set_rejected(&mut self, address: H160) {
+		self.rejected.insert(address);
+	}
+
 	pub fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
 		self.logs.push(Log {
 			address,
@@ -323,6 +441,10 @@ impl<'config> SubstrateStackSubstate<'config> {
 pub struct SubstrateStackState<'vicinity, 'config, T> {
 	vicinity: &'vicinity Vicinity,
 	substate: SubstrateStackSubstate<'config>,
+	creation_rejected: bool,
+	deposit_touched: BTreeSet<H160>,
+	slots_touched: BTreeSet<(H160, H256)>,
+	creating: Option<H160>,
 	_marker: PhantomData<T>,
 }
 
@@ -334,9 +456,14 @@ impl<'vicinity, 'config, T: Config> SubstrateStackState<'vicinity, 'config, T> {
 			substate: SubstrateStackSubstate {
 				metadata,
 				deletes: BTreeSet::new(),
+				rejected: BTreeSet::new(),
 				logs: Vec::new(),
 				parent: None,
 			},
+			creation_rejected: false,
+			deposit_touched: BTreeSet::new(),
+			slots_touched: BTreeSet::new(),
+			creating: None,
 			_marker: PhantomData,
 		}
 	}
@@ -374,7 +501,11 @@ impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity,
 	}
 
 	fn block_difficulty(&self) -> U256 {
-		U256::zero()
+		if T::RandomnessAsDifficulty::get() {
+			U256::from_big_endian(T::Randomness::random_seed().as_ref())
+		} else {
+			U256::zero()
+		}
 	}
 
 	fn block_gas_limit(&self) -> U256 {
@@ -452,6 +583,8 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 	}
 
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) {
+		let existed = AccountStorages::contains_key(address, index);
+
 		if value == H256::default() {
 			debug::debug!(
 				target: "evm",
@@ -460,6 +593,12 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 				index,
 			);
 			AccountStorages::remove(address, index);
+
+			if existed {
+				Module::<T>::mutate_storage_deposit(&address, |info| {
+					info.slots = info.slots.saturating_sub(1)
+				});
+			}
 		} else {
 			debug::debug!(
 				target: "evm",
@@ -469,11 +608,29 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 				value,
 			);
 			AccountStorages::insert(address, index, value);
+
+			if !existed {
+				Module::<T>::mutate_storage_deposit(&address, |info| {
+					info.slots = info.slots.saturating_add(1)
+				});
+			}
 		}
+
+		self.slots_touched.insert((address, index));
 	}
 
 	fn reset_storage(&mut self, address: H160) {
-		AccountStorages::remove_prefix(address);
+		// Only called on contract creation, start tracking the storage deposit from here.
+		StorageDeposits::<T>::mutate(address, |info| {
+			let info = info.get_or_insert_with(Default::default);
+
+			info.slots = 0;
+			info.code_len = 0;
+		});
+		self.deposit_touched.insert(address);
+		// The value transfer to the new contract follows, where the deployer is known and the
+		// creation can still fail.
+		self.creating = Some(address);
 	}
 
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
@@ -485,16 +642,52 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 	}
 
 	fn set_code(&mut self, address: H160, code: Vec<u8>) {
+		if code.first() == Some(&0xEF) && T::hardfork().rejects_ef_code() {
+			// evm 0.22 calls this after committing the create, so a nested `CREATE` still returns
+			// the address. The code is dropped and the account removed at the end of the
+			// execution unless the substate reverts, a top level create is failed by the runner.
+			debug::debug!(
+				target: "evm",
+				"Rejecting code starting with 0xEF at {:?}",
+				address
+			);
+			self.substate.set_rejected(address);
+
+			return;
+		}
+
 		debug::debug!(
 			target: "evm",
 			"Inserting code ({} bytes) at {:?}",
 			code.len(),
 			address
 		);
+		StorageDeposits::<T>::mutate(address, |info| {
+			info.get_or_insert_with(Default::default).code_len = code.len() as u32
+		});
+		self.deposit_touched.insert(address);
 		AccountCodes::insert(address, code);
 	}
 
 	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
+		// Failing the transfer of a `CREATE`/`CREATE2` fails the creation. evm 0.22 always calls
+		// it right after `reset_storage`, even for a zero value, it's the last hook a creation can
+		// still be failed from.
+		if self.creating.take() == Some(transfer.target) {
+			ensure!(
+				Module::<T>::can_deploy(&transfer.source),
+				ExitError::Other("Deployment not permitted".into())
+			);
+			// The storage of a self-destructed contract is removed lazily, its address can only
+			// be reused once the storage is gone.
+			ensure!(
+				AccountStorages::iter_prefix_values(transfer.target)
+					.next()
+					.is_none(),
+				ExitError::CreateCollision
+			);
+		}
+
 		//EVM double transfer issue
 		let source_account = T::AccountBasicMapping::account_basic(&transfer.source);
 		
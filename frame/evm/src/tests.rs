@@ -2,14 +2,18 @@
 
 use super::*;
 
-use frame_support::{assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types};
+use frame_support::{
+	assert_noop, assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types,
+	traits::OnInitialize, weights::RuntimeDbWeight,
+};
+use sha3::Digest;
 use sp_core::{Blake2Hasher, H256};
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
-	RuntimeDebug,
+	ConsensusEngineId, RuntimeDebug,
 };
-use std::{collections::BTreeMap, str::FromStr};
+use std::{cell::RefCell, collections::BTreeMap, str::FromStr};
 
 type Balance = u64;
 
@@ -28,11 +32,19 @@ hyperspace_support::impl_test_account_data! { deprecated }
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Test;
 
+parameter_types! {
+	pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight {
+		read: 1,
+		write: 1,
+	};
+	// The queue read and write, the exhausted check, the deposit record and two slots.
+	pub const DeletionWeightLimit: Weight = 2 + 3 + 2 * 8;
+}
 impl frame_system::Config for Test {
 	type BaseCallFilter = ();
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type Origin = Origin;
 	type Index = u64;
 	type BlockNumber = u64;
@@ -98,6 +110,75 @@ impl FeeCalculator for FixedGasPrice {
 	}
 }
 
+thread_local! {
+	static HARDFORK: RefCell<EvmHardfork> = RefCell::new(EvmHardfork::Istanbul);
+	static CONTRACT_SIZE_LIMIT: RefCell<bool> = RefCell::new(true);
+	static STORAGE_DEPOSIT_PER_SLOT: RefCell<Balance> = RefCell::new(0);
+	static STORAGE_DEPOSIT_PER_BYTE: RefCell<Balance> = RefCell::new(0);
+}
+
+pub struct MockHardforkSchedule;
+impl HardforkSchedule<u64> for MockHardforkSchedule {
+	fn hardfork_at(_: u64) -> EvmHardfork {
+		HARDFORK.with(|v| *v.borrow())
+	}
+
+	fn contract_size_limit() -> bool {
+		CONTRACT_SIZE_LIMIT.with(|v| *v.borrow())
+	}
+}
+
+fn set_hardfork(hardfork: EvmHardfork, contract_size_limit: bool) {
+	HARDFORK.with(|v| *v.borrow_mut() = hardfork);
+	CONTRACT_SIZE_LIMIT.with(|v| *v.borrow_mut() = contract_size_limit);
+}
+
+pub struct StorageDepositPerSlot;
+impl Get<Balance> for StorageDepositPerSlot {
+	fn get() -> Balance {
+		STORAGE_DEPOSIT_PER_SLOT.with(|v| *v.borrow())
+	}
+}
+
+pub struct StorageDepositPerByte;
+impl Get<Balance> for StorageDepositPerByte {
+	fn get() -> Balance {
+		STORAGE_DEPOSIT_PER_BYTE.with(|v| *v.borrow())
+	}
+}
+
+pub struct MockFindAuthor;
+impl FindAuthor<H160> for MockFindAuthor {
+	fn find_author<'a, I>(_: I) -> Option<H160>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		Some(H160::from_str("1000000000000000000000000000000000000005").unwrap())
+	}
+}
+
+parameter_types! {
+	pub TreasuryAccount: AccountId32 = AccountId32::from([9; 32]);
+	pub UnitsPerBalance: U256 = U256::one();
+}
+
+pub struct MockTreasury;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for MockTreasury {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		Etp::resolve_creating(&TreasuryAccount::get(), amount);
+	}
+}
+impl OnUnbalancedDna<DnaNegativeImbalanceOf<Test>> for MockTreasury {
+	fn on_nonzero_unbalanced(amount: DnaNegativeImbalanceOf<Test>) {
+		Dna::resolve_creating(&TreasuryAccount::get(), amount);
+	}
+}
+
+fn set_storage_deposit(per_slot: Balance, per_byte: Balance) {
+	STORAGE_DEPOSIT_PER_SLOT.with(|v| *v.borrow_mut() = per_slot);
+	STORAGE_DEPOSIT_PER_BYTE.with(|v| *v.borrow_mut() = per_byte);
+}
+
 impl Config for Test {
 	type FeeCalculator = FixedGasPrice;
 	type GasWeightMapping = ();
@@ -107,12 +188,21 @@ impl Config for Test {
 	type AddressMapping = HashedAddressMapping<Blake2Hasher>;
 	type EtpCurrency = Etp;
 	type DnaCurrency = Dna;
+	type UnitsPerBalance = UnitsPerBalance;
 
 	type Event = Event<Test>;
 	type Precompiles = ();
 	type ChainId = ();
 	type Runner = crate::runner::stack::Runner<Self>;
 	type AccountBasicMapping = RawAccountBasicMapping<Test>;
+	type Randomness = ();
+	type RandomnessAsDifficulty = ();
+	type HardforkSchedule = MockHardforkSchedule;
+	type StorageDepositPerSlot = StorageDepositPerSlot;
+	type StorageDepositPerByte = StorageDepositPerByte;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type OnChargeTransaction = EVMFeeSplit<MockFindAuthor, MockTreasury>;
+	type DeploymentOrigin = frame_system::EnsureRoot<Self::AccountId>;
 }
 
 type System = frame_system::Module<Test>;
@@ -147,6 +237,38 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		},
 	);
 
+	accounts.insert(
+		H160::from_str("1000000000000000000000000000000000000003").unwrap(),
+		GenesisAccount {
+			nonce: U256::from(1),
+			balance: U256::from(1000000),
+			storage: Default::default(),
+			code: vec![
+				0x60, 0x00, // PUSH1 0
+				0x54, // SLOAD
+				0x50, // POP
+				0x00, // STOP
+			],
+		},
+	);
+
+	let mut storage = BTreeMap::new();
+	for index in 0..3u8 {
+		storage.insert(H256::repeat_byte(index), H256::repeat_byte(1));
+	}
+	accounts.insert(
+		H160::from_str("1000000000000000000000000000000000000004").unwrap(),
+		GenesisAccount {
+			nonce: U256::from(1),
+			balance: U256::from(1000000),
+			storage,
+			code: vec![
+				0x60, 0x00, // PUSH1 0
+				0xff, // SELFDESTRUCT
+			],
+		},
+	);
+
 	EtpConfig::default().assimilate_storage(&mut t).unwrap();
 	GenesisConfig { accounts }
 		.assimilate_storage::<Test>(&mut t)
@@ -203,3 +325,880 @@ fn mutate_account_works() {
 		);
 	});
 }
+
+#[test]
+fn same_contract_under_each_hardfork() {
+	new_test_ext().execute_with(|| {
+		let used_gas = |hardfork| {
+			set_hardfork(hardfork, true);
+
+			<Test as Config>::Runner::call(
+				H160::default(),
+				H160::from_str("1000000000000000000000000000000000000003").unwrap(),
+				Vec::new(),
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+			.used_gas
+		};
+
+		// 21000 + PUSH1 + SLOAD + POP
+		assert_eq!(
+			used_gas(EvmHardfork::Istanbul),
+			U256::from(21000 + 3 + 800 + 2)
+		);
+		assert_eq!(
+			used_gas(EvmHardfork::Eip3529),
+			U256::from(21000 + 3 + 800 + 2)
+		);
+	});
+}
+
+#[test]
+fn ef_code_rejected_since_eip3529() {
+	new_test_ext().execute_with(|| {
+		let create = |hardfork| {
+			set_hardfork(hardfork, true);
+
+			<Test as Config>::Runner::create(
+				H160::default(),
+				vec![
+					0x60, 0xef, // PUSH1 0xEF
+					0x60, 0x00, // PUSH1 0
+					0x53, // MSTORE8
+					0x60, 0x01, // PUSH1 1
+					0x60, 0x00, // PUSH1 0
+					0xf3, // RETURN
+				],
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		let info = create(EvmHardfork::Istanbul);
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(EVM::account_codes(info.value), vec![0xef]);
+
+		let info = create(EvmHardfork::Eip3529);
+
+		assert!(!info.exit_reason.is_succeed());
+		assert_eq!(info.used_gas, U256::from(1000000));
+		assert!(EVM::account_codes(info.value).is_empty());
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&info.value).nonce,
+			U256::zero()
+		);
+	});
+}
+
+#[test]
+fn ef_code_rejected_in_nested_create() {
+	new_test_ext().execute_with(|| {
+		set_hardfork(EvmHardfork::Eip3529, true);
+
+		let info = <Test as Config>::Runner::create(
+			H160::default(),
+			vec![
+				// the init code of `ef_code_rejected_since_eip3529`
+				0x69, 0x60, 0xef, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3, // PUSH10
+				0x60, 0x00, // PUSH1 0
+				0x52, // MSTORE
+				0x60, 0x0a, // PUSH1 10
+				0x60, 0x16, // PUSH1 22
+				0x60, 0x00, // PUSH1 0
+				0xf0, // CREATE
+				0x60, 0x00, // PUSH1 0
+				0x55, // SSTORE
+				0x00, // STOP
+			],
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		assert!(info.exit_reason.is_succeed());
+
+		let created = H160::from(AccountStorages::get(info.value, H256::zero()));
+
+		assert!(EVM::account_codes(created).is_empty());
+	});
+}
+
+#[test]
+fn selfdestruct_refund_removed_since_eip3529() {
+	let used_gas = |hardfork| {
+		new_test_ext().execute_with(|| {
+			set_hardfork(hardfork, true);
+
+			<Test as Config>::Runner::call(
+				H160::default(),
+				H160::from_str("1000000000000000000000000000000000000004").unwrap(),
+				Vec::new(),
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+			.used_gas
+		})
+	};
+
+	// 21000 + PUSH1 + SELFDESTRUCT, the refund is capped at half of it before EIP-3529.
+	let cost = 21000 + 3 + 5000;
+
+	assert_eq!(used_gas(EvmHardfork::Istanbul), U256::from(cost - cost / 2));
+	assert_eq!(used_gas(EvmHardfork::Eip3529), U256::from(cost));
+}
+
+#[test]
+fn refund_capped_at_a_fifth_since_eip3529() {
+	let used_gas = |hardfork| {
+		new_test_ext().execute_with(|| {
+			set_hardfork(hardfork, true);
+
+			<Test as Config>::Runner::create(
+				H160::default(),
+				vec![
+					0x60, 0x01, // PUSH1 1
+					0x60, 0x00, // PUSH1 0
+					0x55, // SSTORE
+					0x60, 0x00, // PUSH1 0
+					0x60, 0x00, // PUSH1 0
+					0x55, // SSTORE
+				],
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+			.used_gas
+			.as_u64()
+		})
+	};
+
+	// Restoring the slot refunds `gas_sstore_set - gas_sload`, below the cap before EIP-3529.
+	let istanbul = used_gas(EvmHardfork::Istanbul);
+	let total_used_gas = istanbul + 20000 - 800;
+
+	assert_eq!(
+		used_gas(EvmHardfork::Eip3529),
+		total_used_gas - total_used_gas / 5
+	);
+}
+
+#[test]
+fn contract_size_limit_can_be_lifted() {
+	new_test_ext().execute_with(|| {
+		let create = |contract_size_limit| {
+			set_hardfork(EvmHardfork::Eip3529, contract_size_limit);
+
+			<Test as Config>::Runner::create(
+				H160::default(),
+				vec![
+					0x61, 0x60, 0x01, // PUSH2 0x6001
+					0x60, 0x00, // PUSH1 0
+					0xf3, // RETURN
+				],
+				U256::default(),
+				10000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		let info = create(true);
+
+		assert_eq!(
+			info.exit_reason,
+			ExitReason::Error(ExitError::CreateContractLimit)
+		);
+		assert!(EVM::account_codes(info.value).is_empty());
+
+		let info = create(false);
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(EVM::account_codes(info.value).len(), 0x6001);
+	});
+}
+
+#[test]
+fn storage_deposit_follows_contract_storage() {
+	new_test_ext().execute_with(|| {
+		set_storage_deposit(100, 1);
+
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let info = <Test as Config>::Runner::create(
+			source,
+			vec![
+				0x60, 0x01, // PUSH1 1
+				0x60, 0x00, // PUSH1 0
+				0x55, // SSTORE
+				0x60, 0x06, // PUSH1 6
+				0x60, 0x11, // PUSH1 0x11
+				0x60, 0x00, // PUSH1 0
+				0x39, // CODECOPY
+				0x60, 0x06, // PUSH1 6
+				0x60, 0x00, // PUSH1 0
+				0xf3, // RETURN
+				// runtime code, clears slot 0
+				0x60, 0x00, // PUSH1 0
+				0x60, 0x00, // PUSH1 0
+				0x55, // SSTORE
+				0x00, // STOP
+			],
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		let contract = info.value;
+		let contract_id = <Test as Config>::AddressMapping::into_account_id(contract);
+		let source_id = <Test as Config>::AddressMapping::into_account_id(source);
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(
+			EVM::slot_deposit_payer(contract, H256::zero()),
+			Some((source_id.clone(), 100))
+		);
+		assert_eq!(
+			EVM::storage_deposit(contract),
+			Some(StorageDeposit {
+				slots: 1,
+				code_len: 6,
+				deposit: 106,
+				code_payer: Some((source_id.clone(), 6)),
+			})
+		);
+		assert_eq!(Etp::reserved_balance(&contract_id), 106);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000 - 106)
+		);
+
+		// The slot is cleared by someone else, the deposit still goes back to who paid it.
+		let other = H160::from_str("1000000000000000000000000000000000000002").unwrap();
+		let info = <Test as Config>::Runner::call(
+			other,
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(
+			EVM::storage_deposit(contract),
+			Some(StorageDeposit {
+				slots: 0,
+				code_len: 6,
+				deposit: 6,
+				code_payer: Some((source_id, 6)),
+			})
+		);
+		assert_eq!(EVM::slot_deposit_payer(contract, H256::zero()), None);
+		assert_eq!(Etp::reserved_balance(&contract_id), 6);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000 - 6)
+		);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&other).balance,
+			U256::from(1000000)
+		);
+	});
+}
+
+#[test]
+fn storage_deposit_of_self_destructed_contract_refunded_to_payers() {
+	new_test_ext().execute_with(|| {
+		set_storage_deposit(100, 1);
+
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let info = <Test as Config>::Runner::create(
+			source,
+			vec![
+				0x60, 0x01, // PUSH1 1
+				0x60, 0x00, // PUSH1 0
+				0x55, // SSTORE
+				0x60, 0x03, // PUSH1 3
+				0x60, 0x11, // PUSH1 0x11
+				0x60, 0x00, // PUSH1 0
+				0x39, // CODECOPY
+				0x60, 0x03, // PUSH1 3
+				0x60, 0x00, // PUSH1 0
+				0xf3, // RETURN
+				// runtime code
+				0x60, 0x00, // PUSH1 0
+				0xff, // SELFDESTRUCT
+			],
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		let contract = info.value;
+		let contract_id = <Test as Config>::AddressMapping::into_account_id(contract);
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(Etp::reserved_balance(&contract_id), 103);
+
+		let info = <Test as Config>::Runner::call(
+			H160::from_str("1000000000000000000000000000000000000002").unwrap(),
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		// The code deposit is refunded right away, the slot one once the slot is removed.
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(Etp::reserved_balance(&contract_id), 100);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000 - 100)
+		);
+
+		EVM::on_initialize(1);
+
+		assert_eq!(Etp::reserved_balance(&contract_id), 0);
+		assert!(EVM::storage_deposit(contract).is_none());
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000)
+		);
+	});
+}
+
+#[test]
+fn storage_deposit_too_low_fails_execution() {
+	new_test_ext().execute_with(|| {
+		set_storage_deposit(0, 1000000);
+
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let info = <Test as Config>::Runner::create(
+			source,
+			vec![
+				0x60, 0x01, // PUSH1 1
+				0x60, 0x00, // PUSH1 0
+				0xf3, // RETURN
+			],
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		assert_eq!(info.exit_reason, ExitReason::Error(ExitError::OutOfFund));
+		assert!(EVM::account_codes(info.value).is_empty());
+		assert!(EVM::storage_deposit(info.value).is_none());
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source),
+			Account {
+				nonce: U256::from(2),
+				balance: U256::from(1000000),
+			}
+		);
+	});
+}
+
+#[test]
+fn self_destructed_storage_removed_lazily() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::from_str("1000000000000000000000000000000000000004").unwrap();
+		let info = <Test as Config>::Runner::call(
+			H160::default(),
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		assert!(info.exit_reason.is_succeed());
+		assert!(EVM::account_codes(contract).is_empty());
+		assert_eq!(AccountStorages::iter_prefix(contract).count(), 3);
+		assert!(EVM::queued_for_deletion(contract));
+
+		// Only two slots fit in one block.
+		assert_eq!(EVM::on_initialize(1), 1 + 3 + 2 * 8);
+		assert_eq!(AccountStorages::iter_prefix(contract).count(), 1);
+		assert!(EVM::queued_for_deletion(contract));
+
+		assert_eq!(EVM::on_initialize(2), 1 + 3 + 8 + 1);
+		assert_eq!(AccountStorages::iter_prefix(contract).count(), 0);
+		assert!(!EVM::queued_for_deletion(contract));
+
+		assert_eq!(EVM::on_initialize(3), 1);
+	});
+}
+
+#[test]
+fn redeployed_contract_waits_for_storage_removal() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let init = vec![
+			0x60, 0x00, // PUSH1 0
+			0x54, // SLOAD
+			0x60, 0x01, // PUSH1 1
+			0x55, // SSTORE
+		];
+		let salt = H256::zero();
+		let contract = H160::from_slice(
+			&sha3::Keccak256::digest(
+				&[
+					&[0xff][..],
+					source.as_bytes(),
+					salt.as_bytes(),
+					&sha3::Keccak256::digest(&init)[..],
+				]
+				.concat(),
+			)[12..],
+		);
+		let create2 = || {
+			<Test as Config>::Runner::create2(
+				source,
+				init.clone(),
+				salt,
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		// Left over by a self-destructed contract at the same address.
+		AccountStorages::insert(contract, H256::zero(), H256::repeat_byte(1));
+		DeletionQueue::insert(contract, true);
+
+		let info = create2();
+
+		assert_eq!(
+			info.exit_reason,
+			ExitReason::Error(ExitError::CreateCollision)
+		);
+		assert_eq!(info.value, contract);
+		assert_eq!(
+			AccountStorages::get(contract, H256::zero()),
+			H256::repeat_byte(1)
+		);
+
+		EVM::on_initialize(1);
+
+		// The new contract reads an empty slot 0 and stores it in slot 1.
+		let info = create2();
+
+		assert!(info.exit_reason.is_succeed());
+		assert_eq!(info.value, contract);
+		assert_eq!(AccountStorages::iter_prefix(contract).count(), 0);
+		assert!(!EVM::queued_for_deletion(contract));
+	});
+}
+
+#[test]
+fn fee_split_between_author_treasury_and_burn() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			EVM::set_fee_split(
+				Origin::root(),
+				Perbill::from_percent(60),
+				Perbill::from_percent(50)
+			),
+			Error::<Test>::InvalidFeeSplit
+		);
+		assert_ok!(EVM::set_fee_split(
+			Origin::root(),
+			Perbill::from_percent(50),
+			Perbill::from_percent(20)
+		));
+
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let author = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		let issuance = Etp::total_issuance();
+		let info = <Test as Config>::Runner::call(
+			source,
+			H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+			Vec::new(),
+			U256::default(),
+			100000,
+			Some(U256::one()),
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+
+		assert_eq!(info.used_gas, U256::from(21000));
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000 - 21000)
+		);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&author).balance,
+			U256::from(10500)
+		);
+		assert_eq!(Etp::free_balance(&TreasuryAccount::get()), 4200);
+		// Only the burned share leaves the issuance, the others are moved from the source.
+		assert_eq!(Etp::total_issuance(), issuance - 6300);
+	});
+}
+
+#[test]
+fn fee_paid_in_dna_at_governance_rate() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let source_id = <Test as Config>::AddressMapping::into_account_id(source);
+		let author = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		let author_id = <Test as Config>::AddressMapping::into_account_id(author);
+		let call = || {
+			<Test as Config>::Runner::call(
+				source,
+				H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+				Vec::new(),
+				U256::default(),
+				100000,
+				Some(U256::one()),
+				None,
+				<Test as Config>::config(),
+			)
+		};
+
+		let _ = Dna::deposit_creating(&source_id, 1000000);
+		assert_ok!(EVM::set_fee_split(
+			Origin::root(),
+			Perbill::from_percent(50),
+			Perbill::from_percent(20)
+		));
+		assert_ok!(EVM::set_fee_currency(
+			Origin::root(),
+			source,
+			FeeCurrency::Dna
+		));
+
+		// Not enabled yet, still paid in ETP.
+		assert_eq!(EVM::fee_currency(&source), FeeCurrency::Etp);
+		assert_noop!(
+			EVM::set_dna_fee_rate(Origin::root(), Some(FixedU128::zero())),
+			Error::<Test>::InvalidFeeRate
+		);
+		assert_ok!(EVM::set_dna_fee_rate(
+			Origin::root(),
+			Some(FixedU128::saturating_from_integer(2))
+		));
+		assert_eq!(EVM::fee_currency(&source), FeeCurrency::Dna);
+		assert_eq!(EVM::fee_balance(&source), U256::from(500000));
+
+		assert_eq!(call().unwrap().used_gas, U256::from(21000));
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000)
+		);
+		assert_eq!(Dna::free_balance(&source_id), 1000000 - 2 * 21000);
+		assert_eq!(EVM::fee_balance(&source), U256::from(500000 - 21000));
+		assert_eq!(Dna::free_balance(&author_id), 2 * 10500);
+		assert_eq!(Dna::free_balance(&TreasuryAccount::get()), 2 * 4200);
+		assert_eq!(Etp::free_balance(&TreasuryAccount::get()), 0);
+
+		// The maximum fee of 100000 must be covered by the DNA balance alone.
+		assert_ok!(EVM::set_dna_fee_rate(
+			Origin::root(),
+			Some(FixedU128::saturating_from_integer(10))
+		));
+		assert!(call().is_err());
+
+		// Covered, but paying it would reap the account.
+		let _ = Dna::deposit_creating(&source_id, 2 * 21000);
+		assert_eq!(EVM::fee_balance(&source), U256::from(100000));
+		assert!(matches!(call(), Err(Error::<Test>::WithdrawFailed)));
+		assert_eq!(Dna::free_balance(&source_id), 1000000);
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).nonce,
+			U256::from(2)
+		);
+
+		assert_ok!(EVM::set_dna_fee_rate(Origin::root(), None));
+		assert_eq!(EVM::fee_currency(&source), FeeCurrency::Etp);
+		assert_ok!(call());
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&source).balance,
+			U256::from(1000000 - 21000)
+		);
+	});
+}
+
+#[test]
+fn deployment_policy_applies_to_transactions_and_opcodes() {
+	new_test_ext().execute_with(|| {
+		let deployer = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let deployer_id = <Test as Config>::AddressMapping::into_account_id(deployer);
+		let factory = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		AccountCodes::insert(
+			factory,
+			vec![
+				0x60, 0x00, // PUSH1 0
+				0x80, // DUP1
+				0x80, // DUP1
+				0xf0, // CREATE
+				0x60, 0x00, // PUSH1 0
+				0x52, // MSTORE
+				0x60, 0x20, // PUSH1 32
+				0x60, 0x00, // PUSH1 0
+				0xf3, // RETURN
+			],
+		);
+		let create = || {
+			<Test as Config>::Runner::create(
+				deployer,
+				Vec::new(),
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+		};
+		// The address created by the factory, zero if the creation failed.
+		let create_by_factory = || {
+			let info = <Test as Config>::Runner::call(
+				deployer,
+				factory,
+				Vec::new(),
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap();
+
+			assert!(info.exit_reason.is_succeed());
+
+			H160::from(H256::from_slice(&info.value))
+		};
+
+		assert_noop!(
+			EVM::set_deployment_policy(
+				Origin::signed(deployer_id.clone()),
+				DeploymentPolicy::AllowList
+			),
+			BadOrigin
+		);
+		assert_ok!(EVM::set_deployment_policy(
+			Origin::root(),
+			DeploymentPolicy::AllowList
+		));
+		assert!(matches!(
+			create(),
+			Err(Error::<Test>::DeploymentNotPermitted)
+		));
+		assert_eq!(create_by_factory(), H160::zero());
+
+		assert_ok!(EVM::add_deployer(Origin::root(), deployer));
+		assert!(create().unwrap().exit_reason.is_succeed());
+		// The factory is the deployer of the contracts it creates.
+		assert_eq!(create_by_factory(), H160::zero());
+
+		assert_ok!(EVM::add_deployer(Origin::root(), factory));
+		assert_ne!(create_by_factory(), H160::zero());
+
+		assert_ok!(EVM::set_deployment_policy(
+			Origin::root(),
+			DeploymentPolicy::Deposit(100)
+		));
+		assert!(matches!(
+			create(),
+			Err(Error::<Test>::DeploymentNotPermitted)
+		));
+
+		assert_ok!(EVM::reserve_deployer_deposit(
+			Origin::signed(deployer_id.clone()),
+			deployer
+		));
+		assert_eq!(Etp::reserved_balance(&deployer_id), 100);
+		assert!(create().unwrap().exit_reason.is_succeed());
+
+		assert_ok!(EVM::release_deployer_deposit(
+			Origin::signed(deployer_id.clone()),
+			deployer
+		));
+		assert_eq!(Etp::reserved_balance(&deployer_id), 0);
+		assert!(matches!(
+			create(),
+			Err(Error::<Test>::DeploymentNotPermitted)
+		));
+	});
+}
+
+// The nested creation checks of `SubstrateStackState::transfer` rely on evm 0.22 calling
+// `reset_storage` and then `transfer` for the new address, even for a zero value. Revisit them
+// before bumping the pinned evm version.
+#[test]
+fn nested_create_checked_by_the_transfer_hook() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let factory = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		// `keccak(rlp([factory, nonce]))`, for a nonce below `0x80`.
+		let created_at = |nonce: u8| {
+			H160::from_slice(
+				&sha3::Keccak256::digest(
+					&[
+						&[0xd6, 0x94][..],
+						factory.as_bytes(),
+						&[if nonce == 0 { 0x80 } else { nonce }],
+					]
+					.concat(),
+				)[12..],
+			)
+		};
+		AccountCodes::insert(
+			factory,
+			vec![
+				0x60, 0x00, // PUSH1 0
+				0x80, // DUP1
+				0x80, // DUP1
+				0xf0, // CREATE
+				0x60, 0x00, // PUSH1 0
+				0x52, // MSTORE
+				0x60, 0x20, // PUSH1 32
+				0x60, 0x00, // PUSH1 0
+				0xf3, // RETURN
+			],
+		);
+		let create_by_factory = || {
+			let info = <Test as Config>::Runner::call(
+				source,
+				factory,
+				Vec::new(),
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap();
+
+			assert!(info.exit_reason.is_succeed());
+
+			H160::from(H256::from_slice(&info.value))
+		};
+
+		assert_ok!(EVM::set_deployment_policy(
+			Origin::root(),
+			DeploymentPolicy::AllowList
+		));
+		assert_eq!(create_by_factory(), H160::zero());
+		// The creation got as far as the hooks, the nonce of the factory is spent.
+		assert_eq!(
+			<Test as Config>::AccountBasicMapping::account_basic(&factory).nonce,
+			U256::one()
+		);
+
+		assert_ok!(EVM::add_deployer(Origin::root(), factory));
+		// Left over by a self-destructed contract at the next address.
+		AccountStorages::insert(created_at(1), H256::zero(), H256::repeat_byte(1));
+		assert_eq!(create_by_factory(), H160::zero());
+		assert!(!AccountCodes::contains_key(created_at(1)));
+
+		assert_eq!(create_by_factory(), created_at(2));
+	});
+}
+
+#[test]
+fn concat_address_mapping_reverses() {
+	let address = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+	let account_id = ConcatAddressMapping::into_account_id(address);
+
+	assert_eq!(
+		ConcatAddressMapping::into_address(&account_id),
+		Some(address)
+	);
+	assert_eq!(
+		ConcatAddressMapping::into_address(&AccountId32::from([1; 32])),
+		None
+	);
+
+	let mut corrupted: [u8; 32] = account_id.into();
+	corrupted[31] ^= 1;
+	assert_eq!(
+		ConcatAddressMapping::into_address(&AccountId32::from(corrupted)),
+		None
+	);
+}
+
+#[test]
+fn geth_alloc_round_trips() {
+	use geth::GethAlloc;
+
+	let genesis = r#"{
+		"config": { "chainId": 1337 },
+		"alloc": {
+			"1000000000000000000000000000000000000001": { "balance": "1000000000000000000" },
+			"0x1000000000000000000000000000000000000002": {
+				"balance": "0x10",
+				"nonce": "0x1",
+				"code": "0x6080",
+				"storage": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001":
+						"0x00000000000000000000000000000000000000000000000000000000000000ff"
+				}
+			}
+		}
+	}"#;
+	let alloc = GethAlloc::from_json(genesis).unwrap();
+	let accounts = alloc.clone().into_genesis_accounts();
+	let eoa = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+	let contract = H160::from_str("1000000000000000000000000000000000000002").unwrap();
+
+	assert_eq!(accounts[&eoa].balance, U256::exp10(18));
+	assert_eq!(accounts[&eoa].nonce, U256::zero());
+	assert!(accounts[&eoa].code.is_empty());
+	assert_eq!(accounts[&contract].balance, U256::from(16));
+	assert_eq!(accounts[&contract].nonce, U256::one());
+	assert_eq!(accounts[&contract].code, vec![0x60, 0x80]);
+	assert_eq!(
+		accounts[&contract].storage[&H256::from_low_u64_be(1)],
+		H256::from_low_u64_be(0xff)
+	);
+
+	// An exported bare `alloc` reads back the same.
+	assert_eq!(
+		GethAlloc::from_json(&serde_json::to_string(&alloc).unwrap()).unwrap(),
+		alloc
+	);
+	assert!(GethAlloc::from_json(r#"{ "0x01": { "balance": "0" } }"#).is_err());
+}
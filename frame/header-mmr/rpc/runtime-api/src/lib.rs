@@ -30,9 +30,13 @@ use core::fmt::{self, Debug, Display};
 use codec::{Codec, Decode, Encode};
 // --- substrate ---
 use sp_api::decl_runtime_apis;
-use sp_runtime::traits::{MaybeDisplay, MaybeFromStr};
+use sp_runtime::{
+	traits::{MaybeDisplay, MaybeFromStr},
+	RuntimeDebug,
+};
 use sp_std::prelude::*;
 // --- hyperspace ---
+pub use hyperspace_header_mmr_primitives::ConsistencyProof;
 use hyperspace_support::impl_runtime_dispatch_info;
 
 impl_runtime_dispatch_info! {
@@ -43,17 +47,101 @@ impl_runtime_dispatch_info! {
 }
 
 decl_runtime_apis! {
+	#[api_version(7)]
 	pub trait HeaderMMRApi<Hash>
 	where
 		Hash: Debug + Codec + MaybeDisplay + MaybeFromStr,
 	{
+		/// Prove one leaf, an empty proof of `mmr_size` zero on failure.
+		#[changed_in(7)]
 		fn gen_proof(
 			block_number_of_member_leaf: u64,
 			block_number_of_last_leaf: u64,
 		) -> RuntimeDispatchInfo<Hash>;
+
+		/// Prove one leaf.
+		///
+		/// The runtimes since version 4 only keep the peaks of the MMR, the proofs are generated
+		/// by the node from the off-chain indexing storage, so do `gen_batch_proof` and
+		/// `get_leaf`. Since version 7, the runtime fails with `ProofError::Offchain` instead of
+		/// an empty proof.
+		fn gen_proof(
+			block_number_of_member_leaf: u64,
+			block_number_of_last_leaf: u64,
+		) -> Result<RuntimeDispatchInfo<Hash>, ProofError>;
+
+		/// Prove several leaves with one proof, the leaves are proved in ascending order
+		/// without duplicates.
+		///
+		/// Since version 2.
+		fn gen_batch_proof(
+			block_numbers_of_member_leaves: Vec<u64>,
+			block_number_of_last_leaf: u64,
+		) -> Result<RuntimeDispatchInfo<Hash>, ProofError>;
+
+		/// The root of the MMR whose last leaf is `block_number_of_last_leaf`.
+		///
+		/// Since version 3.
+		fn get_root(block_number_of_last_leaf: u64) -> Result<Hash, ProofError>;
+
+		/// The MMR node of the leaf of `leaf_index`, the hash of the block of this number for the
+		/// leaves before `first_v1_leaf`, the hash of the ABI encoded leaf since.
+		///
+		/// Since version 3.
+		fn get_leaf(leaf_index: u64) -> Result<Hash, ProofError>;
+
+		/// Verify a proof of the `(leaf index, leaf)` pairs against the root of the MMR of
+		/// `mmr_size`.
+		///
+		/// Since version 3.
+		fn verify_proof(
+			root: Hash,
+			leaves: Vec<(u64, Hash)>,
+			proof: Vec<Hash>,
+			mmr_size: u64,
+		) -> bool;
+
+		/// The MMR size and its peaks, `(position, node)` in ascending order.
+		///
+		/// Since version 4.
+		fn peaks() -> (u64, Vec<(u64, Hash)>);
+
+		/// Index of the first leaf committing to the block number, the state root and the
+		/// extrinsics root of its block, `None` if all the leaves are block hashes.
+		///
+		/// Since version 5.
+		fn first_v1_leaf() -> Option<u64>;
+
+		/// Verify that the MMR of `old_root` and `old_mmr_size` is a prefix of the MMR of
+		/// `new_root` and `new_mmr_size`.
+		///
+		/// Since version 6.
+		fn verify_consistency_proof(
+			old_root: Hash,
+			old_mmr_size: u64,
+			new_root: Hash,
+			new_mmr_size: u64,
+			proof: ConsistencyProof<Hash>,
+		) -> bool;
 	}
 }
 
+/// Why the MMR couldn't be queried.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum ProofError {
+	/// The MMR has no leaf yet.
+	MMRNotBuilt,
+	/// A leaf is beyond the last leaf, or the last leaf is beyond the MMR.
+	LeafBeyondMMR,
+	/// The MMR failed to generate the proof or the root, e.g. no leaf was given.
+	GenProofFailed,
+	/// A node was pruned from the runtime storage.
+	Pruned,
+	/// The proof needs the MMR nodes of the off-chain indexing storage, only the node can
+	/// generate it.
+	Offchain,
+}
+
 #[derive(Default, Eq, PartialEq, Encode, Decode)]
 pub struct Proof<Hash>(pub Vec<Hash>);
 impl<Hash> Debug for Proof<Hash>
@@ -22,68 +22,768 @@
 pub use hyperspace_header_mmr_rpc_runtime_api::HeaderMMRApi as HeaderMMRRuntimeApi;
 
 // --- std ---
-use std::sync::Arc;
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
 // --- crates ---
-use codec::Codec;
+use codec::{Decode, Encode};
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
+use serde::Serialize;
+// --- github ---
+use merkle_mountain_range::{Error as MMRError, MMRStore, Merge, Result as MMRResult, MMR};
 // --- substrate ---
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_core::Bytes;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, MaybeDisplay, MaybeFromStr},
+	traits::{Block as BlockT, HashFor, Header as HeaderT, MaybeFromStr, NumberFor},
+	SaturatedConversion,
 };
 // --- hyperspace ---
-use hyperspace_header_mmr_rpc_runtime_api::RuntimeDispatchInfo;
+use hyperspace_header_mmr_primitives::{
+	find_parent_mmr_root, gen_consistency_proof, leaf_count, leaf_index_to_mmr_size,
+	leaf_index_to_pos, offchain_key, offchain_leaf_key, pos_height_in_tree,
+	verify_consistency_proof, verify_proof, MMRMerge,
+};
+pub use hyperspace_header_mmr_primitives::{ConsistencyProof, LeafV1, MMRLeaf};
+use hyperspace_header_mmr_rpc_runtime_api::{Proof, ProofError, RuntimeDispatchInfo};
 
 const RUNTIME_ERROR: i64 = -1;
+const LEAF_BEYOND_MMR: i64 = 1;
+const MMR_NOT_BUILT: i64 = 2;
+const STATE_PRUNED: i64 = 3;
+const MMR_NODE_PRUNED: i64 = 4;
+
+/// A leaf with its MMR node, its SCALE and its Ethereum ABI encodings.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeafData<BlockNumber, Hash> {
+	pub leaf: MMRLeaf<BlockNumber, Hash>,
+	pub node: Hash,
+	pub scale: Bytes,
+	pub abi: Bytes,
+}
 
 #[rpc]
-pub trait HeaderMMRApi<Hash, Response> {
+pub trait HeaderMMRApi<BlockHash, Hash, Response, Leaf> {
+	/// Prove a leaf against the MMR of the last leaf, at the best block if `at` is omitted.
 	#[rpc(name = "headerMMR_genProof")]
 	fn gen_proof(
 		&self,
 		block_number_of_member_leaf: u64,
 		block_number_of_last_leaf: u64,
+		at: Option<BlockHash>,
+	) -> Result<Response>;
+
+	/// Prove several leaves with one proof against the MMR of the last leaf, at the best block
+	/// if `at` is omitted.
+	///
+	/// The proof is for the leaves in ascending order without duplicates.
+	#[rpc(name = "headerMMR_genBatchProof")]
+	fn gen_batch_proof(
+		&self,
+		block_numbers_of_member_leaves: Vec<u64>,
+		block_number_of_last_leaf: u64,
+		at: Option<BlockHash>,
 	) -> Result<Response>;
+
+	/// The root of the MMR whose last leaf is `block_number_of_last_leaf`, at the best block if
+	/// `at` is omitted.
+	#[rpc(name = "headerMMR_getRoot")]
+	fn get_root(&self, block_number_of_last_leaf: u64, at: Option<BlockHash>) -> Result<Hash>;
+
+	/// The leaf of `leaf_index`, the hash of the block of this number, at the best block if `at`
+	/// is omitted.
+	#[rpc(name = "headerMMR_getLeaf")]
+	fn get_leaf(&self, leaf_index: u64, at: Option<BlockHash>) -> Result<Hash>;
+
+	/// The leaf of `leaf_index` with its encodings, at the best block if `at` is omitted.
+	#[rpc(name = "headerMMR_getLeafData")]
+	fn get_leaf_data(&self, leaf_index: u64, at: Option<BlockHash>) -> Result<Leaf>;
+
+	/// Verify a proof of the `(leaf index, leaf)` pairs against the root of the MMR of
+	/// `mmr_size`, with the runtime of the best block if `at` is omitted.
+	#[rpc(name = "headerMMR_verifyProof")]
+	fn verify_proof(
+		&self,
+		root: Hash,
+		leaves: Vec<(u64, Hash)>,
+		proof: Vec<Hash>,
+		mmr_size: u64,
+		at: Option<BlockHash>,
+	) -> Result<bool>;
+
+	/// Prove that the MMR of the old last leaf is a prefix of the MMR of the new last leaf, at
+	/// the best block if `at` is omitted.
+	///
+	/// The MMR sizes are `leaf_index_to_mmr_size` of the last leaves.
+	#[rpc(name = "headerMMR_genConsistencyProof")]
+	fn gen_consistency_proof(
+		&self,
+		block_number_of_old_last_leaf: u64,
+		block_number_of_new_last_leaf: u64,
+		at: Option<BlockHash>,
+	) -> Result<ConsistencyProof<Hash>>;
+
+	/// Verify that the MMR of `old_root` and `old_mmr_size` is a prefix of the MMR of
+	/// `new_root` and `new_mmr_size`, with the runtime of the best block if `at` is omitted.
+	#[rpc(name = "headerMMR_verifyConsistencyProof")]
+	fn verify_consistency_proof(
+		&self,
+		old_root: Hash,
+		old_mmr_size: u64,
+		new_root: Hash,
+		new_mmr_size: u64,
+		proof: ConsistencyProof<Hash>,
+		at: Option<BlockHash>,
+	) -> Result<bool>;
 }
 
-pub struct HeaderMMR<Client, Block> {
+pub struct HeaderMMR<Client, Block, S> {
 	client: Arc<Client>,
-	_marker: std::marker::PhantomData<Block>,
+	offchain_storage: Option<S>,
+	_marker: PhantomData<Block>,
 }
 
-impl<Client, Block> HeaderMMR<Client, Block> {
-	pub fn new(client: Arc<Client>) -> Self {
+impl<Client, Block, S> HeaderMMR<Client, Block, S> {
+	/// The MMR nodes pruned from the runtime state are read from `offchain_storage`, the
+	/// off-chain indexing storage of the node, and rebuilt from the block hashes without it.
+	pub fn new(client: Arc<Client>, offchain_storage: Option<S>) -> Self {
 		Self {
 			client,
+			offchain_storage,
 			_marker: Default::default(),
 		}
 	}
 }
 
-impl<Client, Block, Hash> HeaderMMRApi<Hash, RuntimeDispatchInfo<Hash>> for HeaderMMR<Client, Block>
+impl<Client, Block, S> HeaderMMR<Client, Block, S>
 where
 	Client: 'static + Send + Sync + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-	Client::Api: HeaderMMRRuntimeApi<Block, Hash>,
+	Client::Api: HeaderMMRRuntimeApi<Block, Block::Hash>,
 	Block: BlockT,
-	Hash: core::fmt::Debug + Codec + MaybeDisplay + MaybeFromStr,
+	Block::Hash: MaybeFromStr,
+	S: 'static + OffchainStorage,
+{
+	/// The block to query at, and whether its runtime supports the API `version`.
+	fn runtime_at(&self, at: Option<Block::Hash>, version: u32) -> Result<(BlockId<Block>, bool)> {
+		let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+		let at = BlockId::hash(hash);
+
+		match self.client.header(at) {
+			Ok(Some(_)) => (),
+			_ => {
+				return Err(Error {
+					code: ErrorCode::InvalidParams,
+					message: format!("Unknown block {:?}.", hash),
+					data: None,
+				})
+			}
+		}
+
+		let supported = self.supports(&at, version)?;
+
+		Ok((at, supported))
+	}
+
+	/// Whether the runtime of `at` supports the API `version`.
+	fn supports(&self, at: &BlockId<Block>, version: u32) -> Result<bool> {
+		// The runtime version is the first read of the block state
+		self.client
+			.runtime_api()
+			.has_api_with::<dyn HeaderMMRRuntimeApi<Block, Block::Hash>, _>(at, |v| v >= version)
+			.map_err(|e| Error {
+				code: ErrorCode::ServerError(STATE_PRUNED),
+				message: format!("State of block {} is pruned.", at),
+				data: Some(format!("{:?}", e).into()),
+			})
+	}
+
+	fn gen_batch_proof_at(
+		&self,
+		at: &BlockId<Block>,
+		block_numbers_of_member_leaves: Vec<u64>,
+		block_number_of_last_leaf: u64,
+	) -> Result<RuntimeDispatchInfo<Block::Hash>> {
+		if self.supports(at, 4)? {
+			return self.gen_batch_proof_natively(
+				at,
+				block_numbers_of_member_leaves,
+				block_number_of_last_leaf,
+			);
+		}
+
+		self.client
+			.runtime_api()
+			.gen_batch_proof(
+				at,
+				block_numbers_of_member_leaves,
+				block_number_of_last_leaf,
+			)
+			.map_err(runtime_error)?
+			.map_err(proof_error)
+	}
+
+	/// Prove the leaves with the MMR nodes of the node, the runtime only keeps the peaks since
+	/// version 4.
+	fn gen_batch_proof_natively(
+		&self,
+		at: &BlockId<Block>,
+		mut block_numbers_of_member_leaves: Vec<u64>,
+		block_number_of_last_leaf: u64,
+	) -> Result<RuntimeDispatchInfo<Block::Hash>> {
+		let mmr = self.mmr_at(at, leaf_index_to_pos(block_number_of_last_leaf))?;
+
+		if block_numbers_of_member_leaves
+			.iter()
+			.any(|leaf| *leaf > block_number_of_last_leaf)
+		{
+			return Err(proof_error(ProofError::LeafBeyondMMR));
+		}
+
+		block_numbers_of_member_leaves.sort_unstable();
+		block_numbers_of_member_leaves.dedup();
+
+		let mmr_size = leaf_index_to_mmr_size(block_number_of_last_leaf);
+		let root = self.parent_mmr_root(block_number_of_last_leaf);
+		let verified = |(leaves, proof): &(Vec<(u64, Block::Hash)>, Vec<Block::Hash>)| {
+			root.map_or(true, |root| {
+				verify_proof::<HashFor<Block>>(root, leaves.clone(), proof.clone(), mmr_size)
+			})
+		};
+		let mut proved = self.prove(&mmr, mmr_size, &block_numbers_of_member_leaves, true)?;
+
+		if !verified(&proved) {
+			// Off-chain nodes indexed by the blocks of a retracted fork, rebuild them
+			proved = self.prove(&mmr, mmr_size, &block_numbers_of_member_leaves, false)?;
+
+			if !verified(&proved) {
+				return Err(Error {
+					code: ErrorCode::ServerError(RUNTIME_ERROR),
+					message: "Unable to generate proof.".into(),
+					data: Some("MMR nodes inconsistent with the parent MMR root".into()),
+				});
+			}
+		}
+
+		Ok(RuntimeDispatchInfo {
+			mmr_size,
+			proof: Proof(proved.1),
+		})
+	}
+
+	/// Prove the consistency of the two MMRs with the MMR nodes of the node, verified against
+	/// the MMR roots of the headers.
+	fn gen_consistency_proof_natively(
+		&self,
+		at: &BlockId<Block>,
+		block_number_of_old_last_leaf: u64,
+		block_number_of_new_last_leaf: u64,
+	) -> Result<ConsistencyProof<Block::Hash>> {
+		let mmr = self.mmr_at(at, leaf_index_to_pos(block_number_of_new_last_leaf))?;
+
+		if block_number_of_old_last_leaf > block_number_of_new_last_leaf {
+			return Err(proof_error(ProofError::LeafBeyondMMR));
+		}
+
+		let old_mmr_size = leaf_index_to_mmr_size(block_number_of_old_last_leaf);
+		let new_mmr_size = leaf_index_to_mmr_size(block_number_of_new_last_leaf);
+		let roots = self
+			.parent_mmr_root(block_number_of_old_last_leaf)
+			.zip(self.parent_mmr_root(block_number_of_new_last_leaf));
+		let verified = |proof: &ConsistencyProof<Block::Hash>| {
+			roots.map_or(true, |(old_root, new_root)| {
+				verify_consistency_proof::<MMRMerge<HashFor<Block>>>(
+					old_root,
+					old_mmr_size,
+					new_root,
+					new_mmr_size,
+					proof.clone(),
+				)
+			})
+		};
+		let prove = |use_offchain| {
+			gen_consistency_proof(
+				&self.node_store(&mmr, use_offchain),
+				old_mmr_size,
+				new_mmr_size,
+			)
+			.map_err(runtime_error)
+		};
+		let mut proof = prove(true)?;
+
+		if !verified(&proof) {
+			// Off-chain nodes indexed by the blocks of a retracted fork, rebuild them
+			proof = prove(false)?;
+
+			if !verified(&proof) {
+				return Err(Error {
+					code: ErrorCode::ServerError(RUNTIME_ERROR),
+					message: "Unable to generate proof.".into(),
+					data: Some("MMR nodes inconsistent with the parent MMR roots".into()),
+				});
+			}
+		}
+
+		Ok(proof)
+	}
+
+	/// The MMR kept in the runtime state of `at`, if it has a node at `pos`.
+	fn mmr_at(&self, at: &BlockId<Block>, pos: u64) -> Result<RuntimeMMR<Block::Hash>> {
+		let api = self.client.runtime_api();
+		let (mmr_size, peaks) = api.peaks(at).map_err(runtime_error)?;
+
+		if mmr_size == 0 {
+			return Err(proof_error(ProofError::MMRNotBuilt));
+		}
+		if pos >= mmr_size {
+			return Err(proof_error(ProofError::LeafBeyondMMR));
+		}
+
+		Ok(RuntimeMMR {
+			peaks: peaks.into_iter().collect(),
+			first_v1_leaf: if self.supports(at, 5)? {
+				api.first_v1_leaf(at).map_err(runtime_error)?
+			} else {
+				None
+			},
+		})
+	}
+
+	/// The root of the MMR of the last leaf, from the digest of the next block of the best
+	/// chain.
+	fn parent_mmr_root(&self, block_number_of_last_leaf: u64) -> Option<Block::Hash> {
+		let at = BlockId::number((block_number_of_last_leaf + 1).saturated_into());
+
+		self.client
+			.header(at)
+			.ok()
+			.flatten()
+			.and_then(|header| find_parent_mmr_root(&header))
+	}
+
+	/// The leaves and their proof in the MMR of `mmr_size`.
+	fn prove(
+		&self,
+		mmr: &RuntimeMMR<Block::Hash>,
+		mmr_size: u64,
+		leaves: &[u64],
+		use_offchain: bool,
+	) -> Result<(Vec<(u64, Block::Hash)>, Vec<Block::Hash>)> {
+		let store = self.node_store(mmr, use_offchain);
+		let leaves = leaves
+			.iter()
+			.map(|leaf| match store.get_elem(leaf_index_to_pos(*leaf)) {
+				Ok(Some(hash)) => Ok((*leaf, hash)),
+				Ok(None) => Err(runtime_error(format!("Unknown leaf {}", leaf))),
+				Err(e) => Err(runtime_error(e)),
+			})
+			.collect::<Result<Vec<_>>>()?;
+		let positions = leaves
+			.iter()
+			.map(|(leaf, _)| leaf_index_to_pos(*leaf))
+			.collect();
+		let proof = <MMR<_, MMRMerge<HashFor<Block>>, _>>::new(mmr_size, store)
+			.gen_proof(positions)
+			.map_err(runtime_error)?;
+
+		Ok((leaves, proof.proof_items().to_vec()))
+	}
+
+	fn node_store<'a>(
+		&'a self,
+		mmr: &'a RuntimeMMR<Block::Hash>,
+		use_offchain: bool,
+	) -> NodeStore<'a, Client, Block, S> {
+		NodeStore {
+			client: &self.client,
+			offchain_storage: self.offchain_storage.clone(),
+			mmr,
+			use_offchain,
+		}
+	}
+}
+
+impl<Client, Block, S>
+	HeaderMMRApi<
+		Block::Hash,
+		Block::Hash,
+		RuntimeDispatchInfo<Block::Hash>,
+		LeafData<NumberFor<Block>, Block::Hash>,
+	> for HeaderMMR<Client, Block, S>
+where
+	Client: 'static + Send + Sync + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	Client::Api: HeaderMMRRuntimeApi<Block, Block::Hash>,
+	Block: BlockT,
+	Block::Hash: MaybeFromStr,
+	S: 'static + OffchainStorage,
 {
 	fn gen_proof(
 		&self,
 		block_number_of_member_leaf: u64,
 		block_number_of_last_leaf: u64,
-	) -> Result<RuntimeDispatchInfo<Hash>> {
-		let api = self.client.runtime_api();
-		let best = self.client.info().best_hash;
-		let at = BlockId::hash(best);
+		at: Option<Block::Hash>,
+	) -> Result<RuntimeDispatchInfo<Block::Hash>> {
+		let (at, batch_supported) = self.runtime_at(at, 2)?;
 
-		api.gen_proof(&at, block_number_of_member_leaf, block_number_of_last_leaf)
-			.map_err(|e| Error {
+		if batch_supported {
+			return self.gen_batch_proof_at(
+				&at,
+				vec![block_number_of_member_leaf],
+				block_number_of_last_leaf,
+			);
+		}
+
+		// Runtimes without batch proofs only report failures with an empty proof
+		#[allow(deprecated)]
+		let info = self
+			.client
+			.runtime_api()
+			.gen_proof_before_version_7(&at, block_number_of_member_leaf, block_number_of_last_leaf)
+			.map_err(runtime_error)?;
+
+		if info.mmr_size == 0 {
+			Err(Error {
 				code: ErrorCode::ServerError(RUNTIME_ERROR),
-				message: "Unable to query power.".into(),
-				data: Some(format!("{:?}", e).into()),
+				message: "Unable to generate proof.".into(),
+				data: None,
 			})
+		} else {
+			Ok(info)
+		}
+	}
+
+	fn gen_batch_proof(
+		&self,
+		block_numbers_of_member_leaves: Vec<u64>,
+		block_number_of_last_leaf: u64,
+		at: Option<Block::Hash>,
+	) -> Result<RuntimeDispatchInfo<Block::Hash>> {
+		if block_numbers_of_member_leaves.is_empty() {
+			return Err(Error::invalid_params("No leaf to prove."));
+		}
+
+		let (at, supported) = self.runtime_at(at, 2)?;
+
+		if !supported {
+			return Err(unsupported("Batch proofs"));
+		}
+
+		self.gen_batch_proof_at(
+			&at,
+			block_numbers_of_member_leaves,
+			block_number_of_last_leaf,
+		)
+	}
+
+	fn get_root(
+		&self,
+		block_number_of_last_leaf: u64,
+		at: Option<Block::Hash>,
+	) -> Result<Block::Hash> {
+		let (at, supported) = self.runtime_at(at, 3)?;
+
+		if !supported {
+			return Err(unsupported("MMR roots"));
+		}
+
+		match self
+			.client
+			.runtime_api()
+			.get_root(&at, block_number_of_last_leaf)
+			.map_err(runtime_error)?
+		{
+			// The runtime only keeps the latest roots since version 4
+			Err(ProofError::Pruned) => self
+				.parent_mmr_root(block_number_of_last_leaf)
+				.ok_or_else(|| proof_error(ProofError::Pruned)),
+			root => root.map_err(proof_error),
+		}
+	}
+
+	fn get_leaf(&self, leaf_index: u64, at: Option<Block::Hash>) -> Result<Block::Hash> {
+		let (at, supported) = self.runtime_at(at, 3)?;
+
+		if !supported {
+			return Err(unsupported("MMR leaves"));
+		}
+		if self.supports(&at, 4)? {
+			let pos = leaf_index_to_pos(leaf_index);
+			let mmr = self.mmr_at(&at, pos)?;
+
+			return match self.node_store(&mmr, true).get_elem(pos) {
+				Ok(Some(leaf)) => Ok(leaf),
+				Ok(None) => Err(proof_error(ProofError::Pruned)),
+				Err(e) => Err(runtime_error(e)),
+			};
+		}
+
+		self.client
+			.runtime_api()
+			.get_leaf(&at, leaf_index)
+			.map_err(runtime_error)?
+			.map_err(proof_error)
+	}
+
+	fn get_leaf_data(
+		&self,
+		leaf_index: u64,
+		at: Option<Block::Hash>,
+	) -> Result<LeafData<NumberFor<Block>, Block::Hash>> {
+		let (at, supported) = self.runtime_at(at, 4)?;
+
+		if !supported {
+			return Err(unsupported("MMR leaf data"));
+		}
+
+		let pos = leaf_index_to_pos(leaf_index);
+		let mmr = self.mmr_at(&at, pos)?;
+		let node = self.node_store(&mmr, true).get_elem(pos);
+
+		// The off-chain leaf may be the one of a retracted fork, then the rebuilt one
+		for use_offchain in &[true, false] {
+			if let (Ok(Some(leaf)), Ok(Some(node))) =
+				(self.node_store(&mmr, *use_offchain).leaf(leaf_index), &node)
+			{
+				if leaf.node::<HashFor<Block>>() == *node {
+					return Ok(LeafData {
+						node: *node,
+						scale: leaf.encode().into(),
+						abi: leaf.abi_encode().into(),
+						leaf,
+					});
+				}
+			}
+		}
+
+		Err(match node {
+			Ok(_) => proof_error(ProofError::Pruned),
+			Err(e) => runtime_error(e),
+		})
+	}
+
+	fn verify_proof(
+		&self,
+		root: Block::Hash,
+		leaves: Vec<(u64, Block::Hash)>,
+		proof: Vec<Block::Hash>,
+		mmr_size: u64,
+		at: Option<Block::Hash>,
+	) -> Result<bool> {
+		let (at, supported) = self.runtime_at(at, 3)?;
+
+		if !supported {
+			return Err(unsupported("Proof verification"));
+		}
+
+		self.client
+			.runtime_api()
+			.verify_proof(&at, root, leaves, proof, mmr_size)
+			.map_err(runtime_error)
+	}
+
+	fn gen_consistency_proof(
+		&self,
+		block_number_of_old_last_leaf: u64,
+		block_number_of_new_last_leaf: u64,
+		at: Option<Block::Hash>,
+	) -> Result<ConsistencyProof<Block::Hash>> {
+		let (at, supported) = self.runtime_at(at, 4)?;
+
+		if !supported {
+			return Err(unsupported("Consistency proofs"));
+		}
+
+		self.gen_consistency_proof_natively(
+			&at,
+			block_number_of_old_last_leaf,
+			block_number_of_new_last_leaf,
+		)
+	}
+
+	fn verify_consistency_proof(
+		&self,
+		old_root: Block::Hash,
+		old_mmr_size: u64,
+		new_root: Block::Hash,
+		new_mmr_size: u64,
+		proof: ConsistencyProof<Block::Hash>,
+		at: Option<Block::Hash>,
+	) -> Result<bool> {
+		let (at, supported) = self.runtime_at(at, 6)?;
+
+		if !supported {
+			return Err(unsupported("Consistency proof verification"));
+		}
+
+		self.client
+			.runtime_api()
+			.verify_consistency_proof(&at, old_root, old_mmr_size, new_root, new_mmr_size, proof)
+			.map_err(runtime_error)
+	}
+}
+
+/// The MMR kept in the runtime state of a block.
+struct RuntimeMMR<Hash> {
+	peaks: BTreeMap<u64, Hash>,
+	/// See `HeaderMMRRuntimeApi::first_v1_leaf`.
+	first_v1_leaf: Option<u64>,
+}
+
+/// The MMR nodes of a block, the peaks kept in its state, then the nodes of the off-chain
+/// indexing storage, then the nodes rebuilt from the headers of the best chain.
+struct NodeStore<'a, Client, Block: BlockT, S> {
+	client: &'a Arc<Client>,
+	offchain_storage: Option<S>,
+	mmr: &'a RuntimeMMR<Block::Hash>,
+	/// Whether to read the off-chain nodes, a fork may have indexed other nodes at the same
+	/// positions.
+	use_offchain: bool,
+}
+
+impl<'a, Client, Block, S> NodeStore<'a, Client, Block, S>
+where
+	Client: HeaderBackend<Block>,
+	Block: BlockT,
+	S: OffchainStorage,
+{
+	/// The leaf of `leaf_index`, from the off-chain indexing storage or rebuilt from the header
+	/// of its block.
+	///
+	/// The rebuilt `MMRLeaf::V1` leaves have no payload, the proofs of the runtimes with a
+	/// payload need the off-chain indexing storage.
+	fn leaf(&self, leaf_index: u64) -> MMRResult<Option<MMRLeaf<NumberFor<Block>, Block::Hash>>> {
+		let header = match self
+			.client
+			.header(BlockId::number(leaf_index.saturated_into()))
+		{
+			Ok(Some(header)) => header,
+			Ok(None) => return Ok(None),
+			Err(_) => return Err(MMRError::InconsistentStore),
+		};
+
+		if self
+			.mmr
+			.first_v1_leaf
+			.map_or(true, |first_v1_leaf| leaf_index < first_v1_leaf)
+		{
+			return Ok(Some(MMRLeaf::V0(header.hash())));
+		}
+		if self.use_offchain {
+			if let Some(leaf) = self
+				.offchain_storage
+				.as_ref()
+				.and_then(|storage| storage.get(STORAGE_PREFIX, &offchain_leaf_key(leaf_index)))
+				.and_then(|leaf| Decode::decode(&mut &leaf[..]).ok())
+			{
+				return Ok(Some(leaf));
+			}
+		}
+
+		Ok(Some(MMRLeaf::V1(LeafV1 {
+			block_number: *header.number(),
+			hash: header.hash(),
+			parent_hash: *header.parent_hash(),
+			state_root: *header.state_root(),
+			extrinsics_root: *header.extrinsics_root(),
+			payload: None,
+		})))
+	}
+
+	/// Rebuild the node at `pos` and index it off-chain.
+	fn rebuild(&self, pos: u64) -> MMRResult<Option<Block::Hash>> {
+		let node = match pos_height_in_tree(pos) {
+			0 => match self.leaf(leaf_count(pos))? {
+				Some(leaf) => leaf.node::<HashFor<Block>>(),
+				None => return Ok(None),
+			},
+			height => match (self.get_elem(pos - (1 << height))?, self.get_elem(pos - 1)?) {
+				(Some(left), Some(right)) => MMRMerge::<HashFor<Block>>::merge(&left, &right),
+				_ => return Ok(None),
+			},
+		};
+
+		if let Some(mut offchain_storage) = self.offchain_storage.clone() {
+			offchain_storage.set(STORAGE_PREFIX, &offchain_key(pos), &node.encode());
+		}
+
+		Ok(Some(node))
+	}
+}
+
+impl<'a, Client, Block, S> MMRStore<Block::Hash> for NodeStore<'a, Client, Block, S>
+where
+	Client: HeaderBackend<Block>,
+	Block: BlockT,
+	S: OffchainStorage,
+{
+	fn get_elem(&self, pos: u64) -> MMRResult<Option<Block::Hash>> {
+		if let Some(peak) = self.mmr.peaks.get(&pos) {
+			return Ok(Some(*peak));
+		}
+		if self.use_offchain {
+			if let Some(node) = self
+				.offchain_storage
+				.as_ref()
+				.and_then(|storage| storage.get(STORAGE_PREFIX, &offchain_key(pos)))
+				.and_then(|node| Decode::decode(&mut &node[..]).ok())
+			{
+				return Ok(Some(node));
+			}
+		}
+
+		self.rebuild(pos)
+	}
+
+	fn append(&mut self, _: u64, _: Vec<Block::Hash>) -> MMRResult<()> {
+		// Read only, the runtime appends the nodes
+		Err(MMRError::InconsistentStore)
+	}
+}
+
+fn runtime_error(e: impl core::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to generate proof.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+fn proof_error(e: ProofError) -> Error {
+	match e {
+		ProofError::MMRNotBuilt => Error {
+			code: ErrorCode::ServerError(MMR_NOT_BUILT),
+			message: "MMR not yet built.".into(),
+			data: None,
+		},
+		ProofError::LeafBeyondMMR => Error {
+			code: ErrorCode::ServerError(LEAF_BEYOND_MMR),
+			message: "Leaf beyond MMR.".into(),
+			data: None,
+		},
+		ProofError::GenProofFailed => Error {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to generate proof.".into(),
+			data: None,
+		},
+		ProofError::Pruned => Error {
+			code: ErrorCode::ServerError(MMR_NODE_PRUNED),
+			message: "MMR node pruned from the runtime state.".into(),
+			data: None,
+		},
+		ProofError::Offchain => Error {
+			code: ErrorCode::ServerError(MMR_NODE_PRUNED),
+			message: "MMR nodes only in the off-chain indexing storage.".into(),
+			data: None,
+		},
+	}
+}
+
+fn unsupported(what: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: format!("{} are not supported by the runtime of this block.", what),
+		data: None,
 	}
 }
@@ -31,9 +31,9 @@
 //! For more details about the MMR struct, refer https://github.com/mimblewimble/grin/blob/master/doc/mmr.md#structure
 //!
 //! ### MMR Proof
-//! Using the MMR Store Storage, MMR Proof can be generated for specific
-//! block header hash. Proofs can be used to verify block inclusion together with
-//! the mmr root in the header digest.
+//! Using the MMR nodes of the off-chain indexing storage, the node RPC generates
+//! MMR Proof for specific block header hash. Proofs can be used to verify block
+//! inclusion together with the mmr root in the header digest.
 //!
 //! ### Digest Item
 //! The is a ```MerkleMountainRangeRoot(Hash)``` digest item pre-subscribed in Digest.
@@ -59,47 +59,71 @@
 mod mock;
 mod tests;
 
-#[cfg(feature = "std")]
-use serde::Serialize;
-
 // --- github ---
 use merkle_mountain_range::{
 	leaf_index_to_mmr_size, leaf_index_to_pos, MMRStore, Result as MMRResult, MMR,
 };
 // --- substrate ---
 use codec::{Decode, Encode};
-use frame_support::{debug::error, decl_module, decl_storage};
+use frame_support::{debug::error, decl_module, decl_storage, traits::Get, weights::Weight};
 use sp_runtime::{
-	generic::{DigestItem, OpaqueDigestItemId},
-	traits::{Hash, Header},
+	generic::DigestItem,
+	traits::{One, Zero},
 	RuntimeDebug, SaturatedConversion,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 // --- hyperspace ---
-use hyperspace_header_mmr_rpc_runtime_api::{Proof, RuntimeDispatchInfo};
+pub use hyperspace_header_mmr_primitives::{
+	find_parent_mmr_root, ConsistencyProof, LeafPayload, LeafV1, MMRLeaf,
+	MerkleMountainRangeRootLog, PARENT_MMR_ROOT_LOG_ID,
+};
+use hyperspace_header_mmr_primitives::{
+	get_peaks, leaf_count, offchain_key, offchain_leaf_key, verify_consistency_proof,
+};
+use hyperspace_header_mmr_rpc_runtime_api::{ProofError, RuntimeDispatchInfo};
 use hyperspace_relay_primitives::MMR as MMRT;
-use hyperspace_support::impl_rpc;
 
-pub const PARENT_MMR_ROOT_LOG_ID: [u8; 4] = *b"MMRR";
+/// Number of `MMRNodeList` positions visited per block while pruning the nodes of a
+/// `Releases::V1_0_0` storage.
+pub const PRUNING_BATCH: u64 = 1024;
 
-#[cfg_attr(feature = "std", derive(Serialize))]
-#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub struct MerkleMountainRangeRootLog<Hash> {
-	/// Specific prefix to identify the mmr root log in the digest items with Other type.
-	pub prefix: [u8; 4],
-	/// The merkle mountain range root hash.
-	pub parent_mmr_root: Hash,
-}
+pub trait Config: frame_system::Config {
+	/// Number of the latest MMR roots kept in the runtime storage, to serve `MMR::get_root`
+	/// once the nodes of their MMR are pruned.
+	type MMRRootHistoryDepth: Get<u64>;
 
-pub trait Config: frame_system::Config {}
+	/// The custom payload of the `MMRLeaf::V1` leaves.
+	type LeafPayload: LeafPayload<Self::Hash>;
+}
 
 decl_storage! {
 	trait Store for Module<T: Config> as HyperspaceHeaderMMR {
-		/// MMR struct of the previous blocks, from first(genesis) to parent hash.
+		/// Peaks of the MMR of the previous blocks, from first(genesis) to parent hash.
+		///
+		/// Every node is written to the off-chain indexing storage under
+		/// `hyperspace_header_mmr_primitives::offchain_key`.
 		pub MMRNodeList get(fn mmr_node_list): map hasher(identity) u64 => Option<T::Hash>;
 
-		/// The MMR size and length of the mmr node list
+		/// The MMR size
 		pub MMRCounter get(fn mmr_counter): u64;
+
+		/// The MMR roots of the latest `MMRRootHistoryDepth` leaves, by last leaf index.
+		pub MMRRoots get(fn mmr_root_of): map hasher(identity) u64 => Option<T::Hash>;
+
+		/// The next position of `MMRNodeList` to prune, while migrating from `Releases::V1_0_0`.
+		pub PruningCursor get(fn pruning_cursor): Option<u64>;
+
+		/// Storage version of the pallet, `Releases::V1_0_0` if not set.
+		StorageVersion: Releases;
+
+		/// Index of the first `MMRLeaf::V1` leaf, the previous leaves are `MMRLeaf::V0`.
+		pub FirstV1Leaf get(fn first_v1_leaf): Option<u64>;
+
+		/// State root of the parent block, see `note_parent_state_root`.
+		ParentStateRoot: Option<T::Hash>;
+
+		/// Extrinsics root of the last finalized block, computed in `on_finalize`.
+		LastExtrinsicsRoot: Option<T::Hash>;
 	}
 }
 
@@ -108,16 +132,53 @@ decl_module! {
 	where
 		origin: T::Origin
 	{
-		fn on_finalize(_block_number: T::BlockNumber) {
+		const MMRRootHistoryDepth: u64 = T::MMRRootHistoryDepth::get();
+
+		fn on_runtime_upgrade() -> Weight {
+			if !StorageVersion::exists() {
+				StorageVersion::put(Releases::V2_0_0);
+				PruningCursor::put(0);
+
+				T::DbWeight::get().reads_writes(1, 2)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		fn on_initialize(_block_number: T::BlockNumber) -> Weight {
+			if let Some(cursor) = PruningCursor::get() {
+				migrations::prune_nodes::<T>(cursor)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		fn on_finalize(block_number: T::BlockNumber) {
 			let store = <ModuleMMRStore<T>>::default();
-			let parent_hash = <frame_system::Module<T>>::parent_hash();
+			let leaf_index = leaf_count(MMRCounter::get());
+			let leaf = Self::parent_leaf(block_number);
 			let mut mmr = <MMR<_, MMRMerge<T>, _>>::new(MMRCounter::get(), store);
 
 			// Update MMR and add mmr root to digest of block header
-			let _ = mmr.push(parent_hash);
+			let _ = mmr.push(leaf.node::<T::Hashing>());
 
 			if let Ok(parent_mmr_root) = mmr.get_root() {
 				if mmr.commit().is_ok() {
+					let depth = T::MMRRootHistoryDepth::get();
+
+					<MMRRoots<T>>::insert(leaf_index, parent_mmr_root);
+
+					if leaf_index >= depth {
+						<MMRRoots<T>>::remove(leaf_index - depth);
+					}
+					if let MMRLeaf::V1(_) = leaf {
+						if !FirstV1Leaf::exists() {
+							FirstV1Leaf::put(leaf_index);
+						}
+					}
+
+					sp_io::offchain_index::set(&offchain_leaf_key(leaf_index), &leaf.encode());
+
 					let mmr_root_log = MerkleMountainRangeRootLog::<T::Hash> {
 						prefix: PARENT_MMR_ROOT_LOG_ID,
 						parent_mmr_root: parent_mmr_root.into()
@@ -131,69 +192,215 @@ decl_module! {
 			} else {
 				error!("[hyperspace-header-mmr] FAILED to Calculate MMR");
 			}
+
+			<LastExtrinsicsRoot<T>>::put(Self::extrinsics_root());
 		}
 	}
 }
 
 impl<T: Config> Module<T> {
-	impl_rpc! {
-		pub fn gen_proof_rpc(
-			block_number_of_member_leaf: u64,
-			block_number_of_last_leaf: u64,
-		) -> RuntimeDispatchInfo<T::Hash> {
-			if block_number_of_member_leaf <= block_number_of_last_leaf {
-				let store = <ModuleMMRStore<T>>::default();
-				let mmr_size = leaf_index_to_mmr_size(block_number_of_last_leaf);
-				if mmr_size <= MMRCounter::get() {
-					let mmr = <MMR<_, MMRMerge<T>, _>>::new(mmr_size, store);
-					let pos = leaf_index_to_pos(block_number_of_member_leaf);
-
-					if let Ok(merkle_proof) = mmr.gen_proof(vec![pos]) {
-						return RuntimeDispatchInfo {
-							mmr_size,
-							proof: Proof(merkle_proof.proof_items().to_vec()),
-						};
-					}
-				}
+	/// Note the state root of the parent block, to commit to it in the leaf of the parent block.
+	///
+	/// Must be called by the runtime before `Executive::initialize_block` and
+	/// `Executive::execute_block`, while the state is still the one of the parent block.
+	pub fn note_parent_state_root() {
+		match T::Hash::decode(&mut &sp_io::storage::root()[..]) {
+			Ok(state_root) => <ParentStateRoot<T>>::put(state_root),
+			Err(e) => error!("[hyperspace-header-mmr] Invalid state root: {:?}", e),
+		}
+	}
+
+	/// The leaf of the parent block, `MMRLeaf::V0` until the roots of the parent block are
+	/// known.
+	fn parent_leaf(block_number: T::BlockNumber) -> MMRLeaf<T::BlockNumber, T::Hash> {
+		let parent_number = block_number - One::one();
+		let hash = <frame_system::Module<T>>::parent_hash();
+		let state_root = <ParentStateRoot<T>>::take();
+		// The genesis block has no extrinsic
+		let extrinsics_root = <LastExtrinsicsRoot<T>>::take().or_else(|| {
+			if parent_number.is_zero() {
+				Some(frame_system::extrinsics_data_root::<T::Hashing>(vec![]))
+			} else {
+				None
 			}
+		});
+
+		let (state_root, extrinsics_root) = match (state_root, extrinsics_root) {
+			(Some(state_root), Some(extrinsics_root)) => (state_root, extrinsics_root),
+			(state_root, extrinsics_root) => {
+				if !FirstV1Leaf::exists() {
+					return MMRLeaf::V0(hash);
+				}
+
+				// Once the leaves are `MMRLeaf::V1`, the runtime must note the state roots
+				error!(
+					"[hyperspace-header-mmr] Missing roots of block {:?}",
+					parent_number
+				);
 
-			RuntimeDispatchInfo {
-				mmr_size: 0,
-				proof: Proof(vec![]),
+				(
+					state_root.unwrap_or_default(),
+					extrinsics_root.unwrap_or_default(),
+				)
 			}
+		};
+
+		MMRLeaf::V1(LeafV1 {
+			block_number: parent_number,
+			hash,
+			parent_hash: if parent_number.is_zero() {
+				Default::default()
+			} else {
+				<frame_system::Module<T>>::block_hash(parent_number - One::one())
+			},
+			state_root,
+			extrinsics_root,
+			payload: T::LeafPayload::payload(),
+		})
+	}
+
+	/// The extrinsics root of the current block, once its extrinsics are applied.
+	fn extrinsics_root() -> T::Hash {
+		let extrinsics = (0..<frame_system::Module<T>>::extrinsic_count())
+			.map(<frame_system::Module<T>>::extrinsic_data)
+			.collect();
+
+		frame_system::extrinsics_data_root::<T::Hashing>(extrinsics)
+	}
+
+	/// Check one member leaf against the MMR of size `leaf_index_to_mmr_size(last_leaf)`, see
+	/// `gen_batch_proof_rpc`.
+	pub fn gen_proof_rpc(
+		block_number_of_member_leaf: u64,
+		block_number_of_last_leaf: u64,
+	) -> Result<RuntimeDispatchInfo<T::Hash>, ProofError> {
+		Self::gen_batch_proof_rpc(vec![block_number_of_member_leaf], block_number_of_last_leaf)
+	}
+
+	/// Check the member leaves against the MMR of size `leaf_index_to_mmr_size(last_leaf)`.
+	///
+	/// Only the peaks are kept on-chain, the proof needs the nodes of the off-chain indexing
+	/// storage the runtime can't read, so the valid leaves fail with `ProofError::Offchain`. The
+	/// node RPC `headerMMR_genBatchProof` proves them from the off-chain nodes.
+	pub fn gen_batch_proof_rpc(
+		block_numbers_of_member_leaves: Vec<u64>,
+		block_number_of_last_leaf: u64,
+	) -> Result<RuntimeDispatchInfo<T::Hash>, ProofError> {
+		let mmr_counter = MMRCounter::get();
+
+		if mmr_counter == 0 {
+			return Err(ProofError::MMRNotBuilt);
 		}
+		if block_numbers_of_member_leaves.is_empty() {
+			return Err(ProofError::GenProofFailed);
+		}
+		if leaf_index_to_mmr_size(block_number_of_last_leaf) > mmr_counter
+			|| block_numbers_of_member_leaves
+				.iter()
+				.any(|leaf| *leaf > block_number_of_last_leaf)
+		{
+			return Err(ProofError::LeafBeyondMMR);
+		}
+
+		Err(ProofError::Offchain)
 	}
 
-	// TODO: For future rpc calls
-	fn _find_parent_mmr_root(header: T::Header) -> Option<T::Hash> {
-		let id = OpaqueDigestItemId::Other;
+	/// The root of the MMR whose last leaf is `block_number_of_last_leaf`.
+	///
+	/// Only the nodes of the peaks are kept on-chain, so the root can only be computed for the
+	/// current MMR and read from `MMRRoots` for the latest `MMRRootHistoryDepth` leaves and the
+	/// ones kept by `migrations::keep_roots`. Older roots fail with an MMR error, they need the
+	/// nodes of the off-chain indexing storage.
+	pub fn get_root_rpc(block_number_of_last_leaf: u64) -> Result<T::Hash, ProofError> {
+		let mmr_counter = MMRCounter::get();
 
-		let filter_log = |MerkleMountainRangeRootLog {
-		                      prefix,
-		                      parent_mmr_root,
-		                  }: MerkleMountainRangeRootLog<T::Hash>| match prefix {
-			PARENT_MMR_ROOT_LOG_ID => Some(parent_mmr_root),
-			_ => None,
-		};
+		if mmr_counter == 0 {
+			return Err(ProofError::MMRNotBuilt);
+		}
+
+		let mmr_size = leaf_index_to_mmr_size(block_number_of_last_leaf);
+
+		if mmr_size > mmr_counter {
+			return Err(ProofError::LeafBeyondMMR);
+		}
+		if let Some(mmr_root) = <MMRRoots<T>>::get(block_number_of_last_leaf) {
+			return Ok(mmr_root);
+		}
+
+		let store = <ModuleMMRStore<T>>::default();
+		let mmr = <MMR<_, MMRMerge<T>, _>>::new(mmr_size, store);
 
-		// find the first other digest with the right prefix which converts to
-		// the right kind of mmr root log.
-		header
-			.digest()
-			.convert_first(|l| l.try_to(id).and_then(filter_log))
+		mmr.get_root().map_err(proof_error)
 	}
-}
 
-pub struct MMRMerge<T>(PhantomData<T>);
-impl<T: Config> merkle_mountain_range::Merge for MMRMerge<T> {
-	type Item = <T as frame_system::Config>::Hash;
+	/// The MMR node of the leaf of `leaf_index`, see `MMRLeaf::node`.
+	pub fn get_leaf_rpc(leaf_index: u64) -> Result<T::Hash, ProofError> {
+		let mmr_counter = MMRCounter::get();
+
+		if mmr_counter == 0 {
+			return Err(ProofError::MMRNotBuilt);
+		}
+
+		let pos = leaf_index_to_pos(leaf_index);
+
+		if pos >= mmr_counter {
+			return Err(ProofError::LeafBeyondMMR);
+		}
+
+		<Module<T>>::mmr_node_list(pos).ok_or(ProofError::Pruned)
+	}
+
+	/// The MMR size and its peaks, `(position, node)` in ascending order.
+	pub fn peaks_rpc() -> (u64, Vec<(u64, T::Hash)>) {
+		let mmr_size = MMRCounter::get();
+		let peaks = get_peaks(mmr_size)
+			.into_iter()
+			.filter_map(|pos| <MMRNodeList<T>>::get(pos).map(|peak| (pos, peak)))
+			.collect();
+
+		(mmr_size, peaks)
+	}
+
+	/// Verify a proof of the `(leaf index, leaf)` pairs against `root`, see
+	/// `hyperspace_header_mmr_primitives::verify_proof`.
+	pub fn verify_proof_rpc(
+		root: T::Hash,
+		leaves: Vec<(u64, T::Hash)>,
+		proof: Vec<T::Hash>,
+		mmr_size: u64,
+	) -> bool {
+		hyperspace_header_mmr_primitives::verify_proof::<T::Hashing>(root, leaves, proof, mmr_size)
+	}
 
-	fn merge(lhs: &Self::Item, rhs: &Self::Item) -> Self::Item {
-		let encodable = (lhs, rhs);
-		<T as frame_system::Config>::Hashing::hash_of(&encodable)
+	/// Verify that the MMR of `old_root` is a prefix of the MMR of `new_root`, see
+	/// `hyperspace_header_mmr_primitives::verify_consistency_proof`.
+	pub fn verify_consistency_proof_rpc(
+		old_root: T::Hash,
+		old_mmr_size: u64,
+		new_root: T::Hash,
+		new_mmr_size: u64,
+		proof: ConsistencyProof<T::Hash>,
+	) -> bool {
+		verify_consistency_proof::<MMRMerge<T>>(
+			old_root,
+			old_mmr_size,
+			new_root,
+			new_mmr_size,
+			proof,
+		)
 	}
 }
 
+fn proof_error(e: merkle_mountain_range::Error) -> ProofError {
+	match e {
+		merkle_mountain_range::Error::InconsistentStore => ProofError::Pruned,
+		_ => ProofError::GenProofFailed,
+	}
+}
+
+pub type MMRMerge<T> =
+	hyperspace_header_mmr_primitives::MMRMerge<<T as frame_system::Config>::Hashing>;
+
 pub struct ModuleMMRStore<T>(PhantomData<T>);
 impl<T> Default for ModuleMMRStore<T> {
 	fn default() -> Self {
@@ -212,9 +419,23 @@ impl<T: Config> MMRStore<T::Hash> for ModuleMMRStore<T> {
 			Err(merkle_mountain_range::Error::InconsistentStore)?;
 		}
 		let elems_len = elems.len() as u64;
+		let peaks_before = get_peaks(mmr_count);
+		let peaks_after = get_peaks(mmr_count + elems_len);
 
+		// Every node goes off-chain, only the peaks stay on-chain
 		for (i, elem) in elems.into_iter().enumerate() {
-			<MMRNodeList<T>>::insert(mmr_count + i as u64, elem);
+			let pos = mmr_count + i as u64;
+
+			sp_io::offchain_index::set(&offchain_key(pos), &elem.encode());
+
+			if peaks_after.binary_search(&pos).is_ok() {
+				<MMRNodeList<T>>::insert(pos, elem);
+			}
+		}
+		for pos in peaks_before {
+			if peaks_after.binary_search(&pos).is_err() {
+				<MMRNodeList<T>>::remove(pos);
+			}
 		}
 
 		// increment counter
@@ -224,16 +445,92 @@ impl<T: Config> MMRStore<T::Hash> for ModuleMMRStore<T> {
 	}
 }
 
-impl<T: Config> MMRT<T::BlockNumber, T::Hash> for Module<T> {
-	fn get_root(block_number: T::BlockNumber) -> Option<T::Hash> {
-		let store = <ModuleMMRStore<T>>::default();
-		let mmr_size = leaf_index_to_mmr_size(block_number.saturated_into::<u64>() as _);
-		let mmr = <MMR<_, MMRMerge<T>, _>>::new(mmr_size, store);
+// A value placed in storage that represents the current version of the HeaderMMR storage. This
+// value is used by the `on_runtime_upgrade` logic to determine whether we run storage migration
+// logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+	/// Every node in `MMRNodeList`.
+	V1_0_0,
+	/// Only the peaks in `MMRNodeList`, the nodes in the off-chain indexing storage.
+	V2_0_0,
+}
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V2_0_0
+	}
+}
+
+pub mod migrations {
+	use super::*;
 
-		if let Ok(mmr_root) = mmr.get_root() {
-			Some(mmr_root)
+	/// Keep the roots of the MMRs whose last leaves are `leaves` in `MMRRoots`, e.g. the ones
+	/// still to be signed by the relay authorities.
+	///
+	/// Only runs against a `Releases::V1_0_0` storage, from an `OnRuntimeUpgrade` run before the
+	/// one of this pallet, while every node is still on-chain. Unlike the latest roots, the kept
+	/// ones are never removed.
+	pub fn keep_roots<T: Config>(leaves: impl IntoIterator<Item = u64>) -> Weight {
+		if StorageVersion::exists() {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut weight = T::DbWeight::get().reads(2);
+
+		for leaf_index in leaves {
+			if <MMRRoots<T>>::contains_key(leaf_index) {
+				continue;
+			}
+
+			let mmr_size = leaf_index_to_mmr_size(leaf_index);
+
+			if let Ok(mmr_root) = Module::<T>::get_root_rpc(leaf_index) {
+				<MMRRoots<T>>::insert(leaf_index, mmr_root);
+			}
+
+			weight = weight.saturating_add(
+				T::DbWeight::get().reads_writes(2 + get_peaks(mmr_size).len() as u64, 1),
+			);
+		}
+
+		weight
+	}
+
+	/// Move a batch of `MMRNodeList` nodes, except the peaks, to the off-chain indexing storage.
+	///
+	/// The nodes are only kept by the nodes importing this block with off-chain indexing enabled,
+	/// the others rebuild them from the block hashes.
+	pub fn prune_nodes<T: Config>(cursor: u64) -> Weight {
+		let mmr_size = MMRCounter::get();
+		let peaks = get_peaks(mmr_size);
+		let end = mmr_size.min(cursor.saturating_add(PRUNING_BATCH));
+		let mut pruned = 0;
+
+		for pos in cursor..end {
+			if peaks.binary_search(&pos).is_ok() {
+				continue;
+			}
+
+			if let Some(node) = <MMRNodeList<T>>::take(pos) {
+				sp_io::offchain_index::set(&offchain_key(pos), &node.encode());
+
+				pruned += 1;
+			}
+		}
+
+		if end == mmr_size {
+			PruningCursor::kill();
 		} else {
-			None
+			PruningCursor::put(end);
 		}
+
+		T::DbWeight::get().reads_writes(2 + end - cursor, 1 + pruned)
+	}
+}
+
+impl<T: Config> MMRT<T::BlockNumber, T::Hash> for Module<T> {
+	/// `None` for the roots `get_root_rpc` can't serve anymore.
+	fn get_root(block_number: T::BlockNumber) -> Option<T::Hash> {
+		Self::get_root_rpc(block_number.saturated_into()).ok()
 	}
 }
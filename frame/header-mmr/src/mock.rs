@@ -21,13 +21,14 @@
 #![cfg(test)]
 
 // --- crates ---
-use codec::Encode;
+use codec::{Decode, Encode};
 // --- substrate ---
+use frame_support::{parameter_types, traits::OnFinalize};
 use frame_system::mocking::*;
-use sp_core::H256;
+use sp_core::{offchain::testing::TestPersistentOffchainDB, H256};
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, Header as HeaderT, IdentityLookup},
 	DigestItem,
 };
 // --- hyperspace ---
@@ -51,7 +52,7 @@ impl frame_system::Config for Test {
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
 	type Event = ();
-	type BlockHashCount = ();
+	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
 	type AccountData = ();
@@ -61,7 +62,17 @@ impl frame_system::Config for Test {
 	type SS58Prefix = ();
 }
 
-impl Config for Test {}
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+parameter_types! {
+	pub const MMRRootHistoryDepth: u64 = 10;
+}
+impl Config for Test {
+	type MMRRootHistoryDepth = MMRRootHistoryDepth;
+	type LeafPayload = ();
+}
 
 frame_support::construct_runtime! {
 	pub enum Test
@@ -100,6 +111,61 @@ pub fn initialize_block(number: u64, parent_hash: H256) {
 	);
 }
 
+/// Finalize the blocks `1..=n`, return their headers.
+pub fn finalize_blocks(n: u64) -> Vec<Header> {
+	finalize_blocks_with(n, |_| false)
+}
+
+/// Finalize the blocks `1..=n`, noting the parent state root like the runtime for the blocks
+/// `i` where `note_state_root(i)`, return their headers.
+pub fn finalize_blocks_with(n: u64, note_state_root: impl Fn(u64) -> bool) -> Vec<Header> {
+	let mut headers = vec![];
+	let mut parent_hash = Default::default();
+
+	for i in 1..=n {
+		if note_state_root(i) {
+			HeaderMMR::note_parent_state_root();
+		}
+
+		initialize_block(i, parent_hash);
+		HeaderMMR::on_finalize(i);
+
+		let header = System::finalize();
+
+		parent_hash = header.hash();
+		headers.push(header);
+	}
+
+	headers
+}
+
+/// The leaf of `leaf_index` in the off-chain indexing storage, once persisted.
+pub fn offchain_leaf(
+	offchain_db: &TestPersistentOffchainDB,
+	leaf_index: u64,
+) -> Option<MMRLeaf<u64, H256>> {
+	offchain_db
+		.get(&hyperspace_header_mmr_primitives::offchain_leaf_key(
+			leaf_index,
+		))
+		.map(|leaf| Decode::decode(&mut &leaf[..]).unwrap())
+}
+
+/// The MMR nodes of the off-chain indexing storage, once persisted.
+pub struct OffchainMMRStore(pub TestPersistentOffchainDB);
+impl MMRStore<H256> for OffchainMMRStore {
+	fn get_elem(&self, pos: u64) -> MMRResult<Option<H256>> {
+		Ok(self
+			.0
+			.get(&hyperspace_header_mmr_primitives::offchain_key(pos))
+			.map(|node| H256::decode(&mut &node[..]).unwrap()))
+	}
+
+	fn append(&mut self, _: u64, _: Vec<H256>) -> MMRResult<()> {
+		unimplemented!("Read only")
+	}
+}
+
 // -- helpers ---
 pub const HEADERS_N_ROOTS: [(&str, &str); 10] = [
 	(
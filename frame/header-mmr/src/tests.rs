@@ -20,11 +20,17 @@
 
 #![cfg(test)]
 
+// --- crates ---
+use codec::Decode;
 // --- substrate ---
-use frame_support::traits::OnFinalize;
-use sp_runtime::testing::{Digest, H256};
+use frame_support::traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade};
+use sp_runtime::{
+	testing::{Digest, H256},
+	traits::{BlakeTwo256, Hash, Header},
+};
 // --- hyperspace ---
 use crate::{mock::*, *};
+use hyperspace_header_mmr_primitives::gen_consistency_proof;
 
 use merkle_mountain_range::{leaf_index_to_pos, Merge};
 
@@ -49,47 +55,116 @@ fn first_header_mmr() {
 
 #[test]
 fn test_insert_header() {
-	new_test_ext().execute_with(|| {
-		initialize_block(1, Default::default());
+	let mut ext = new_test_ext();
+	let headers = ext.execute_with(|| finalize_blocks(29));
 
-		HeaderMMR::on_finalize(1);
+	ext.persist_offchain_overlay();
+
+	let store = OffchainMMRStore(ext.offchain_db());
+	let h1 = 11 as u64;
+	let h2 = 19 as u64;
+
+	let prove_elem = headers[h1 as usize - 1].hash();
 
-		let mut headers = vec![];
+	let pos = 19;
+	assert_eq!(pos, leaf_index_to_pos(h1));
+	assert_eq!(prove_elem, store.get_elem(pos).unwrap().unwrap());
 
-		let mut header = System::finalize();
-		headers.push(header.clone());
+	let parent_mmr_root =
+		find_parent_mmr_root(&headers[h2 as usize - 1]).expect("Header mmr get failed");
 
-		for i in 2..30 {
-			initialize_block(i, header.hash());
+	let mmr = MMR::<_, MMRMerge<Test>, _>::new(leaf_index_to_mmr_size(h2 - 1), store);
+
+	assert_eq!(mmr.get_root().expect("Get Root Failed"), parent_mmr_root);
+
+	let proof = mmr.gen_proof(vec![pos]).expect("gen proof");
+
+	let result = proof
+		.verify(parent_mmr_root, vec![(pos, prove_elem)])
+		.expect("verify");
+	assert!(result);
+}
 
-			HeaderMMR::on_finalize(i);
-			header = System::finalize();
-			headers.push(header.clone());
+#[test]
+fn should_keep_only_peaks_on_chain() {
+	let mut ext = new_test_ext();
+
+	ext.execute_with(|| {
+		finalize_blocks(29);
+
+		let mmr_size = HeaderMMR::mmr_counter();
+		let mut positions = <MMRNodeList<Test>>::iter()
+			.map(|(pos, _)| pos)
+			.collect::<Vec<_>>();
+
+		positions.sort_unstable();
+
+		assert_eq!(mmr_size, leaf_index_to_mmr_size(28));
+		assert_eq!(positions, get_peaks(mmr_size));
+
+		// The latest `MMRRootHistoryDepth` roots
+		assert!(HeaderMMR::mmr_root_of(18).is_none());
+		assert!((19..29).all(|leaf| HeaderMMR::mmr_root_of(leaf).is_some()));
+	});
+
+	ext.persist_offchain_overlay();
+
+	let store = OffchainMMRStore(ext.offchain_db());
+
+	ext.execute_with(|| {
+		for pos in 0..HeaderMMR::mmr_counter() {
+			assert!(store.get_elem(pos).unwrap().is_some());
+		}
+	});
+}
+
+#[test]
+fn should_migrate_to_peaks_only_storage() {
+	let mut ext = new_test_ext();
+
+	ext.execute_with(|| {
+		finalize_blocks(29);
+	});
+	ext.persist_offchain_overlay();
+
+	let store = OffchainMMRStore(ext.offchain_db());
+
+	ext.execute_with(|| {
+		let mmr_size = HeaderMMR::mmr_counter();
+
+		// Back to the `Releases::V1_0_0` storage, with every node on-chain
+		for pos in 0..mmr_size {
+			<MMRNodeList<Test>>::insert(pos, store.get_elem(pos).unwrap().unwrap());
 		}
+		StorageVersion::kill();
+
+		let kept_root = HeaderMMR::get_root_rpc(5).unwrap();
 
-		let h1 = 11 as u64;
-		let h2 = 19 as u64;
+		migrations::keep_roots::<Test>(vec![5]);
+		HeaderMMR::on_runtime_upgrade();
 
-		let prove_elem = headers[h1 as usize - 1].hash();
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+		assert_eq!(HeaderMMR::pruning_cursor(), Some(0));
 
-		let pos = 19;
-		assert_eq!(pos, leaf_index_to_pos(h1));
-		assert_eq!(prove_elem, HeaderMMR::mmr_node_list(pos).unwrap());
+		HeaderMMR::on_initialize(30);
 
-		let parent_mmr_root = HeaderMMR::_find_parent_mmr_root(headers[h2 as usize - 1].clone())
-			.expect("Header mmr get failed");
+		let mut positions = <MMRNodeList<Test>>::iter()
+			.map(|(pos, _)| pos)
+			.collect::<Vec<_>>();
 
-		let store = <ModuleMMRStore<Test>>::default();
-		let mmr = MMR::<_, MMRMerge<Test>, _>::new(leaf_index_to_mmr_size(h2 - 1), store);
+		positions.sort_unstable();
 
-		assert_eq!(mmr.get_root().expect("Get Root Failed"), parent_mmr_root);
+		assert_eq!(positions, get_peaks(mmr_size));
+		assert_eq!(HeaderMMR::pruning_cursor(), None);
+		// Only the kept and the latest roots are left once the nodes are pruned
+		assert_eq!(HeaderMMR::get_root_rpc(5), Ok(kept_root));
+		assert!(HeaderMMR::get_root_rpc(6).is_err());
+		assert!(HeaderMMR::get_root_rpc(19).is_ok());
 
-		let proof = mmr.gen_proof(vec![pos]).expect("gen proof");
+		// Once migrated
+		HeaderMMR::on_runtime_upgrade();
 
-		let result = proof
-			.verify(parent_mmr_root, vec![(pos, prove_elem)])
-			.expect("verify");
-		assert!(result);
+		assert_eq!(HeaderMMR::pruning_cursor(), None);
 	});
 }
 
@@ -151,3 +226,318 @@ fn test_mmr_merge() {
 		"0x3aafcc7fe12cb8fad62c261458f1c19dba0a3756647fa4e8bff6e248883938be"
 	);
 }
+
+#[test]
+fn gen_batch_proof_should_work() {
+	let mut ext = new_test_ext();
+
+	ext.execute_with(|| {
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![0], 0),
+			Err(ProofError::MMRNotBuilt)
+		);
+
+		finalize_blocks(1);
+
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![0], 0),
+			Err(ProofError::Offchain)
+		);
+	});
+
+	let mut ext = new_test_ext();
+	let headers = ext.execute_with(|| finalize_blocks(29));
+	let last_leaf = 19;
+
+	ext.execute_with(|| {
+		// The nodes below the peaks are only off-chain
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![11, 3, 17, 3], last_leaf),
+			Err(ProofError::Offchain)
+		);
+		assert_eq!(
+			HeaderMMR::gen_proof_rpc(11, last_leaf),
+			Err(ProofError::Offchain)
+		);
+
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![11, 20], last_leaf),
+			Err(ProofError::LeafBeyondMMR)
+		);
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![11], 100),
+			Err(ProofError::LeafBeyondMMR)
+		);
+		assert_eq!(
+			HeaderMMR::gen_batch_proof_rpc(vec![], last_leaf),
+			Err(ProofError::GenProofFailed)
+		);
+	});
+	ext.persist_offchain_overlay();
+
+	let parent_mmr_root =
+		find_parent_mmr_root(&headers[last_leaf as usize]).expect("Header mmr get failed");
+	let mmr_size = leaf_index_to_mmr_size(last_leaf);
+	let proof = MMR::<_, MMRMerge<Test>, _>::new(mmr_size, OffchainMMRStore(ext.offchain_db()))
+		.gen_proof(vec![leaf_index_to_pos(3), leaf_index_to_pos(11)])
+		.expect("gen proof");
+
+	ext.execute_with(|| {
+		let leaves = vec![(11, headers[10].hash()), (3, headers[2].hash())];
+
+		assert!(HeaderMMR::verify_proof_rpc(
+			parent_mmr_root,
+			leaves,
+			proof.proof_items().to_vec(),
+			mmr_size
+		));
+	});
+}
+
+#[test]
+fn get_root_get_leaf_and_verify_proof_should_work() {
+	let mut ext = new_test_ext();
+
+	ext.execute_with(|| {
+		assert_eq!(HeaderMMR::get_root_rpc(0), Err(ProofError::MMRNotBuilt));
+		assert_eq!(HeaderMMR::get_leaf_rpc(0), Err(ProofError::MMRNotBuilt));
+	});
+
+	let headers = ext.execute_with(|| finalize_blocks(29));
+	let last_leaf = 19;
+	let mmr_size = leaf_index_to_mmr_size(last_leaf);
+
+	ext.persist_offchain_overlay();
+
+	let proof = MMR::<_, MMRMerge<Test>, _>::new(mmr_size, OffchainMMRStore(ext.offchain_db()))
+		.gen_proof(vec![leaf_index_to_pos(3), leaf_index_to_pos(11)])
+		.expect("gen proof")
+		.proof_items()
+		.to_vec();
+
+	ext.execute_with(|| {
+		let root = HeaderMMR::get_root_rpc(last_leaf).unwrap();
+
+		assert_eq!(
+			Some(root),
+			find_parent_mmr_root(&headers[last_leaf as usize])
+		);
+		// Beyond the root history, with its nodes pruned
+		assert_eq!(HeaderMMR::get_root_rpc(5), Err(ProofError::Pruned));
+		assert_eq!(HeaderMMR::get_root_rpc(100), Err(ProofError::LeafBeyondMMR));
+		// The last leaf is a peak
+		assert_eq!(HeaderMMR::get_leaf_rpc(28), Ok(headers[27].hash()));
+		assert_eq!(HeaderMMR::get_leaf_rpc(11), Err(ProofError::Pruned));
+		assert_eq!(HeaderMMR::get_leaf_rpc(100), Err(ProofError::LeafBeyondMMR));
+
+		let leaves = vec![(11, headers[10].hash()), (3, headers[2].hash())];
+
+		assert!(HeaderMMR::verify_proof_rpc(
+			root,
+			leaves.clone(),
+			proof.clone(),
+			mmr_size
+		));
+		assert!(!HeaderMMR::verify_proof_rpc(
+			root,
+			vec![(11, headers[10].hash()), (3, headers[3].hash())],
+			proof.clone(),
+			mmr_size
+		));
+		assert!(!HeaderMMR::verify_proof_rpc(
+			root,
+			vec![(11, headers[10].hash()), (11, headers[11].hash())],
+			proof.clone(),
+			mmr_size
+		));
+		assert!(!HeaderMMR::verify_proof_rpc(
+			root,
+			vec![],
+			proof.clone(),
+			mmr_size
+		));
+		assert!(!HeaderMMR::verify_proof_rpc(
+			HeaderMMR::get_root_rpc(last_leaf + 1).unwrap(),
+			leaves,
+			proof,
+			mmr_size
+		));
+	});
+}
+
+#[test]
+fn leaves_should_commit_to_the_headers() {
+	let mut ext = new_test_ext();
+	let (genesis_state_root, headers) = ext.execute_with(|| {
+		let genesis_state_root = H256::decode(&mut &sp_io::storage::root()[..]).unwrap();
+
+		(genesis_state_root, finalize_blocks_with(5, |_| true))
+	});
+
+	ext.execute_with(|| assert_eq!(HeaderMMR::first_v1_leaf(), Some(0)));
+	ext.persist_offchain_overlay();
+
+	let offchain_db = ext.offchain_db();
+
+	// The parent hash of the first block is the genesis hash in the tests
+	assert_eq!(
+		offchain_leaf(&offchain_db, 0),
+		Some(MMRLeaf::V1(LeafV1 {
+			block_number: 0,
+			hash: Default::default(),
+			parent_hash: Default::default(),
+			state_root: genesis_state_root,
+			extrinsics_root: frame_system::extrinsics_data_root::<BlakeTwo256>(vec![]),
+			payload: None,
+		}))
+	);
+
+	// The leaf of the last block is pushed by the next block
+	for header in &headers[..4] {
+		let leaf_index = *header.number();
+		let leaf = MMRLeaf::V1(LeafV1 {
+			block_number: leaf_index,
+			hash: header.hash(),
+			parent_hash: *header.parent_hash(),
+			state_root: *header.state_root(),
+			extrinsics_root: *header.extrinsics_root(),
+			payload: None,
+		});
+		let abi = leaf.abi_encode();
+
+		assert_eq!(abi.len(), 7 * 32);
+		assert_eq!(abi[63], leaf_index as u8);
+		assert_eq!(&abi[64..96], header.hash().as_bytes());
+		assert_eq!(offchain_leaf(&offchain_db, leaf_index), Some(leaf.clone()));
+		assert_eq!(
+			OffchainMMRStore(offchain_db.clone())
+				.get_elem(leaf_index_to_pos(leaf_index))
+				.unwrap(),
+			Some(BlakeTwo256::hash(&abi))
+		);
+	}
+}
+
+#[test]
+fn v0_leaves_should_stay_provable() {
+	let mut ext = new_test_ext();
+	// The runtime notes the parent state roots since the block 4
+	let headers = ext.execute_with(|| finalize_blocks_with(8, |i| i >= 4));
+	let last_leaf = 7;
+
+	ext.execute_with(|| assert_eq!(HeaderMMR::first_v1_leaf(), Some(3)));
+	ext.persist_offchain_overlay();
+
+	let offchain_db = ext.offchain_db();
+
+	assert_eq!(
+		offchain_leaf(&offchain_db, 2),
+		Some(MMRLeaf::V0(headers[1].hash()))
+	);
+	assert!(matches!(
+		offchain_leaf(&offchain_db, 3),
+		Some(MMRLeaf::V1(_))
+	));
+
+	let leaves = vec![1, 5]
+		.into_iter()
+		.map(|leaf_index| {
+			let leaf = offchain_leaf(&offchain_db, leaf_index).unwrap();
+
+			(leaf_index, leaf.node::<BlakeTwo256>())
+		})
+		.collect::<Vec<_>>();
+	let mmr_size = leaf_index_to_mmr_size(last_leaf);
+	let proof = MMR::<_, MMRMerge<Test>, _>::new(mmr_size, OffchainMMRStore(offchain_db))
+		.gen_proof(vec![leaf_index_to_pos(1), leaf_index_to_pos(5)])
+		.expect("gen proof");
+
+	assert_eq!(leaves[0].1, headers[0].hash());
+
+	ext.execute_with(|| {
+		assert!(HeaderMMR::verify_proof_rpc(
+			find_parent_mmr_root(&headers[last_leaf as usize]).unwrap(),
+			leaves,
+			proof.proof_items().to_vec(),
+			mmr_size
+		));
+	});
+}
+
+#[test]
+fn consistency_proof_should_work() {
+	let mut ext = new_test_ext();
+	let headers = ext.execute_with(|| finalize_blocks(30));
+
+	ext.persist_offchain_overlay();
+
+	let store = OffchainMMRStore(ext.offchain_db());
+	let mmr_root = |last_leaf: u64| find_parent_mmr_root(&headers[last_leaf as usize]).unwrap();
+
+	ext.execute_with(|| {
+		for new_last_leaf in 0..29 {
+			for old_last_leaf in 0..=new_last_leaf {
+				let old_mmr_size = leaf_index_to_mmr_size(old_last_leaf);
+				let new_mmr_size = leaf_index_to_mmr_size(new_last_leaf);
+				let proof = gen_consistency_proof(&store, old_mmr_size, new_mmr_size).unwrap();
+
+				assert!(HeaderMMR::verify_consistency_proof_rpc(
+					mmr_root(old_last_leaf),
+					old_mmr_size,
+					mmr_root(new_last_leaf),
+					new_mmr_size,
+					proof
+				));
+			}
+		}
+
+		let (old_mmr_size, new_mmr_size) = (leaf_index_to_mmr_size(10), leaf_index_to_mmr_size(27));
+		let proof = gen_consistency_proof(&store, old_mmr_size, new_mmr_size).unwrap();
+		let verify = |old_root, old_mmr_size, new_root, proof| {
+			HeaderMMR::verify_consistency_proof_rpc(
+				old_root,
+				old_mmr_size,
+				new_root,
+				new_mmr_size,
+				proof,
+			)
+		};
+
+		assert!(verify(
+			mmr_root(10),
+			old_mmr_size,
+			mmr_root(27),
+			proof.clone()
+		));
+		// Another old MMR
+		assert!(!verify(
+			mmr_root(11),
+			old_mmr_size,
+			mmr_root(27),
+			proof.clone()
+		));
+		assert!(!verify(
+			mmr_root(10),
+			old_mmr_size + 1,
+			mmr_root(27),
+			proof.clone()
+		));
+		// A tampered or an incomplete proof
+		let mut tampered = proof.clone();
+		tampered.items[0] = H256::repeat_byte(1);
+		assert!(!verify(mmr_root(10), old_mmr_size, mmr_root(27), tampered));
+
+		let mut incomplete = proof.clone();
+		incomplete.items.pop();
+		assert!(!verify(
+			mmr_root(10),
+			old_mmr_size,
+			mmr_root(27),
+			incomplete
+		));
+
+		let mut extended = proof;
+		extended.items.push(H256::repeat_byte(1));
+		assert!(!verify(mmr_root(10), old_mmr_size, mmr_root(27), extended));
+	});
+}
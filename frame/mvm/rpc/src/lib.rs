@@ -1,9 +0,0 @@
-mod eth;
-mod eth_pubsub;
-mod net;
-mod web3;
-
-pub use eth::{EthApi, EthApiServer, EthFilterApi};
-pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer};
-pub use net::{NetApi, NetApiServer};
-pub use web3::{Web3Api, Web3ApiServer};
@@ -49,6 +49,8 @@ pub enum EthereumError {
 	MerkleProofMismatch(&'static str),
 	Rlp(&'static str),
 	InvalidReceiptProof,
+	ProofOfWorkEnded,
+	InvalidExtraData,
 }
 
 impl From<EthereumError> for &str {
@@ -63,6 +65,8 @@ impl From<EthereumError> for &str {
 			MerkleProofMismatch(msg) => msg,
 			Rlp(msg) => msg,
 			InvalidReceiptProof => "EthereumReceipt Proof - INVALID",
+			ProofOfWorkEnded => "Proof Of Work - ENDED",
+			InvalidExtraData => "Extra Data - INVALID",
 		}
 	}
 }
@@ -17,7 +17,7 @@
 // along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
 
 // --- crates ---
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, Error as CodecError, Input, Output};
 #[cfg(any(feature = "deserialize", test))]
 use serde::Deserialize;
 // --- github ---
@@ -41,8 +41,19 @@ enum Seal {
 	Without,
 }
 
+/// Flags of the SCALE encoded `EthereumHeader::hash`, the headers without fork fields are encoded
+/// like the headers before London so the stored headers still decode.
+const HASH_FLAG: u8 = 0b01;
+const FORK_FIELDS_FLAG: u8 = 0b10;
+
+/// The Ethash seal, the mix hash and the nonce, the proof of stake keeps them as `prevRandao` and
+/// a zero nonce.
+const ETHASH_SEAL_LEN: usize = 2;
+/// The fields of the header before the seal.
+const BASE_FIELDS_LEN: usize = 13;
+
 #[cfg_attr(any(feature = "deserialize", test), derive(serde::Deserialize))]
-#[derive(Clone, Eq, Encode, Decode, RuntimeDebug)]
+#[derive(Clone, Eq, RuntimeDebug)]
 pub struct EthereumHeader {
 	pub parent_hash: H256,
 	pub timestamp: u64,
@@ -79,6 +90,21 @@ pub struct EthereumHeader {
 	)]
 	pub seal: Vec<Bytes>,
 	pub hash: Option<H256>,
+	/// Since London, EIP-1559.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub base_fee_per_gas: Option<U256>,
+	/// Since Shanghai, EIP-4895.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub withdrawals_root: Option<H256>,
+	/// Since Cancun, EIP-4844.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub blob_gas_used: Option<u64>,
+	/// Since Cancun, EIP-4844.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub excess_blob_gas: Option<u64>,
+	/// Since Cancun, EIP-4788.
+	#[cfg_attr(any(feature = "deserialize", test), serde(default))]
+	pub parent_beacon_block_root: Option<H256>,
 }
 impl EthereumHeader {
 	#[cfg(any(feature = "deserialize", test))]
@@ -176,6 +202,21 @@ impl EthereumHeader {
 			} else if s.starts_with("\"transactionsRoot") {
 				eth_header.transactions_root =
 					array_bytes::hex2array_unchecked!(parse_value_unchecked(s), 32).into();
+			} else if s.starts_with("\"baseFeePerGas") {
+				eth_header.base_fee_per_gas = Some(
+					U256::from_str(parse_value_unchecked(s).trim_start_matches("0x"))
+						.unwrap_or_default(),
+				);
+			} else if s.starts_with("\"withdrawalsRoot") {
+				eth_header.withdrawals_root =
+					Some(array_bytes::hex2array_unchecked!(parse_value_unchecked(s), 32).into());
+			} else if s.starts_with("\"blobGasUsed") {
+				eth_header.blob_gas_used = Some(str_to_u64(parse_value_unchecked(s)));
+			} else if s.starts_with("\"excessBlobGas") {
+				eth_header.excess_blob_gas = Some(str_to_u64(parse_value_unchecked(s)));
+			} else if s.starts_with("\"parentBeaconBlockRoot") {
+				eth_header.parent_beacon_block_root =
+					Some(array_bytes::hex2array_unchecked!(parse_value_unchecked(s), 32).into());
 			}
 		}
 		eth_header.seal = vec![rlp::encode(&mix_hash), rlp::encode(&nonce)];
@@ -201,6 +242,11 @@ impl Default for EthereumHeader {
 			difficulty: U256::default(),
 			seal: vec![],
 			hash: None,
+			base_fee_per_gas: None,
+			withdrawals_root: None,
+			blob_gas_used: None,
+			excess_blob_gas: None,
+			parent_beacon_block_root: None,
 		}
 	}
 }
@@ -229,10 +275,96 @@ impl PartialEq for EthereumHeader {
 			&& self.gas_limit == c.gas_limit
 			&& self.difficulty == c.difficulty
 			&& self.seal == c.seal
+			&& self.base_fee_per_gas == c.base_fee_per_gas
+			&& self.withdrawals_root == c.withdrawals_root
+			&& self.blob_gas_used == c.blob_gas_used
+			&& self.excess_blob_gas == c.excess_blob_gas
+			&& self.parent_beacon_block_root == c.parent_beacon_block_root
+	}
+}
+impl Encode for EthereumHeader {
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		self.parent_hash.encode_to(dest);
+		self.timestamp.encode_to(dest);
+		self.number.encode_to(dest);
+		self.author.encode_to(dest);
+		self.transactions_root.encode_to(dest);
+		self.uncles_hash.encode_to(dest);
+		self.extra_data.encode_to(dest);
+		self.state_root.encode_to(dest);
+		self.receipts_root.encode_to(dest);
+		self.log_bloom.encode_to(dest);
+		self.gas_used.encode_to(dest);
+		self.gas_limit.encode_to(dest);
+		self.difficulty.encode_to(dest);
+		self.seal.encode_to(dest);
+
+		let has_fork_fields = self.fork_fields_len() != 0;
+		let mut flags = 0;
+
+		if self.hash.is_some() {
+			flags |= HASH_FLAG;
+		}
+		if has_fork_fields {
+			flags |= FORK_FIELDS_FLAG;
+		}
+
+		dest.push_byte(flags);
+
+		if let Some(hash) = &self.hash {
+			hash.encode_to(dest);
+		}
+		if has_fork_fields {
+			self.base_fee_per_gas.encode_to(dest);
+			self.withdrawals_root.encode_to(dest);
+			self.blob_gas_used.encode_to(dest);
+			self.excess_blob_gas.encode_to(dest);
+			self.parent_beacon_block_root.encode_to(dest);
+		}
+	}
+}
+impl Decode for EthereumHeader {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+		let mut header = EthereumHeader {
+			parent_hash: Decode::decode(input)?,
+			timestamp: Decode::decode(input)?,
+			number: Decode::decode(input)?,
+			author: Decode::decode(input)?,
+			transactions_root: Decode::decode(input)?,
+			uncles_hash: Decode::decode(input)?,
+			extra_data: Decode::decode(input)?,
+			state_root: Decode::decode(input)?,
+			receipts_root: Decode::decode(input)?,
+			log_bloom: Decode::decode(input)?,
+			gas_used: Decode::decode(input)?,
+			gas_limit: Decode::decode(input)?,
+			difficulty: Decode::decode(input)?,
+			seal: Decode::decode(input)?,
+			..Default::default()
+		};
+		let flags = input.read_byte()?;
+
+		if flags & !(HASH_FLAG | FORK_FIELDS_FLAG) != 0 {
+			return Err("Invalid EthereumHeader hash flags".into());
+		}
+		if flags & HASH_FLAG != 0 {
+			header.hash = Some(Decode::decode(input)?);
+		}
+		if flags & FORK_FIELDS_FLAG != 0 {
+			header.base_fee_per_gas = Decode::decode(input)?;
+			header.withdrawals_root = Decode::decode(input)?;
+			header.blob_gas_used = Decode::decode(input)?;
+			header.excess_blob_gas = Decode::decode(input)?;
+			header.parent_beacon_block_root = Decode::decode(input)?;
+		}
+
+		Ok(header)
 	}
 }
 impl Decodable for EthereumHeader {
 	fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+		let item_count = r.item_count()?;
+		let fork_field_at = |i| BASE_FIELDS_LEN + ETHASH_SEAL_LEN + i;
 		let mut blockheader = EthereumHeader {
 			parent_hash: r.val_at(0)?,
 			uncles_hash: r.val_at(1)?,
@@ -249,9 +381,19 @@ impl Decodable for EthereumHeader {
 			extra_data: r.val_at(12)?,
 			seal: vec![],
 			hash: keccak(r.as_raw()).into(),
+			base_fee_per_gas: optional_val_at(r, item_count, fork_field_at(0))?,
+			withdrawals_root: optional_val_at(r, item_count, fork_field_at(1))?,
+			blob_gas_used: optional_val_at(r, item_count, fork_field_at(2))?,
+			excess_blob_gas: optional_val_at(r, item_count, fork_field_at(3))?,
+			parent_beacon_block_root: optional_val_at(r, item_count, fork_field_at(4))?,
 		};
 
-		for i in 13..r.item_count()? {
+		if item_count > fork_field_at(4) + 1 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		// The fork fields follow the Ethash seal
+		for i in BASE_FIELDS_LEN..item_count.min(fork_field_at(0)) {
 			blockheader.seal.push(r.at(i)?.as_raw().to_vec())
 		}
 
@@ -264,6 +406,19 @@ impl Encodable for EthereumHeader {
 	}
 }
 
+/// The item at `i` of the list if any.
+fn optional_val_at<T: Decodable>(
+	r: &Rlp,
+	item_count: usize,
+	i: usize,
+) -> Result<Option<T>, DecoderError> {
+	if i < item_count {
+		r.val_at(i).map(Some)
+	} else {
+		Ok(None)
+	}
+}
+
 /// Alter value of given field, reset memoised hash if changed.
 fn change_field<T>(hash: &mut Option<H256>, field: &mut T, value: T)
 where
@@ -346,6 +501,35 @@ impl EthereumHeader {
 		&self.difficulty
 	}
 
+	/// Get the base fee per gas field of the header, since London.
+	pub fn base_fee_per_gas(&self) -> Option<&U256> {
+		self.base_fee_per_gas.as_ref()
+	}
+
+	/// Get the withdrawals root field of the header, since Shanghai.
+	pub fn withdrawals_root(&self) -> Option<&H256> {
+		self.withdrawals_root.as_ref()
+	}
+
+	/// Get the parent beacon block root field of the header, since Cancun.
+	pub fn parent_beacon_block_root(&self) -> Option<&H256> {
+		self.parent_beacon_block_root.as_ref()
+	}
+
+	/// Number of the fork fields set, they are encoded in order after the seal.
+	pub(crate) fn fork_fields_len(&self) -> usize {
+		[
+			self.base_fee_per_gas.is_some(),
+			self.withdrawals_root.is_some(),
+			self.blob_gas_used.is_some(),
+			self.excess_blob_gas.is_some(),
+			self.parent_beacon_block_root.is_some(),
+		]
+		.iter()
+		.filter(|is_some| **is_some)
+		.count()
+	}
+
 	/// Get the seal field of the header.
 	pub fn seal(&self) -> &[Bytes] {
 		&self.seal
@@ -397,10 +581,12 @@ impl EthereumHeader {
 
 	/// Place this header into an RLP stream `s`, optionally `with_seal`.
 	fn stream_rlp(&self, s: &mut RlpStream, with_seal: Seal) {
+		let fork_fields_len = self.fork_fields_len();
+
 		if let Seal::With = with_seal {
-			s.begin_list(13 + self.seal.len());
+			s.begin_list(BASE_FIELDS_LEN + self.seal.len() + fork_fields_len);
 		} else {
-			s.begin_list(13);
+			s.begin_list(BASE_FIELDS_LEN + fork_fields_len);
 		}
 
 		s.append(&self.parent_hash);
@@ -422,6 +608,22 @@ impl EthereumHeader {
 				s.append_raw(b, 1);
 			}
 		}
+
+		if let Some(base_fee_per_gas) = &self.base_fee_per_gas {
+			s.append(base_fee_per_gas);
+		}
+		if let Some(withdrawals_root) = &self.withdrawals_root {
+			s.append(withdrawals_root);
+		}
+		if let Some(blob_gas_used) = &self.blob_gas_used {
+			s.append(blob_gas_used);
+		}
+		if let Some(excess_blob_gas) = &self.excess_blob_gas {
+			s.append(excess_blob_gas);
+		}
+		if let Some(parent_beacon_block_root) = &self.parent_beacon_block_root {
+			s.append(parent_beacon_block_root);
+		}
 	}
 }
 
@@ -910,5 +1112,130 @@ mod tests {
 			<EthereumHeader as Decode>::decode(&mut &encoded_header[..]).unwrap(),
 			header
 		);
+		// The headers before London keep their encoding
+		assert_eq!(header.encode(), encoded_header);
+	}
+
+	#[inline]
+	fn mainnet_cancun_header() -> EthereumHeader {
+		EthereumHeader::from_str_unchecked(
+			r#"
+			{
+				"baseFeePerGas": "0x886b221ad",
+				"blobGasUsed": "0x0",
+				"difficulty": "0x0",
+				"excessBlobGas": "0x0",
+				"extraData": "0x6265617665726275696c642e6f7267",
+				"gasLimit": "0x1c9c380",
+				"gasUsed": "0xb0033c",
+				"hash": "0x85cdcbe36217fd57bf2c33731d8460657a7ce512401f49c9f6392c82a7ccf7ac",
+				"logsBloom": "0xc36919406572730518285284f2293101104140c0d42c4a786c892467868a8806f40159d29988002870403902413a1d04321320308da2e845438429e0012a00b419d8ccc8584a1c28f82a415d04eab8a5ae75c00d07761acf233414c08b6d9b571c06156086c70ea5186e9b989b0c2d55c0213c936805cd2ab331589c90194d070c00867549b1e1be14cb24500b0386cd901197c1ef5a00da453234fa48f3003dcaa894e3111c22b80e17f7d4388385a10720cda1140c0400f9e084ca34fc4870fb16b472340a2a6a63115a82522f506c06c2675080508834828c63defd06bc2331b4aa708906a06a560457b114248041e40179ebc05c6846c1e922125982f427",
+				"miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+				"mixHash": "0x4c068e902990f21f92a2456fc75c59bec8be03b7f13682b6ebd27da56269beb5",
+				"nonce": "0x0000000000000000",
+				"number": "0x128c6df",
+				"parentBeaconBlockRoot": "0x2843cb9f7d001bd58816a915e685ed96a555c9aeec1217736bd83a96ebd409cc",
+				"parentHash": "0x90926e0298d418181bd20c23b332451e35fd7d696b5dcdc5a3a0a6b715f4c717",
+				"receiptsRoot": "0xd43aa19ecb03571d1b86d89d9bb980139d32f2f2ba59646cd5c1de9e80c68c90",
+				"sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+				"size": "0x1a7c",
+				"stateRoot": "0x707875120a7103621fb4131df59904cda39de948dfda9084a1e3da44594d5404",
+				"timestamp": "0x65f5f4c3",
+				"totalDifficulty": "0xc70d815d562d3cfa955",
+				"transactions": [omitted],
+				"transactionsRoot": "0x889a1c26dc42ba829dab552b779620feac231cde8a6c79af022bdc605c23a780",
+				"uncles": [],
+				"withdrawals": [omitted],
+				"withdrawalsRoot": "0x360c33f20eeed5efbc7d08be46e58f8440af5db503e40908ef3d1eb314856ef7"
+			}
+			"#,
+		)
+	}
+
+	#[test]
+	fn cancun_header_hash_should_work() {
+		let header = mainnet_cancun_header();
+
+		assert_eq!(
+			header.base_fee_per_gas(),
+			Some(&U256::from(0x886b221ad_u64))
+		);
+		assert_eq!(header.blob_gas_used, Some(0));
+		assert_eq!(header.excess_blob_gas, Some(0));
+		assert_eq!(header.re_compute_hash(), header.hash());
+		assert_eq!(
+			header.hash(),
+			array_bytes::hex2array_unchecked!(
+				"0x85cdcbe36217fd57bf2c33731d8460657a7ce512401f49c9f6392c82a7ccf7ac",
+				32
+			)
+			.into()
+		);
+	}
+
+	#[test]
+	fn cancun_header_rlp_should_work() {
+		let header = mainnet_cancun_header();
+		let encoded_header = array_bytes::hex2bytes_unchecked("f90255a090926e0298d418181bd20c23b332451e35fd7d696b5dcdc5a3a0a6b715f4c717a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d493479495222290dd7278aa3ddd389cc1e1d165cc4bafe5a0707875120a7103621fb4131df59904cda39de948dfda9084a1e3da44594d5404a0889a1c26dc42ba829dab552b779620feac231cde8a6c79af022bdc605c23a780a0d43aa19ecb03571d1b86d89d9bb980139d32f2f2ba59646cd5c1de9e80c68c90b90100c36919406572730518285284f2293101104140c0d42c4a786c892467868a8806f40159d29988002870403902413a1d04321320308da2e845438429e0012a00b419d8ccc8584a1c28f82a415d04eab8a5ae75c00d07761acf233414c08b6d9b571c06156086c70ea5186e9b989b0c2d55c0213c936805cd2ab331589c90194d070c00867549b1e1be14cb24500b0386cd901197c1ef5a00da453234fa48f3003dcaa894e3111c22b80e17f7d4388385a10720cda1140c0400f9e084ca34fc4870fb16b472340a2a6a63115a82522f506c06c2675080508834828c63defd06bc2331b4aa708906a06a560457b114248041e40179ebc05c6846c1e922125982f42780840128c6df8401c9c38083b0033c8465f5f4c38f6265617665726275696c642e6f7267a04c068e902990f21f92a2456fc75c59bec8be03b7f13682b6ebd27da56269beb5880000000000000000850886b221ada0360c33f20eeed5efbc7d08be46e58f8440af5db503e40908ef3d1eb314856ef78080a02843cb9f7d001bd58816a915e685ed96a555c9aeec1217736bd83a96ebd409cc");
+
+		assert_eq!(rlp::encode(&header), encoded_header);
+		assert_eq!(
+			rlp::decode::<EthereumHeader>(&encoded_header).unwrap(),
+			header
+		);
+	}
+
+	// TODO: the mainnet London block 12965000 and Shanghai block 17034870 with their hashes, the
+	// London and Shanghai layouts are only checked by stripping the fields of the Cancun block here
+	#[test]
+	fn fork_fields_should_follow_the_seal() {
+		let mut header = mainnet_cancun_header();
+
+		// Shanghai
+		header.blob_gas_used = None;
+		header.excess_blob_gas = None;
+		header.parent_beacon_block_root = None;
+		assert_eq!(Rlp::new(&rlp::encode(&header)).item_count(), Ok(17));
+
+		// London
+		header.withdrawals_root = None;
+		assert_eq!(Rlp::new(&rlp::encode(&header)).item_count(), Ok(16));
+
+		let decoded_header = rlp::decode::<EthereumHeader>(&rlp::encode(&header)).unwrap();
+
+		assert_eq!(decoded_header.seal(), header.seal());
+		assert_eq!(decoded_header.base_fee_per_gas(), header.base_fee_per_gas());
+		assert_eq!(decoded_header.withdrawals_root(), None);
+	}
+
+	#[test]
+	fn scale_codec_of_cancun_header_should_work() {
+		let header = mainnet_cancun_header();
+		let encoded_header = header.encode();
+
+		assert_eq!(
+			<EthereumHeader as Decode>::decode(&mut &encoded_header[..]).unwrap(),
+			header
+		);
+		// The flags, followed by the hash and the fork fields
+		assert_eq!(
+			encoded_header[encoded_header.len() - 1 - 32 - (33 + 33 + 9 + 9 + 33)],
+			0b11
+		);
+	}
+
+	#[test]
+	fn proof_of_work_should_end_at_the_merge() {
+		let header = mainnet_cancun_header();
+		let ethash_params = EthashPartial::production();
+
+		assert_eq!(
+			ethash_params.verify_block_basic(&header),
+			Err(EthereumError::ProofOfWorkEnded)
+		);
+		assert_eq!(
+			ethash_params.calculate_difficulty(&header, &sequential_header().0),
+			U256::zero()
+		);
 	}
 }
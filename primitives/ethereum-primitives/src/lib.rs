@@ -26,6 +26,7 @@ pub mod encoded;
 pub mod error;
 pub mod ethashproof;
 pub mod header;
+pub mod poa;
 pub mod pow;
 pub mod receipt;
 
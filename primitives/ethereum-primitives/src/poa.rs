@@ -0,0 +1,431 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Proof of authority sealing of the EVM sidechains, Clique (EIP-225) and the Parlia engine of the
+//! BNB Smart Chain.
+//!
+//! The headers are signed by a validator set, which is checkpointed in the extra data every
+//! epoch. A validator signs `turn_length` consecutive blocks in its turn, with the in-turn
+//! difficulty, any other validator may sign instead with the out-of-turn difficulty.
+
+// --- crates ---
+use codec::{Decode, Encode};
+// --- github ---
+use keccak_hash::keccak;
+use rlp::RlpStream;
+// --- substrate ---
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+// --- hyperspace ---
+use crate::{
+	error::{EthereumError, Mismatch},
+	header::EthereumHeader,
+	*,
+};
+
+/// Fixed number of extra data prefix bytes reserved for the signer vanity.
+pub const EXTRA_VANITY: usize = 32;
+/// Fixed number of extra data suffix bytes reserved for the signer seal.
+pub const EXTRA_SEAL: usize = 65;
+/// Length of a validator's BLS public key, carried by the Parlia checkpoints since Luban.
+pub const BLS_PUBLIC_KEY_LEN: usize = 48;
+/// Block difficulty of the in-turn signatures.
+pub const DIFF_IN_TURN: u64 = 2;
+/// Block difficulty of the out-of-turn signatures.
+pub const DIFF_NO_TURN: u64 = 1;
+
+/// The mix hash and the nonce, they are signed but unused.
+const POA_SEAL_LEN: usize = 2;
+/// Length of a validator's address.
+const ADDRESS_LEN: usize = 20;
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum PoAEngine {
+	/// EIP-225, the checkpoints carry the signers' addresses.
+	Clique,
+	/// The BNB Smart Chain engine, the seal hash commits to the chain ID.
+	Parlia {
+		chain_id: u64,
+		/// Since Luban, the checkpoints carry the validators' BLS public keys.
+		luban_block: EthereumBlockNumber,
+		/// Since Bohr, the checkpoints carry the turn length. It's a fork by timestamp.
+		bohr_time: u64,
+	},
+}
+
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PoAChainSpec {
+	pub engine: PoAEngine,
+	/// The validator set is checkpointed in the headers whose number is a multiple of it.
+	pub epoch_length: u64,
+}
+impl PoAChainSpec {
+	pub fn is_checkpoint(&self, number: EthereumBlockNumber) -> bool {
+		number % self.epoch_length == 0
+	}
+
+	/// Check the layout of the extra data, the validator set of the checkpoints is returned.
+	pub fn verify_extra_data(
+		&self,
+		header: &EthereumHeader,
+	) -> Result<Option<PoAValidatorSet>, EthereumError> {
+		let extra_data = &header.extra_data;
+
+		if extra_data.len() < EXTRA_VANITY + EXTRA_SEAL {
+			return Err(EthereumError::InvalidExtraData);
+		}
+
+		let signers_data = &extra_data[EXTRA_VANITY..extra_data.len() - EXTRA_SEAL];
+
+		if !self.is_checkpoint(header.number) {
+			// Parlia puts the fast finality votes there
+			return match (&self.engine, signers_data.is_empty()) {
+				(PoAEngine::Clique, false) => Err(EthereumError::InvalidExtraData),
+				_ => Ok(None),
+			};
+		}
+
+		let validator_set = match self.engine {
+			PoAEngine::Parlia {
+				luban_block,
+				bohr_time,
+				..
+			} if header.number >= luban_block => {
+				let validator_len = ADDRESS_LEN + BLS_PUBLIC_KEY_LEN;
+				let count = *signers_data
+					.first()
+					.ok_or(EthereumError::InvalidExtraData)? as usize;
+				let end = 1 + count * validator_len;
+
+				if signers_data.len() < end {
+					return Err(EthereumError::InvalidExtraData);
+				}
+
+				let validators = signers_data[1..end]
+					.chunks(validator_len)
+					.map(|validator| EthereumAddress::from_slice(&validator[..ADDRESS_LEN]))
+					.collect();
+				let turn_length = if header.timestamp >= bohr_time {
+					*signers_data
+						.get(end)
+						.ok_or(EthereumError::InvalidExtraData)?
+				} else {
+					1
+				};
+
+				PoAValidatorSet::new(validators, turn_length)
+			}
+			_ => {
+				if signers_data.len() % ADDRESS_LEN != 0 {
+					return Err(EthereumError::InvalidExtraData);
+				}
+
+				let validators = signers_data
+					.chunks(ADDRESS_LEN)
+					.map(EthereumAddress::from_slice)
+					.collect();
+
+				PoAValidatorSet::new(validators, 1)
+			}
+		};
+
+		if validator_set.validators.is_empty() || validator_set.turn_length == 0 {
+			return Err(EthereumError::InvalidExtraData);
+		}
+
+		Ok(Some(validator_set))
+	}
+
+	/// The hash the validator signs, the RLP of the header without the signature in the extra
+	/// data.
+	pub fn seal_hash(&self, header: &EthereumHeader) -> Result<H256, EthereumError> {
+		if header.extra_data.len() < EXTRA_SEAL {
+			return Err(EthereumError::InvalidExtraData);
+		}
+		if header.seal.len() != POA_SEAL_LEN {
+			return Err(EthereumError::InvalidSealArity(Mismatch {
+				expected: POA_SEAL_LEN,
+				found: header.seal.len(),
+			}));
+		}
+
+		let mut s = RlpStream::new();
+
+		match self.engine {
+			PoAEngine::Clique => {
+				s.begin_list(15 + header.base_fee_per_gas.iter().count());
+				stream_sig_header(&mut s, header);
+
+				if let Some(base_fee_per_gas) = &header.base_fee_per_gas {
+					s.append(base_fee_per_gas);
+				}
+			}
+			PoAEngine::Parlia { chain_id, .. } => {
+				// Since Cancun, which sets the parent beacon block root to zero
+				let with_fork_fields = header.parent_beacon_block_root == Some(H256::zero());
+
+				if with_fork_fields {
+					s.begin_list(16 + header.fork_fields_len());
+				} else {
+					s.begin_list(16);
+				}
+
+				s.append(&chain_id);
+				stream_sig_header(&mut s, header);
+
+				if with_fork_fields {
+					if let Some(base_fee_per_gas) = &header.base_fee_per_gas {
+						s.append(base_fee_per_gas);
+					}
+					if let Some(withdrawals_root) = &header.withdrawals_root {
+						s.append(withdrawals_root);
+					}
+					if let Some(blob_gas_used) = &header.blob_gas_used {
+						s.append(blob_gas_used);
+					}
+					if let Some(excess_blob_gas) = &header.excess_blob_gas {
+						s.append(excess_blob_gas);
+					}
+					if let Some(parent_beacon_block_root) = &header.parent_beacon_block_root {
+						s.append(parent_beacon_block_root);
+					}
+				}
+			}
+		}
+
+		Ok(keccak(s.out()))
+	}
+
+	/// Recover the validator's address from the signature at the end of the extra data.
+	pub fn recover_signer(
+		&self,
+		header: &EthereumHeader,
+	) -> Result<EthereumAddress, EthereumError> {
+		let seal_hash = self.seal_hash(header)?;
+		let mut signature = [0; EXTRA_SEAL];
+
+		signature.copy_from_slice(&header.extra_data[header.extra_data.len() - EXTRA_SEAL..]);
+
+		let public_key =
+			sp_io::crypto::secp256k1_ecdsa_recover(&signature, seal_hash.as_fixed_bytes())
+				.map_err(|_| EthereumError::SealInvalid)?;
+
+		Ok(EthereumAddress::from_slice(
+			&keccak(&public_key[..]).as_bytes()[12..],
+		))
+	}
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PoAValidatorSet {
+	/// Sorted ascending, in the order they take turns.
+	pub validators: Vec<EthereumAddress>,
+	/// Number of consecutive blocks of a turn, it's always 1 before Bohr.
+	pub turn_length: u8,
+}
+impl PoAValidatorSet {
+	pub fn new(mut validators: Vec<EthereumAddress>, turn_length: u8) -> Self {
+		validators.sort();
+
+		Self {
+			validators,
+			turn_length,
+		}
+	}
+
+	pub fn contains(&self, validator: &EthereumAddress) -> bool {
+		self.validators.binary_search(validator).is_ok()
+	}
+
+	/// The validator whose turn it is to sign the block.
+	pub fn in_turn(&self, number: EthereumBlockNumber) -> Option<&EthereumAddress> {
+		if self.validators.is_empty() || self.turn_length == 0 {
+			return None;
+		}
+
+		let turn = number / self.turn_length as u64;
+
+		self.validators
+			.get((turn % self.validators.len() as u64) as usize)
+	}
+
+	/// The expected difficulty of a block signed by the validator.
+	pub fn difficulty(&self, number: EthereumBlockNumber, validator: &EthereumAddress) -> U256 {
+		if self.in_turn(number) == Some(validator) {
+			DIFF_IN_TURN.into()
+		} else {
+			DIFF_NO_TURN.into()
+		}
+	}
+
+	/// Number of the previous blocks in which a validator must have signed less than
+	/// `turn_length` blocks to sign again, `minerHistoryCheckLen` of Parlia.
+	///
+	/// In Clique, a signer can sign one block out of `validators / 2 + 1` consecutive blocks.
+	pub fn recent_signers_window(&self) -> usize {
+		(self.validators.len() / 2 + 1) * self.turn_length as usize - 1
+	}
+
+	/// Number of distinct validators signing a block or its descendants to finalize it.
+	///
+	/// A competing branch would need one of them to sign twice in a window, which the recent
+	/// signers rule forbids.
+	pub fn finality_threshold(&self) -> usize {
+		self.validators.len() / 2 + 1
+	}
+}
+
+/// The header fields signed by both engines, with the signature stripped from the extra data.
+fn stream_sig_header(s: &mut RlpStream, header: &EthereumHeader) {
+	s.append(&header.parent_hash);
+	s.append(&header.uncles_hash);
+	s.append(&header.author);
+	s.append(&header.state_root);
+	s.append(&header.transactions_root);
+	s.append(&header.receipts_root);
+	s.append(&header.log_bloom);
+	s.append(&header.difficulty);
+	s.append(&header.number);
+	s.append(&header.gas_limit);
+	s.append(&header.gas_used);
+	s.append(&header.timestamp);
+	s.append(&&header.extra_data[..header.extra_data.len() - EXTRA_SEAL]);
+
+	for b in &header.seal {
+		s.append_raw(b, 1);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// --- hyperspace ---
+	use super::*;
+
+	fn address(i: u8) -> EthereumAddress {
+		EthereumAddress::repeat_byte(i)
+	}
+
+	fn checkpoint(number: EthereumBlockNumber, signers_data: &[u8]) -> EthereumHeader {
+		EthereumHeader {
+			number,
+			extra_data: [&[0; EXTRA_VANITY][..], signers_data, &[0; EXTRA_SEAL][..]].concat(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn verify_extra_data_should_work() {
+		let clique = PoAChainSpec {
+			engine: PoAEngine::Clique,
+			epoch_length: 8,
+		};
+		let signers_data = [address(3).0, address(1).0, address(2).0].concat();
+
+		assert_eq!(
+			clique.verify_extra_data(&checkpoint(8, &signers_data)),
+			Ok(Some(PoAValidatorSet {
+				validators: vec![address(1), address(2), address(3)],
+				turn_length: 1,
+			}))
+		);
+		assert_eq!(
+			clique.verify_extra_data(&checkpoint(8, &signers_data[1..])),
+			Err(EthereumError::InvalidExtraData)
+		);
+		assert_eq!(
+			clique.verify_extra_data(&checkpoint(9, &signers_data)),
+			Err(EthereumError::InvalidExtraData)
+		);
+		assert_eq!(clique.verify_extra_data(&checkpoint(9, &[])), Ok(None));
+
+		let parlia = PoAChainSpec {
+			engine: PoAEngine::Parlia {
+				chain_id: 56,
+				luban_block: 16,
+				bohr_time: 1,
+			},
+			epoch_length: 8,
+		};
+		// Since Luban: the count, the addresses with their BLS public keys and the turn length
+		let signers_data = [
+			&[2][..],
+			&address(2).0,
+			&[0; BLS_PUBLIC_KEY_LEN],
+			&address(1).0,
+			&[0; BLS_PUBLIC_KEY_LEN],
+			&[4],
+		]
+		.concat();
+		let mut header = checkpoint(16, &signers_data);
+
+		assert_eq!(
+			parlia.verify_extra_data(&header),
+			Ok(Some(PoAValidatorSet {
+				validators: vec![address(1), address(2)],
+				turn_length: 1,
+			}))
+		);
+
+		header.timestamp = 1;
+
+		assert_eq!(
+			parlia.verify_extra_data(&header),
+			Ok(Some(PoAValidatorSet {
+				validators: vec![address(1), address(2)],
+				turn_length: 4,
+			}))
+		);
+
+		let header = checkpoint(16, &signers_data[..signers_data.len() - 1]);
+
+		assert_eq!(
+			parlia.verify_extra_data(&EthereumHeader {
+				timestamp: 1,
+				..header
+			}),
+			Err(EthereumError::InvalidExtraData)
+		);
+		// The fast finality votes
+		assert_eq!(parlia.verify_extra_data(&checkpoint(17, &[1; 8])), Ok(None));
+	}
+
+	#[test]
+	fn validators_should_take_turns() {
+		let validator_set = PoAValidatorSet::new(vec![address(3), address(1), address(2)], 2);
+
+		assert_eq!(
+			(0..8)
+				.map(|number| validator_set.in_turn(number).unwrap().clone())
+				.collect::<Vec<_>>(),
+			vec![1, 1, 2, 2, 3, 3, 1, 1]
+				.into_iter()
+				.map(address)
+				.collect::<Vec<_>>()
+		);
+		assert_eq!(
+			validator_set.difficulty(2, &address(2)),
+			DIFF_IN_TURN.into()
+		);
+		assert_eq!(
+			validator_set.difficulty(2, &address(1)),
+			DIFF_NO_TURN.into()
+		);
+		assert_eq!(validator_set.recent_signers_window(), 3);
+		assert_eq!(validator_set.finality_threshold(), 2);
+	}
+}
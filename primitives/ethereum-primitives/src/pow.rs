@@ -54,6 +54,8 @@ pub struct EthashPartial {
 	pub expip2_transition: u64,
 	pub expip2_duration_limit: u64,
 	pub progpow_transition: u64,
+	/// The first block of the proof of stake, there is no proof of work to verify since then.
+	pub pos_transition: EthereumBlockNumber,
 }
 
 impl EthashPartial {
@@ -83,6 +85,7 @@ impl EthashPartial {
 			expip2_transition: 0xc3500,
 			expip2_duration_limit: 0x1e,
 			progpow_transition: u64::max_value(),
+			pos_transition: u64::max_value(),
 		}
 	}
 
@@ -105,11 +108,19 @@ impl EthashPartial {
 				m.insert(4370000, 3000000);
 				m.insert(7280000, 2000000);
 				m.insert(0x8c6180, 0x3d0900);
+				// London, EIP-3554
+				m.insert(12965000, 700000);
+				// Arrow Glacier, EIP-4345
+				m.insert(13773000, 1000000);
+				// Gray Glacier, EIP-5133
+				m.insert(15050000, 700000);
 				m
 			},
 			expip2_transition: u64::max_value(),
 			expip2_duration_limit: 30,
 			progpow_transition: u64::max_value(),
+			// The Merge
+			pos_transition: 15537394,
 		}
 	}
 
@@ -137,6 +148,8 @@ impl EthashPartial {
 			expip2_transition: u64::max_value(),
 			expip2_duration_limit: 30,
 			progpow_transition: u64::max_value(),
+			// Ropsten is deprecated, its merge is not tracked
+			pos_transition: u64::max_value(),
 		}
 	}
 }
@@ -148,6 +161,8 @@ impl EthashPartial {
 		ethash_proof: &[EthashProof],
 		merkle_root: &H128,
 	) -> Result<(), EthereumError> {
+		self.verify_proof_of_work_era(header)?;
+
 		let seal = EthashSeal::parse_seal(header.seal())?;
 
 		let (mix_hash, _result) = self.hashimoto_merkle(
@@ -228,6 +243,8 @@ impl EthashPartial {
 	}
 
 	pub fn verify_block_basic(&self, header: &EthereumHeader) -> Result<(), EthereumError> {
+		self.verify_proof_of_work_era(header)?;
+
 		// check the seal fields.
 		let seal = EthashSeal::parse_seal(header.seal())?;
 
@@ -259,12 +276,25 @@ impl EthashPartial {
 		Ok(())
 	}
 
+	/// The headers of the proof of stake can't be verified with Ethash.
+	pub fn verify_proof_of_work_era(&self, header: &EthereumHeader) -> Result<(), EthereumError> {
+		if header.number() >= self.pos_transition {
+			return Err(EthereumError::ProofOfWorkEnded);
+		}
+
+		Ok(())
+	}
+
 	pub fn calculate_difficulty(&self, header: &EthereumHeader, parent: &EthereumHeader) -> U256 {
 		const EXP_DIFF_PERIOD: u64 = 100_000;
 
 		if header.number() == 0 {
 			panic!("Can't calculate genesis block difficulty");
 		}
+		// The difficulty is always zero since The Merge, EIP-3675
+		if header.number() >= self.pos_transition {
+			return U256::zero();
+		}
 
 		let parent_has_uncles = parent.uncles_hash() != &KECCAK_EMPTY_LIST_RLP;
 
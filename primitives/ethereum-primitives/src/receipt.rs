@@ -16,6 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
 
+// --- core ---
+use core::convert::TryFrom;
 // --- crates ---
 use codec::{Decode, Encode};
 // --- github ---
@@ -40,6 +42,40 @@ pub enum TransactionOutcome {
 	StatusCode(u8),
 }
 
+/// The transaction types, the receipts of the typed transactions are prefixed with the type.
+/// EIP-2718.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum TransactionType {
+	/// Before EIP-2718, not prefixed.
+	Legacy = 0x00,
+	/// EIP-2930.
+	AccessList = 0x01,
+	/// EIP-1559.
+	DynamicFee = 0x02,
+	/// EIP-4844.
+	Blob = 0x03,
+	/// EIP-7702.
+	SetCode = 0x04,
+}
+impl Default for TransactionType {
+	fn default() -> Self {
+		Self::Legacy
+	}
+}
+impl TryFrom<u8> for TransactionType {
+	type Error = DecoderError;
+
+	fn try_from(tx_type: u8) -> Result<Self, Self::Error> {
+		match tx_type {
+			0x01 => Ok(Self::AccessList),
+			0x02 => Ok(Self::DynamicFee),
+			0x03 => Ok(Self::Blob),
+			0x04 => Ok(Self::SetCode),
+			_ => Err(DecoderError::Custom("Unknown transaction type")),
+		}
+	}
+}
+
 #[derive(Clone, PartialEq, Eq, Encode, Decode, RlpEncodable, RlpDecodable, RuntimeDebug)]
 pub struct LogEntry {
 	/// The address of the contract executing at the point of the `LOG` operation.
@@ -73,6 +109,8 @@ pub struct EthereumReceipt {
 	pub logs: Vec<LogEntry>,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type of the transaction.
+	pub tx_type: TransactionType,
 }
 
 #[cfg_attr(any(feature = "deserialize", test), derive(serde::Deserialize))]
@@ -98,6 +136,47 @@ impl EthereumReceipt {
 			}),
 			logs,
 			outcome,
+			tx_type: TransactionType::Legacy,
+		}
+	}
+
+	/// Encode the receipt like the value in the receipts trie, the typed receipts are the type
+	/// followed by the RLP payload.
+	pub fn typed_encode(&self) -> Bytes {
+		let payload = {
+			let mut s = RlpStream::new();
+
+			self.rlp_append_payload(&mut s);
+
+			s.out()
+		};
+
+		match self.tx_type {
+			TransactionType::Legacy => payload,
+			tx_type => {
+				let mut bytes = Vec::with_capacity(1 + payload.len());
+
+				bytes.push(tx_type as u8);
+				bytes.extend(payload);
+
+				bytes
+			}
+		}
+	}
+
+	/// Decode the receipt from the value in the receipts trie, the legacy receipts are RLP lists.
+	pub fn typed_decode(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let first = *bytes.first().ok_or(DecoderError::RlpIsTooShort)?;
+
+		if first >= 0xc0 {
+			Self::decode_payload(&Rlp::new(bytes))
+		} else {
+			let tx_type = TransactionType::try_from(first)?;
+			let mut receipt = Self::decode_payload(&Rlp::new(&bytes[1..]))?;
+
+			receipt.tx_type = tx_type;
+
+			Ok(receipt)
 		}
 	}
 
@@ -112,14 +191,12 @@ impl EthereumReceipt {
 		let value = MerklePatriciaTrie::verify_proof(receipt_root.0.to_vec(), &key, proof)
 			.map_err(|_| EthereumError::InvalidReceiptProof)?
 			.ok_or(EthereumError::InvalidReceiptProof)?;
-		let receipt = rlp::decode(&value).map_err(|_| EthereumError::InvalidReceiptProof)?;
+		let receipt = Self::typed_decode(&value).map_err(|_| EthereumError::InvalidReceiptProof)?;
 
 		Ok(receipt)
 	}
-}
 
-impl Encodable for EthereumReceipt {
-	fn rlp_append(&self, s: &mut RlpStream) {
+	fn rlp_append_payload(&self, s: &mut RlpStream) {
 		match self.outcome {
 			TransactionOutcome::Unknown => {
 				s.begin_list(3);
@@ -137,16 +214,19 @@ impl Encodable for EthereumReceipt {
 		s.append(&self.log_bloom);
 		s.append_list(&self.logs);
 	}
-}
 
-impl Decodable for EthereumReceipt {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+	fn decode_payload(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if !rlp.is_list() {
+			return Err(DecoderError::RlpExpectedToBeList);
+		}
+
 		if rlp.item_count()? == 3 {
 			Ok(EthereumReceipt {
 				outcome: TransactionOutcome::Unknown,
 				gas_used: rlp.val_at(0)?,
 				log_bloom: rlp.val_at(1)?,
 				logs: rlp.list_at(2)?,
+				tx_type: TransactionType::Legacy,
 			})
 		} else {
 			Ok(EthereumReceipt {
@@ -161,11 +241,34 @@ impl Decodable for EthereumReceipt {
 						TransactionOutcome::StateRoot(first.as_val()?)
 					}
 				},
+				tx_type: TransactionType::Legacy,
 			})
 		}
 	}
 }
 
+impl Encodable for EthereumReceipt {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		// The typed receipts are RLP strings out of the receipts trie, EIP-2718
+		match self.tx_type {
+			TransactionType::Legacy => self.rlp_append_payload(s),
+			_ => {
+				s.append(&self.typed_encode());
+			}
+		}
+	}
+}
+
+impl Decodable for EthereumReceipt {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.is_list() {
+			Self::decode_payload(rlp)
+		} else {
+			Self::typed_decode(rlp.data()?)
+		}
+	}
+}
+
 pub type EthereumTransactionIndex = (H256, u64);
 
 #[cfg(test)]
@@ -264,4 +367,100 @@ mod tests {
 
 		assert_eq!(receipts_root, expected_root);
 	}
+
+	/// mainnet tx hash: 0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616
+	/// block number: 19526841, a dynamic fee (EIP-1559) transaction
+	#[inline]
+	fn mainnet_dynamic_fee_receipt() -> EthereumReceipt {
+		let log_entries = vec![LogEntry {
+			address: EthereumAddress::from_str("dac17f958d2ee523a2206206994597c13d831ec7").unwrap(),
+			topics: vec![
+				array_bytes::hex2array_unchecked!(
+					"0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+					32
+				)
+				.into(),
+				array_bytes::hex2array_unchecked!(
+					"0x0000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2",
+					32
+				)
+				.into(),
+				array_bytes::hex2array_unchecked!(
+					"0x00000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76",
+					32
+				)
+				.into(),
+			],
+			data: array_bytes::hex2bytes_unchecked(
+				"0x00000000000000000000000000000000000000000052b7d2dcc80cd2e4000000",
+			),
+		}];
+
+		EthereumReceipt {
+			tx_type: TransactionType::DynamicFee,
+			..EthereumReceipt::new(
+				TransactionOutcome::StatusCode(1),
+				0xa42aec.into(),
+				log_entries,
+			)
+		}
+	}
+
+	#[test]
+	fn typed_receipt_codec_should_work() {
+		let receipt = mainnet_dynamic_fee_receipt();
+		let typed_encoded_receipt = receipt.typed_encode();
+
+		// The log bloom of the mainnet receipt
+		assert_eq!(receipt.log_bloom, Bloom::from_str(
+			"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000200000000000000000040000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000800000000000000000000000000000000004000000000000000000800000000100000020000000000000000000080000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000010000000000000000000000000000"
+		).unwrap());
+		assert_eq!(typed_encoded_receipt[0], 0x02);
+		assert_eq!(
+			EthereumReceipt::typed_decode(&typed_encoded_receipt).unwrap(),
+			receipt
+		);
+		assert_eq!(
+			rlp::decode::<EthereumReceipt>(&rlp::encode(&receipt)).unwrap(),
+			receipt
+		);
+		assert_eq!(
+			EthereumReceipt::typed_decode(&[0x7f]),
+			Err(DecoderError::Custom("Unknown transaction type"))
+		);
+	}
+
+	// TODO: a proof against the real `receiptsRoot` of a mainnet block mixing legacy and typed
+	// receipts, the trie here is built from a legacy receipt and the mainnet typed one
+	#[test]
+	fn typed_receipt_proof_should_work() {
+		let legacy_receipt = construct_receipts(None, 21000.into(), Some(1), vec![]);
+		let typed_receipt = mainnet_dynamic_fee_receipt();
+		let receipts = vec![legacy_receipt, typed_receipt];
+		let mut trie = merkle_patricia_trie::build_trie(
+			receipts
+				.iter()
+				.enumerate()
+				.map(|(i, receipt)| (rlp::encode(&i), receipt.typed_encode())),
+		)
+		.unwrap();
+		let receipts_root: H256 = H256(triehash::ordered_trie_root::<KeccakHasher, _>(
+			receipts.iter().map(|x| x.typed_encode()),
+		));
+
+		assert_eq!(trie.root().unwrap(), receipts_root.0.to_vec());
+
+		for (i, receipt) in receipts.iter().enumerate() {
+			let proof_record = EthereumReceiptProof {
+				index: i as _,
+				proof: trie.get_proof(&rlp::encode(&i)).unwrap().to_rlp(),
+				header_hash: Default::default(),
+			};
+
+			assert_eq!(
+				EthereumReceipt::verify_proof_and_generate(&receipts_root, &proof_record).unwrap(),
+				*receipt
+			);
+		}
+	}
 }
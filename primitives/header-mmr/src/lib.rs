@@ -0,0 +1,462 @@
+// This file is part of Hyperspace.
+//
+// Copyright (C) 2018-2021 Hyperspace Network
+// SPDX-License-Identifier: GPL-3.0
+//
+// Hyperspace is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Hyperspace is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Hyperspace. If not, see <https://www.gnu.org/licenses/>.
+
+//! Header MMR primitives.
+//!
+//! The merge of the MMR nodes, the proof verification, the consistency proofs, the MMR leaves
+//! and the MMR root log of the header digest, shared by the header-mmr pallet and the relayers so both sides hash the
+//! same bytes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// --- crates ---
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+// --- github ---
+pub use merkle_mountain_range::{leaf_index_to_mmr_size, leaf_index_to_pos};
+use merkle_mountain_range::{
+	Error as MMRError, MMRStore, Merge, MerkleProof, Result as MMRResult, MMR,
+};
+// --- substrate ---
+use sp_runtime::{
+	generic::OpaqueDigestItemId,
+	traits::{Hash, Header, UniqueSaturatedInto},
+	RuntimeDebug,
+};
+use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, marker::PhantomData, prelude::*};
+
+pub const PARENT_MMR_ROOT_LOG_ID: [u8; 4] = *b"MMRR";
+/// Prefix of the MMR nodes in the off-chain indexing storage.
+pub const OFFCHAIN_PREFIX: &[u8] = b"header-mmr-node";
+/// Prefix of the MMR leaves in the off-chain indexing storage.
+pub const OFFCHAIN_LEAF_PREFIX: &[u8] = b"header-mmr-leaf";
+
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct MerkleMountainRangeRootLog<Hash> {
+	/// Specific prefix to identify the mmr root log in the digest items with Other type.
+	pub prefix: [u8; 4],
+	/// The merkle mountain range root hash.
+	pub parent_mmr_root: Hash,
+}
+
+/// The MMR leaf of a block, its MMR node is `MMRLeaf::node`.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum MMRLeaf<BlockNumber, Hash> {
+	/// The block hash, the leaves pushed before `MMRLeaf::V1`.
+	V0(Hash),
+	V1(LeafV1<BlockNumber, Hash>),
+}
+impl<BlockNumber, Hash> MMRLeaf<BlockNumber, Hash>
+where
+	BlockNumber: Copy + UniqueSaturatedInto<u64>,
+	Hash: Copy + AsRef<[u8]>,
+{
+	pub fn version(&self) -> u8 {
+		match self {
+			MMRLeaf::V0(_) => 0,
+			MMRLeaf::V1(_) => 1,
+		}
+	}
+
+	/// The hash of the block of the leaf.
+	pub fn block_hash(&self) -> Hash {
+		match self {
+			MMRLeaf::V0(hash) => *hash,
+			MMRLeaf::V1(leaf) => leaf.hash,
+		}
+	}
+
+	/// The Ethereum ABI encoding of `(uint8 version, ..fields)`, every field is a 32 bytes word.
+	pub fn abi_encode(&self) -> Vec<u8> {
+		let mut encoded = abi_uint(self.version() as _).to_vec();
+
+		match self {
+			MMRLeaf::V0(hash) => encoded.extend_from_slice(&abi_bytes32(hash)),
+			MMRLeaf::V1(leaf) => {
+				encoded.extend_from_slice(&abi_uint(leaf.block_number.unique_saturated_into()));
+
+				for hash in &[
+					leaf.hash,
+					leaf.parent_hash,
+					leaf.state_root,
+					leaf.extrinsics_root,
+				] {
+					encoded.extend_from_slice(&abi_bytes32(hash));
+				}
+
+				// A zero word for no payload
+				encoded
+					.extend_from_slice(&leaf.payload.as_ref().map(abi_bytes32).unwrap_or_default());
+			}
+		}
+
+		encoded
+	}
+
+	/// The MMR node of the leaf, the block hash itself for `MMRLeaf::V0` and the hash of the
+	/// ABI encoding otherwise.
+	pub fn node<Hashing: sp_runtime::traits::Hash<Output = Hash>>(&self) -> Hash {
+		match self {
+			MMRLeaf::V0(hash) => *hash,
+			MMRLeaf::V1(_) => Hashing::hash(&self.abi_encode()),
+		}
+	}
+}
+
+/// The block number, the hash and the roots of the header of a block, with a custom payload.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct LeafV1<BlockNumber, Hash> {
+	pub block_number: BlockNumber,
+	pub hash: Hash,
+	pub parent_hash: Hash,
+	pub state_root: Hash,
+	pub extrinsics_root: Hash,
+	/// Committed by the runtime when pushing the leaf, e.g. the root of the bridge messages.
+	pub payload: Option<Hash>,
+}
+
+/// The custom payload of the `MMRLeaf::V1` leaves, `()` for none.
+pub trait LeafPayload<Hash> {
+	fn payload() -> Option<Hash>;
+}
+impl<Hash> LeafPayload<Hash> for () {
+	fn payload() -> Option<Hash> {
+		None
+	}
+}
+
+/// Merge two MMR nodes into their parent, the hash of the encoded pair.
+pub struct MMRMerge<Hashing>(PhantomData<Hashing>);
+impl<Hashing: Hash> Merge for MMRMerge<Hashing> {
+	type Item = Hashing::Output;
+
+	fn merge(lhs: &Self::Item, rhs: &Self::Item) -> Self::Item {
+		let encodable = (lhs, rhs);
+		Hashing::hash_of(&encodable)
+	}
+}
+
+/// Verify a proof of the `(leaf index, leaf)` pairs against the root of the MMR of `mmr_size`.
+pub fn verify_proof<Hashing: Hash>(
+	root: Hashing::Output,
+	leaves: Vec<(u64, Hashing::Output)>,
+	proof: Vec<Hashing::Output>,
+	mmr_size: u64,
+) -> bool {
+	let mut leaves = leaves
+		.into_iter()
+		.map(|(leaf_index, leaf)| (leaf_index_to_pos(leaf_index), leaf))
+		.collect::<Vec<_>>();
+
+	leaves.sort_by_key(|(pos, _)| *pos);
+	leaves.dedup();
+
+	// A leaf must be in the MMR and can't be proved with two values
+	if leaves.is_empty()
+		|| leaves.iter().any(|(pos, _)| *pos >= mmr_size)
+		|| leaves.windows(2).any(|pair| pair[0].0 == pair[1].0)
+	{
+		return false;
+	}
+
+	MerkleProof::<_, MMRMerge<Hashing>>::new(mmr_size, proof)
+		.verify(root, leaves)
+		.unwrap_or(false)
+}
+
+/// Proof that the MMR of an old size is a prefix of the MMR of a new size.
+///
+/// The new peaks are rebuilt from the old peaks and the `items`, the roots of the subtrees of
+/// the new peaks without old leaves, in the order of `gen_consistency_proof`.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ConsistencyProof<Hash> {
+	/// The peaks of the old MMR, in ascending order.
+	pub old_peaks: Vec<Hash>,
+	pub items: Vec<Hash>,
+}
+
+/// Prove that the MMR of `old_mmr_size` is a prefix of the MMR of `new_mmr_size` in `store`.
+pub fn gen_consistency_proof<T, S>(
+	store: &S,
+	old_mmr_size: u64,
+	new_mmr_size: u64,
+) -> MMRResult<ConsistencyProof<T>>
+where
+	S: MMRStore<T>,
+{
+	if !is_mmr_size(old_mmr_size) || !is_mmr_size(new_mmr_size) || old_mmr_size > new_mmr_size {
+		return Err(MMRError::GenProofForInvalidLeaves);
+	}
+
+	let get_elem = |pos| store.get_elem(pos)?.ok_or(MMRError::InconsistentStore);
+	let old_peaks = get_peaks(old_mmr_size)
+		.into_iter()
+		.map(get_elem)
+		.collect::<MMRResult<_>>()?;
+	let mut items = vec![];
+
+	for peak in get_peaks(new_mmr_size) {
+		let mut stack = vec![(peak, pos_height_in_tree(peak))];
+
+		// Depth first, left subtree first
+		while let Some((pos, height)) = stack.pop() {
+			match subtree_kind(pos, height, old_mmr_size) {
+				Subtree::OldPeak => (),
+				Subtree::New => items.push(get_elem(pos)?),
+				Subtree::Mixed => {
+					stack.push((pos - 1, height - 1));
+					stack.push((pos - (1 << height), height - 1));
+				}
+			}
+		}
+	}
+
+	Ok(ConsistencyProof { old_peaks, items })
+}
+
+/// Verify that the MMR of `old_root` and `old_mmr_size` is a prefix of the MMR of `new_root`
+/// and `new_mmr_size`, see `gen_consistency_proof`.
+pub fn verify_consistency_proof<M>(
+	old_root: M::Item,
+	old_mmr_size: u64,
+	new_root: M::Item,
+	new_mmr_size: u64,
+	proof: ConsistencyProof<M::Item>,
+) -> bool
+where
+	M: Merge,
+	M::Item: Clone + PartialEq + Debug,
+{
+	if !is_mmr_size(old_mmr_size) || !is_mmr_size(new_mmr_size) || old_mmr_size > new_mmr_size {
+		return false;
+	}
+
+	let ConsistencyProof { old_peaks, items } = proof;
+	let old_peak_positions = get_peaks(old_mmr_size);
+
+	if old_peaks.len() != old_peak_positions.len() {
+		return false;
+	}
+
+	let old_peaks = old_peak_positions
+		.into_iter()
+		.zip(old_peaks)
+		.collect::<BTreeMap<_, _>>();
+
+	if root_of_peaks::<M>(old_mmr_size, old_peaks.clone()).as_ref() != Some(&old_root) {
+		return false;
+	}
+
+	let mut items = items.into_iter();
+	let mut new_peaks = BTreeMap::new();
+
+	for peak in get_peaks(new_mmr_size) {
+		// The post-order of the subtree, the children of a node are above it on the stack
+		let mut stack = vec![(peak, pos_height_in_tree(peak), false)];
+		let mut nodes = vec![];
+
+		while let Some((pos, height, visited)) = stack.pop() {
+			match subtree_kind(pos, height, old_mmr_size) {
+				Subtree::OldPeak => match old_peaks.get(&pos) {
+					Some(old_peak) => nodes.push(old_peak.clone()),
+					None => return false,
+				},
+				Subtree::New => match items.next() {
+					Some(item) => nodes.push(item),
+					None => return false,
+				},
+				Subtree::Mixed if visited => {
+					let (right, left) = match (nodes.pop(), nodes.pop()) {
+						(Some(right), Some(left)) => (right, left),
+						_ => return false,
+					};
+
+					nodes.push(M::merge(&left, &right));
+				}
+				Subtree::Mixed => {
+					stack.push((pos, height, true));
+					stack.push((pos - 1, height - 1, false));
+					stack.push((pos - (1 << height), height - 1, false));
+				}
+			}
+		}
+
+		match nodes.pop() {
+			Some(node) if nodes.is_empty() => {
+				new_peaks.insert(peak, node);
+			}
+			_ => return false,
+		}
+	}
+
+	// Every item must be used
+	items.next().is_none()
+		&& root_of_peaks::<M>(new_mmr_size, new_peaks).as_ref() == Some(&new_root)
+}
+
+/// Key of the node at `pos` in the off-chain indexing storage.
+pub fn offchain_key(pos: u64) -> Vec<u8> {
+	(OFFCHAIN_PREFIX, pos).encode()
+}
+
+/// Key of the leaf of `leaf_index` in the off-chain indexing storage.
+pub fn offchain_leaf_key(leaf_index: u64) -> Vec<u8> {
+	(OFFCHAIN_LEAF_PREFIX, leaf_index).encode()
+}
+
+/// Positions of the peaks of the MMR of `mmr_size`, in ascending order.
+pub fn get_peaks(mmr_size: u64) -> Vec<u64> {
+	let mut peaks = vec![];
+	let mut offset = 0;
+
+	// The MMR is a list of perfect binary trees of decreasing heights
+	while offset < mmr_size {
+		let height = 63 - (mmr_size - offset + 1).leading_zeros();
+		let tree_size = (1 << height) - 1;
+
+		offset += tree_size;
+		peaks.push(offset - 1);
+	}
+
+	peaks
+}
+
+/// Height of the node at `pos`, zero for the leaves.
+pub fn pos_height_in_tree(pos: u64) -> u32 {
+	let all_ones = |n: u64| n != 0 && n.count_zeros() == n.leading_zeros();
+	let jump_left = |n: u64| n - ((1 << (63 - n.leading_zeros())) - 1);
+	// With one based positions, the leftmost node of each height is all ones in binary
+	let mut pos = pos + 1;
+
+	while !all_ones(pos) {
+		pos = jump_left(pos);
+	}
+
+	63 - pos.leading_zeros()
+}
+
+/// Number of leaves of the MMR of `mmr_size`, also the index of the leaf at position `mmr_size`.
+pub fn leaf_count(mmr_size: u64) -> u64 {
+	let mut leaves = 0;
+	let mut offset = 0;
+
+	for peak in get_peaks(mmr_size) {
+		leaves += (peak - offset + 2) / 2;
+		offset = peak + 1;
+	}
+
+	leaves
+}
+
+/// Whether `mmr_size` is the size of an MMR with at least one leaf.
+pub fn is_mmr_size(mmr_size: u64) -> bool {
+	match leaf_count(mmr_size) {
+		0 => false,
+		leaves => leaf_index_to_mmr_size(leaves - 1) == mmr_size,
+	}
+}
+
+/// How the subtree of the node at `pos` overlaps the MMR of `old_mmr_size`.
+enum Subtree {
+	OldPeak,
+	/// No old leaf.
+	New,
+	/// Both old and new leaves.
+	Mixed,
+}
+
+fn subtree_kind(pos: u64, height: u32, old_mmr_size: u64) -> Subtree {
+	// The first position of the subtree
+	let start = pos + 2 - (1 << (height + 1));
+
+	if start >= old_mmr_size {
+		Subtree::New
+	} else if pos < old_mmr_size {
+		// A node of the old MMR whose parent is not, the straddling subtrees end after it
+		Subtree::OldPeak
+	} else {
+		Subtree::Mixed
+	}
+}
+
+/// The root of the MMR of `mmr_size` with these peaks, bagged as `MMR::get_root` does.
+fn root_of_peaks<M>(mmr_size: u64, peaks: BTreeMap<u64, M::Item>) -> Option<M::Item>
+where
+	M: Merge,
+	M::Item: Clone + PartialEq + Debug,
+{
+	MMR::<_, M, _>::new(mmr_size, PeakStore(peaks))
+		.get_root()
+		.ok()
+}
+
+/// A read-only store of the peaks of an MMR.
+struct PeakStore<T>(BTreeMap<u64, T>);
+impl<T: Clone> MMRStore<T> for PeakStore<T> {
+	fn get_elem(&self, pos: u64) -> MMRResult<Option<T>> {
+		Ok(self.0.get(&pos).cloned())
+	}
+
+	fn append(&mut self, _: u64, _: Vec<T>) -> MMRResult<()> {
+		Err(MMRError::InconsistentStore)
+	}
+}
+
+fn abi_uint(value: u64) -> [u8; 32] {
+	let mut word = [0; 32];
+
+	word[24..].copy_from_slice(&value.to_be_bytes());
+
+	word
+}
+
+fn abi_bytes32(hash: &impl AsRef<[u8]>) -> [u8; 32] {
+	let hash = hash.as_ref();
+	let len = hash.len().min(32);
+	let mut word = [0; 32];
+
+	word[..len].copy_from_slice(&hash[..len]);
+
+	word
+}
+
+/// The MMR root of the parent blocks in the digest of `header`.
+pub fn find_parent_mmr_root<H: Header>(header: &H) -> Option<H::Hash> {
+	let id = OpaqueDigestItemId::Other;
+
+	let filter_log = |MerkleMountainRangeRootLog {
+	                      prefix,
+	                      parent_mmr_root,
+	                  }: MerkleMountainRangeRootLog<H::Hash>| match prefix {
+		PARENT_MMR_ROOT_LOG_ID => Some(parent_mmr_root),
+		_ => None,
+	};
+
+	// find the first other digest with the right prefix which converts to
+	// the right kind of mmr root log.
+	header
+		.digest()
+		.convert_first(|l| l.try_to(id).and_then(filter_log))
+}
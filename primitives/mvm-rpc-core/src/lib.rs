@@ -1,54 +0,0 @@
-// Copyright 2015-2020 Parity Technologies (UK) Ltd.
-// This file is part of Frontier.
-
-// Open Ethereum is free software: you can redistribute it and/or modify
-// it under the terms of the GNU General Public License as published by
-// the Free Software Foundation, either version 3 of the License, or
-// (at your option) any later version.
-
-// Open Ethereum is distributed in the hope that it will be useful,
-// but WITHOUT ANY WARRANTY; without even the implied warranty of
-// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
-// GNU General Public License for more details.
-
-// You should have received a copy of the GNU General Public License
-// along with Open Ethereum. If not, see <http://www.gnu.org/licenses/>.
-
-//! RPC types
-
-mod account_info;
-mod block;
-mod block_number;
-mod bytes;
-mod call_request;
-mod filter;
-mod index;
-mod log;
-mod receipt;
-mod sync;
-mod transaction;
-mod transaction_request;
-mod work;
-
-pub mod pubsub;
-
-pub use self::account_info::{
-	AccountInfo, EthAccount, ExtAccountInfo, RecoveredAccount, StorageProof,
-};
-pub use self::block::{Block, BlockTransactions, Header, Rich, RichBlock, RichHeader};
-pub use self::block_number::BlockNumber;
-pub use self::bytes::Bytes;
-pub use self::call_request::CallRequest;
-pub use self::filter::{
-	Filter, FilterAddress, FilterChanges, FilteredParams, Topic, VariadicValue,
-};
-pub use self::index::Index;
-pub use self::log::Log;
-pub use self::receipt::Receipt;
-pub use self::sync::{
-	ChainStatus, EthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo, Peers,
-	PipProtocolInfo, SyncInfo, SyncStatus, TransactionStats,
-};
-pub use self::transaction::{LocalTransactionStatus, RichRawTransaction, Transaction};
-pub use self::transaction_request::TransactionRequest;
-pub use self::work::Work;
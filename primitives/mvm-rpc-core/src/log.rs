@@ -1,46 +0,0 @@
-// Copyright 2015-2020 Parity Technologies (UK) Ltd.
-// This file is part of Frontier.
-
-// Open Ethereum is free software: you can redistribute it and/or modify
-// it under the terms of the GNU General Public License as published by
-// the Free Software Foundation, either version 3 of the License, or
-// (at your option) any later version.
-
-// Open Ethereum is distributed in the hope that it will be useful,
-// but WITHOUT ANY WARRANTY; without even the implied warranty of
-// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
-// GNU General Public License for more details.
-
-// You should have received a copy of the GNU General Public License
-// along with Open Ethereum. If not, see <http://www.gnu.org/licenses/>.
-
-use crate::Bytes;
-use ethereum_types::{H160, H256, U256};
-use serde::Serialize;
-
-/// Log
-#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Log {
-	/// H160
-	pub address: H160,
-	/// Topics
-	pub topics: Vec<H256>,
-	/// Data
-	pub data: Bytes,
-	/// Block Hash
-	pub block_hash: Option<H256>,
-	/// Block Number
-	pub block_number: Option<U256>,
-	/// Transaction Hash
-	pub transaction_hash: Option<H256>,
-	/// Transaction Index
-	pub transaction_index: Option<U256>,
-	/// Log Index in Block
-	pub log_index: Option<U256>,
-	/// Log Index in Transaction
-	pub transaction_log_index: Option<U256>,
-	/// Whether Log Type is Removed (Geth Compatibility Field)
-	#[serde(default)]
-	pub removed: bool,
-}